@@ -0,0 +1,60 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use letter_bounced::{board::Board, dictionary::Dictionary, playable::PlayableIndex, solver::Solver};
+use std::hint::black_box;
+
+/*
+ * Solver benchmarks over a few past puzzles. Timing alone doesn't say why a change helped, so the search counters
+ * for each board are printed once before it's measured.
+ */
+
+const BOARDS: &[&str] = &["yfa,otk,lgw,rni", "vyq,fig,ote,xlu", "jgh,nvy,eid,orp"];
+
+fn board_from_spec(spec: &str) -> Board {
+    Board::from_sides(spec.split(',').map(String::from).collect()).unwrap()
+}
+
+fn solve_boards(c: &mut Criterion) {
+    let dictionary = Dictionary::from_path("data/dictionary.txt").unwrap();
+    let mut group = c.benchmark_group("solve");
+    group.sample_size(10);
+
+    for spec in BOARDS {
+        let board = board_from_spec(spec);
+        let (_, stats) = Solver::new(board.clone(), &dictionary, 500).solve_with_stats(None);
+        eprintln!("{}: {:?}", spec, stats);
+
+        group.bench_with_input(BenchmarkId::from_parameter(spec), &board, |b, board| {
+            b.iter(|| Solver::new(board.clone(), &dictionary, 500).solve().len())
+        });
+    }
+    group.finish();
+}
+
+fn build_solver(c: &mut Criterion) {
+    let dictionary = Dictionary::from_path("data/dictionary.txt").unwrap();
+    let board = board_from_spec(BOARDS[0]);
+    c.bench_function("build solver", |b| {
+        b.iter(|| Solver::new(black_box(board.clone()), &dictionary, 500))
+    });
+}
+
+// What the generator pays to try one letter on a board: filtering the whole dictionary, or updating an index
+fn swap_letter(c: &mut Criterion) {
+    let dictionary = Dictionary::from_path("data/dictionary.txt").unwrap();
+    let board = board_from_spec(BOARDS[0]);
+    let swapped = board_from_spec("yfa,ots,lgw,rni");
+    c.bench_function("swap letter: playable_dictionary", |b| {
+        b.iter(|| black_box(&swapped).playable_dictionary(&dictionary).words().len())
+    });
+
+    let mut index = PlayableIndex::new(board, &dictionary);
+    c.bench_function("swap letter: PlayableIndex", |b| {
+        b.iter(|| {
+            index.replace_letter(1, 2, 's').unwrap();
+            index.replace_letter(1, 2, 'k').unwrap();
+        })
+    });
+}
+
+criterion_group!(benches, solve_boards, build_solver, swap_letter);
+criterion_main!(benches);
@@ -0,0 +1,12 @@
+#!/bin/bash
+set -e
+
+echo "🟢 Building WASM for Node.js..."
+
+# Build the WASM package as a CommonJS module, with the Node API variant
+echo "📦 Building WASM package..."
+wasm-pack build --target nodejs --out-dir pkg-node -- --features node
+
+echo "✅ Node build complete!"
+echo "🚀 WASM files are in 'pkg-node/'"
+echo "💡 Try it: node -e \"const lb = require('./pkg-node'); lb.initialize_dictionary_from_path('data/dictionary.txt'); lb.solve_game(['abc','def','ghi','jkl'], 10).then(console.log)\""
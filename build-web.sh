@@ -7,10 +7,28 @@ echo "🌐 Building WASM for web..."
 echo "📦 Building WASM package..."
 wasm-pack build --target web --out-dir web/svelte-app/src/pkg
 
+# Glue to run the solver in a dedicated Web Worker. Messages which arrive while the wasm loads are queued, then
+# run_worker takes over the message loop; see "Solver Worker" in the README for the protocol.
+echo "🧵 Writing worker glue..."
+cat > web/svelte-app/src/pkg/solver-worker.js <<'JS'
+// Generated by build-web.sh
+import init, { run_worker } from './letter_bounced.js';
+
+const queued = [];
+self.onmessage = (event) => queued.push(event);
+
+init().then(
+  () => run_worker(queued),
+  (error) => self.postMessage({ type: 'ERROR', error: String(error) })
+);
+JS
+
 # Copy dictionary to svelte-app public directory
 echo "📋 Copying dictionary..."
 mkdir -p web/svelte-app/public
 cp data/dictionary.txt web/svelte-app/public/
+# initialize_dictionary also accepts the compressed copy, which is much smaller to download
+gzip -9 -k -f web/svelte-app/public/dictionary.txt
 
 echo "✅ Web build complete!"
 echo "🚀 WASM files are in 'web/svelte-app/src/pkg/'"
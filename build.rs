@@ -0,0 +1,29 @@
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    #[cfg(feature = "embedded-dictionary")]
+    embed_dictionary();
+}
+
+/**
+ * Compress the default dictionary into OUT_DIR, where `Dictionary::embedded` picks it up with `include_bytes!`.
+ * Doing this at build time means we don't have to keep a second, compressed copy of the dictionary in git.
+ */
+#[cfg(feature = "embedded-dictionary")]
+fn embed_dictionary() {
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::fs;
+    use std::io::Write;
+    use std::path::Path;
+
+    const SOURCE: &str = "data/dictionary.txt";
+    println!("cargo:rerun-if-changed={}", SOURCE);
+
+    let text = fs::read(SOURCE).expect("embedded-dictionary feature requires data/dictionary.txt");
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+    encoder.write_all(&text).unwrap();
+    let compressed = encoder.finish().unwrap();
+
+    let out_dir = std::env::var("OUT_DIR").unwrap();
+    fs::write(Path::new(&out_dir).join("dictionary.txt.gz"), compressed).unwrap();
+}
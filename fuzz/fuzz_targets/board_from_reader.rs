@@ -0,0 +1,11 @@
+#![no_main]
+
+use letter_bounced::board::{Board, BoardOptions, GameRules};
+use libfuzzer_sys::fuzz_target;
+
+// Board::from_path reads files through from_reader
+fuzz_target!(|data: &[u8]| {
+    for rules in [GameRules::Standard, GameRules::DuplicateLetters, GameRules::DoubledLetters] {
+        let _ = Board::from_reader(data, BoardOptions::new(rules));
+    }
+});
@@ -0,0 +1,13 @@
+#![no_main]
+
+use letter_bounced::dictionary::Dictionary;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    // Anything accepted must survive a round trip through the encoder
+    if let Ok(dictionary) = Dictionary::from_binary(data) {
+        let bytes = dictionary.to_binary().expect("decoded dictionaries re-encode");
+        let decoded = Dictionary::from_binary(&bytes).expect("re-encoded dictionary is valid");
+        assert_eq!(decoded.words(), dictionary.words());
+    }
+});
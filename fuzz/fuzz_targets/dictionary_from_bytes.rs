@@ -0,0 +1,9 @@
+#![no_main]
+
+use letter_bounced::dictionary::Dictionary;
+use libfuzzer_sys::fuzz_target;
+
+// Text or binary, whatever the web app is sent must parse or fail cleanly
+fuzz_target!(|data: &[u8]| {
+    let _ = Dictionary::from_bytes(data);
+});
@@ -0,0 +1,253 @@
+use crate::board::Board;
+use crate::dictionary::{Dictionary, Word};
+use crate::solver::{Solution, Solver};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+/*
+ * Statistics about a board, for judging how hard a puzzle is or debugging why a dictionary doesn't solve it.
+ */
+
+// Counting solutions stops here; dense boards can have far more three-word solutions than this.
+pub const SOLUTION_COUNT_LIMIT: u16 = u16::MAX;
+
+// `par` samples this many solutions at most; enough to tell a tight board from a loose one
+pub const PAR_SAMPLE_LIMIT: u16 = 1000;
+
+// `par` gives up on boards which need more words than this
+pub const PAR_MAX_WORDS: usize = 6;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LetterStats {
+    pub letter: char,
+    pub words_starting: usize,
+    pub words_containing: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoardAnalysis {
+    pub playable_words: usize,
+    /// One entry per board letter, in board order
+    pub letters: Vec<LetterStats>,
+    /// Digraphs on the board which occur in at least one playable word
+    pub digraphs_used: usize,
+    /// All digraphs the board allows
+    pub digraphs_total: usize,
+    /// Solution counts for 1, 2 and 3 words. A count equal to `SOLUTION_COUNT_LIMIT` was truncated.
+    pub solution_counts: Vec<(usize, usize)>,
+    /// The fewest words which cover every letter, chaining or not; None if some letter is in no playable word
+    pub min_cover_words: Option<usize>,
+    /// One entry per board letter, in board order, as `letter_coverage` works them out
+    pub letter_coverage: Vec<LetterCoverage>,
+}
+
+/// How troublesome one board letter is, for colour-coding boards
+#[derive(Debug, Clone, PartialEq)]
+pub struct LetterCoverage {
+    pub letter: char,
+    pub words_containing: usize,
+    /// Solutions of 2 or 3 words in which only the rarest word covers this letter
+    pub hard_in_solutions: usize,
+}
+
+/// The fewest words a board can be solved in, like the par the NYT shows, and how many ways there are to do it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Par {
+    pub words: usize,
+    /// Solutions of exactly `words` words, counted up to `PAR_SAMPLE_LIMIT`
+    pub solutions: usize,
+}
+
+impl Par {
+    /// Whether `solutions` stopped at `PAR_SAMPLE_LIMIT`, so there may be more
+    pub fn is_sampled(&self) -> bool {
+        self.solutions >= PAR_SAMPLE_LIMIT as usize
+    }
+}
+
+/// Which words `solution_graph` draws
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GraphOptions {
+    /// Leave out playable words which aren't in any solution of up to three words
+    pub solution_words_only: bool,
+}
+
+impl BoardAnalysis {
+    /// The letters which appear in the fewest playable words
+    pub fn bottleneck_letters(&self) -> Vec<char> {
+        let fewest = self.letters.iter().map(|l| l.words_containing).min();
+        self.letters
+            .iter()
+            .filter(|l| Some(l.words_containing) == fewest)
+            .map(|l| l.letter)
+            .collect()
+    }
+
+    /// The board's par, like `par`, but taken from `solution_counts` when there's a solution of up to three words.
+    /// Otherwise the search starts at `min_cover_words`, since no chain can be shorter.
+    pub fn par(&self, board: &Board, dictionary: &Dictionary) -> Option<Par> {
+        if let Some(&(words, solutions)) = self.solution_counts.iter().find(|(_, count)| *count > 0) {
+            return Some(Par { words, solutions: solutions.min(PAR_SAMPLE_LIMIT as usize) });
+        }
+        let shortest = self.min_cover_words?.max(self.solution_counts.len() + 1);
+        search_par(board, dictionary, shortest)
+    }
+
+    /// Whether the board's letters can be covered in fewer words than any solution uses, so it's the chaining that
+    /// makes it hard rather than the letters. Only boards coverable in as many words as `solution_counts` goes up to
+    /// can tell; past that, there may be a solution of `min_cover_words` words which wasn't counted.
+    pub fn is_chaining_hard(&self) -> bool {
+        self.min_cover_words.is_some_and(|cover| {
+            cover <= self.solution_counts.len()
+                && self.solution_counts.iter().filter(|(words, _)| *words <= cover).all(|(_, count)| *count == 0)
+        })
+    }
+}
+
+pub fn analyze(board: &Board, dictionary: &Dictionary) -> BoardAnalysis {
+    let playable = board.playable_dictionary(dictionary);
+
+    let letters = board
+        .sides
+        .iter()
+        .flat_map(|side| side.chars())
+        .map(|letter| LetterStats {
+            letter,
+            words_starting: playable
+                .words()
+                .iter()
+                .filter(|w| w.first_letter == letter)
+                .count(),
+            words_containing: playable
+                .words()
+                .iter()
+                .filter(|w| w.word.contains(letter))
+                .count(),
+        })
+        .collect();
+
+    let solver = Solver::new(board.clone(), dictionary, SOLUTION_COUNT_LIMIT);
+    let solutions: Vec<Vec<Solution>> = (1..=3).map(|n| solver.solve_exact_length(n)).collect();
+    let solution_counts = solutions.iter().enumerate().map(|(i, found)| (i + 1, found.len())).collect();
+    let min_cover_words = Solver::new(board.clone(), dictionary, 1).minimal_covers().first().map(Vec::len);
+    let letter_coverage = coverage_in(board, &playable, solutions[1..].iter().flatten());
+
+    BoardAnalysis {
+        playable_words: playable.words().len(),
+        letters,
+        digraphs_used: playable.digraphs.len(),
+        digraphs_total: board.digraphs.len(),
+        solution_counts,
+        min_cover_words,
+        letter_coverage,
+    }
+}
+
+/**
+ * The board's par: the fewest words any solution uses, found by searching each length in turn, with a count of the
+ * solutions of that length. None if no solution of up to `PAR_MAX_WORDS` words exists.
+ */
+pub fn par(board: &Board, dictionary: &Dictionary) -> Option<Par> {
+    if board.is_solvable(dictionary).is_dead() {
+        return None;
+    }
+    search_par(board, dictionary, 1)
+}
+
+// Par, searching each length from `shortest` up
+fn search_par(board: &Board, dictionary: &Dictionary, shortest: usize) -> Option<Par> {
+    let solver = Solver::new(board.clone(), dictionary, PAR_SAMPLE_LIMIT);
+    (shortest..=PAR_MAX_WORDS).find_map(|words| {
+        let solutions = solver.solve_exact_length(words).len();
+        (solutions > 0).then_some(Par { words, solutions })
+    })
+}
+
+/**
+ * For each board letter, how many playable words contain it, and in how many solutions it was the "hard" letter.
+ * A solution's hard letters are the ones only its rarest word covers: they're why the solver had to reach for that
+ * word. One-word solutions are left out, since a pangram covers every letter alone.
+ */
+pub fn letter_coverage(board: &Board, dictionary: &Dictionary) -> Vec<LetterCoverage> {
+    let playable = board.playable_dictionary(dictionary);
+    let solver = Solver::new(board.clone(), dictionary, SOLUTION_COUNT_LIMIT);
+    let solutions: Vec<Solution> = (2..=3).flat_map(|n| solver.solve_exact_length(n)).collect();
+    coverage_in(board, &playable, &solutions)
+}
+
+// `letter_coverage`, given the board's playable words and its solutions of two and three words
+fn coverage_in<'a>(
+    board: &Board,
+    playable: &Dictionary,
+    solutions: impl IntoIterator<Item = &'a Solution>,
+) -> Vec<LetterCoverage> {
+    let mut hard_counts: HashMap<char, usize> = HashMap::new();
+    for solution in solutions {
+        let Some(rarest) = solution.words.iter().min_by_key(|w| w.frequency) else {
+            continue;
+        };
+        let others: HashSet<char> = solution
+            .words
+            .iter()
+            .filter(|w| !std::ptr::eq(*w, rarest))
+            .flat_map(|w| w.word.chars())
+            .collect();
+        for letter in rarest.word.chars().collect::<HashSet<_>>() {
+            if !others.contains(&letter) {
+                *hard_counts.entry(letter).or_default() += 1;
+            }
+        }
+    }
+
+    board
+        .sides
+        .iter()
+        .flat_map(|side| side.chars())
+        .map(|letter| LetterCoverage {
+            letter,
+            words_containing: playable.words().iter().filter(|w| w.word.contains(letter)).count(),
+            hard_in_solutions: hard_counts.get(&letter).copied().unwrap_or(0),
+        })
+        .collect()
+}
+
+/**
+ * A Graphviz DOT graph of the board's playable words, with an edge from each word to every word which can follow it.
+ * Words in solutions of up to three words are drawn bold, so a hard board shows up as a big tangle of words with
+ * only a few bold paths through it. Render it with e.g. `dot -Tsvg board.dot > board.svg`.
+ */
+pub fn solution_graph(board: &Board, dictionary: &Dictionary, options: &GraphOptions) -> String {
+    let playable = board.playable_dictionary(dictionary);
+    let solver = Solver::new(board.clone(), dictionary, SOLUTION_COUNT_LIMIT);
+    let solution_words: HashSet<String> = (1..=3)
+        .flat_map(|n| solver.solve_exact_length(n))
+        .flat_map(|solution| solution.words.into_iter().map(|w| w.word))
+        .collect();
+
+    let words: Vec<&Word> = playable
+        .words()
+        .iter()
+        .filter(|w| !options.solution_words_only || solution_words.contains(&w.word))
+        .collect();
+    let mut by_first_letter: HashMap<char, Vec<&Word>> = HashMap::new();
+    for word in &words {
+        by_first_letter.entry(word.first_letter).or_default().push(word);
+    }
+
+    // Dictionary words are plain lowercase letters, so they never need escaping inside quotes
+    let mut dot = format!("digraph \"{}\" {{\n    node [shape=box];\n", board.sides.join(","));
+    for word in &words {
+        let style = if !options.solution_words_only && solution_words.contains(&word.word) { ", style=bold" } else { "" };
+        let _ = writeln!(dot, "    \"{}\" [tooltip=\"frequency {}\"{}];", word.word, word.frequency, style);
+    }
+    for word in &words {
+        for next in by_first_letter.get(&word.last_letter).into_iter().flatten() {
+            // Playing a word twice never helps
+            if next.word != word.word {
+                let _ = writeln!(dot, "    \"{}\" -> \"{}\";", word.word, next.word);
+            }
+        }
+    }
+    dot.push_str("}\n");
+    dot
+}
@@ -1,60 +1,371 @@
-use crate::dictionary::Dictionary;
-use std::collections::{HashMap, HashSet};
+use crate::dawg::Dawg;
+use crate::dictionary::{Dictionary, Digraph, Word};
+use crate::error::{LetterBouncedError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fs::File;
-use std::io::{self, BufRead, BufReader};
+use std::io::{BufRead, BufReader};
 use std::path::Path;
+use tracing::{debug_span, field};
 
-const SIDES_DISPLAY: &[&str] = &["top", "right", "left", "bottom"];
+/// What the sides are called, in the order they're given
+pub const SIDES_DISPLAY: &[&str] = &["top", "right", "left", "bottom"];
+
+/// Which version of the rules a board is played under. Clones, homemade and foreign-language variants relax the
+/// NYT's, and these decide both which boards are valid and which letters can follow each other.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum GameRules {
+    /// The NYT puzzle: each letter is on the board once, and consecutive letters must be on different sides
+    #[default]
+    Standard,
+    /// Letters may appear on the board more than once, possibly on different sides. A word can only use a letter
+    /// twice in a row by moving between copies on different sides.
+    DuplicateLetters,
+    /// Like `DuplicateLetters`, and any letter may also follow itself, as in BUTT, by playing it twice
+    DoubledLetters,
+}
+
+impl GameRules {
+    pub fn allows_duplicate_letters(self) -> bool {
+        self != GameRules::Standard
+    }
+
+    pub fn allows_doubled_letters(self) -> bool {
+        self == GameRules::DoubledLetters
+    }
+
+    /// Whether a word's spelling could be played under these rules on some board. Under the standard rules no
+    /// letter can follow itself, since it would have to be on two sides; the variants allow it.
+    pub fn allows_word(self, word: &str) -> bool {
+        self.allows_duplicate_letters() || word.as_bytes().windows(2).all(|pair| pair[0] != pair[1])
+    }
+}
+
+impl std::str::FromStr for GameRules {
+    type Err = LetterBouncedError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "standard" | "nyt" => Ok(GameRules::Standard),
+            "duplicate-letters" => Ok(GameRules::DuplicateLetters),
+            "doubled-letters" => Ok(GameRules::DoubledLetters),
+            _ => Err(LetterBouncedError::Board(format!(
+                "Unknown rules '{}'. Choose standard, duplicate-letters or doubled-letters",
+                s
+            ))),
+        }
+    }
+}
+
+/// Settings for building a board. The defaults match the NYT puzzle.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct BoardOptions {
+    pub rules: GameRules,
+    /// The old way to ask for `GameRules::DuplicateLetters`, still honoured when `rules` is left standard
+    #[deprecated(note = "set `rules` to `GameRules::DuplicateLetters` instead")]
+    pub allow_duplicate_letters: bool,
+}
+
+impl BoardOptions {
+    pub fn new(rules: GameRules) -> Self {
+        BoardOptions { rules, ..Default::default() }
+    }
+
+    /// The rules boards are built under, taking the deprecated `allow_duplicate_letters` into account
+    #[allow(deprecated)]
+    pub fn game_rules(&self) -> GameRules {
+        match self.rules {
+            GameRules::Standard if self.allow_duplicate_letters => GameRules::DuplicateLetters,
+            rules => rules,
+        }
+    }
+}
+
+/// Appearance of boards drawn by `render_svg`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SvgOptions {
+    /// Width and height of the image in pixels
+    pub size: u32,
+    pub foreground: String,
+    pub background: String,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        SvgOptions {
+            size: 400,
+            foreground: "black".to_string(),
+            background: "white".to_string(),
+        }
+    }
+}
+
+/// A board spec tidied up by `Board::normalize_spec`: its sides, and what had to be done to get them
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NormalizedSpec {
+    pub sides: Vec<String>,
+    /// Notes for whoever typed the spec, like "Ignored '.'". Empty if it only needed lowercasing.
+    pub changes: Vec<String>,
+}
+
+/// What `Board::is_solvable` found. This only catches the quick, explainable ways a board can be dead; a board
+/// which passes can still turn out to have no solutions.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SolvabilityReport {
+    /// Letters which appear in no playable word
+    pub unplayable_letters: Vec<char>,
+    /// Letters which only appear in words that no playable word can follow, when no one word has them all. They
+    /// would all have to be in the answer's last word. Empty unless that's what kills the board.
+    pub stranded_letters: Vec<char>,
+}
+
+impl SolvabilityReport {
+    pub fn is_dead(&self) -> bool {
+        !self.unplayable_letters.is_empty() || !self.stranded_letters.is_empty()
+    }
+
+    /// Why the board has no solutions, or None if it may have some
+    pub fn reason(&self) -> Option<String> {
+        if !self.unplayable_letters.is_empty() {
+            let letters = &self.unplayable_letters;
+            Some(format!(
+                "This board has no solutions because {} only {} in unplayable words",
+                list_letters(letters),
+                if letters.len() == 1 { "appears" } else { "appear" }
+            ))
+        } else if !self.stranded_letters.is_empty() {
+            Some(format!(
+                "This board has no solutions because {} only appear in words which nothing can follow, and no \
+                 one word has them all",
+                list_letters(&self.stranded_letters)
+            ))
+        } else {
+            None
+        }
+    }
+
+    /// Ok if the board may have solutions, otherwise an `UnsolvableBoard` error with the reason
+    pub fn into_result(self) -> Result<()> {
+        match self.reason() {
+            Some(reason) => Err(LetterBouncedError::UnsolvableBoard(reason)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Why `Board::is_word_playable` rejected a word. Positions count letters from 0.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum PlayabilityError {
+    #[error("The word is empty")]
+    Empty,
+
+    #[error("{} isn't on the board", .letter.to_ascii_uppercase())]
+    LetterNotOnBoard { letter: char, position: usize },
+
+    /// A letter following itself, which only `GameRules::DoubledLetters`, or a second copy of the letter on
+    /// another side, allows
+    #[error("{} can't follow itself", .letter.to_ascii_uppercase())]
+    DoubledLetter { letter: char, position: usize },
+
+    /// Two different letters in a row which are on the same side, or whose copies on other sides the letters
+    /// before them can't reach; `position` is the first of them
+    #[error("{}{} are on the same side", .first.to_ascii_uppercase(), .second.to_ascii_uppercase())]
+    SameSide { first: char, second: char, position: usize },
+}
+
+impl PlayabilityError {
+    /// Which letters of the word are at fault, for highlighting them as the player types
+    pub fn letters(&self) -> std::ops::Range<usize> {
+        match *self {
+            PlayabilityError::Empty => 0..0,
+            PlayabilityError::LetterNotOnBoard { position, .. } => position..position + 1,
+            PlayabilityError::DoubledLetter { position, .. } | PlayabilityError::SameSide { position, .. } => {
+                position..position + 2
+            }
+        }
+    }
+}
+
+// "Q", "Q and X", "J, Q and X"
+fn list_letters(letters: &[char]) -> String {
+    let upper: Vec<String> = letters.iter().map(|c| c.to_ascii_uppercase().to_string()).collect();
+    match upper.split_last() {
+        Some((last, rest)) if !rest.is_empty() => format!("{} and {}", rest.join(", "), last),
+        _ => upper.concat(),
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct Board {
     pub sides: Vec<String>,
-    pub digraphs: HashSet<String>,
+    pub digraphs: HashSet<Digraph>,
+    pub rules: GameRules,
 }
 
 impl Board {
-    pub fn from_sides(sides: Vec<String>) -> io::Result<Self> {
+    pub fn from_sides(sides: Vec<String>) -> Result<Self> {
+        Self::from_sides_with_options(sides, BoardOptions::default())
+    }
+
+    pub fn from_sides_with_options(sides: Vec<String>, options: BoardOptions) -> Result<Self> {
         Self::validate_sides_structure(&sides)?;
-        Self::validate_sides_content(&sides)?;
+        Self::validate_sides_content(&sides, &options)?;
 
-        let digraphs = Self::playable_digraphs(&sides);
-        let game = Board { sides, digraphs };
+        let digraphs = Self::playable_digraphs(&sides, options.game_rules());
+        let game = Board {
+            sides,
+            digraphs,
+            rules: options.game_rules(),
+        };
 
         Ok(game)
     }
 
-    pub fn from_path<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_path_with_options(path, BoardOptions::default())
+    }
+
+    pub fn from_path_with_options<P: AsRef<Path>>(path: P, options: BoardOptions) -> Result<Self> {
         let file = File::open(path)?;
-        let reader = BufReader::new(file);
-        let sides: Vec<String> = reader
+        Self::from_reader(BufReader::new(file), options)
+    }
+
+    /// Read a board file's contents: one side per line. Text which isn't UTF-8 is an error.
+    pub fn from_reader<R: BufRead>(reader: R, options: BoardOptions) -> Result<Self> {
+        let sides = reader
             .lines()
-            .map_while(Result::ok)
-            .map(|s| s.to_lowercase())
+            .map(|line| line.map(|s| s.to_lowercase()))
+            .collect::<std::io::Result<Vec<String>>>()?;
+
+        Self::from_sides_with_options(sides, options)
+    }
+
+    /// Parse a board typed as text, like `yfa,otk,lgw,rni`. See `normalize_spec` for the other ways it may be
+    /// written.
+    pub fn from_spec(spec: &str) -> Result<Self> {
+        Self::from_spec_with_options(spec, BoardOptions::default())
+    }
+
+    pub fn from_spec_with_options(spec: &str, options: BoardOptions) -> Result<Self> {
+        Self::from_sides_with_options(Self::parse_spec(spec)?, options)
+    }
+
+    /// Split a board spec into lowercase sides, without checking them against the game rules
+    pub fn parse_spec(spec: &str) -> Result<Vec<String>> {
+        Ok(Self::normalize_spec(spec)?.sides)
+    }
+
+    /// Split a board spec into lowercase sides, forgiving the ways people type and paste boards. Sides may be
+    /// separated by commas, semicolons, dashes, slashes, bars or whitespace, in any mix, and letters may be either
+    /// case. Other punctuation, like quotes or full stops, is dropped. Letters with no separators at all, like
+    /// `YFAOTKLGWRNI`, are split evenly into the 4 sides. Anything else, like a digit, is an error, since it's
+    /// more likely a typo for a letter than something to drop.
+    pub fn normalize_spec(spec: &str) -> Result<NormalizedSpec> {
+        let is_separator = |ch: char| matches!(ch, ',' | ';' | '-' | '/' | '|' | '\\') || ch.is_whitespace();
+        if let Some(ch) = spec
+            .chars()
+            .find(|&ch| !ch.is_ascii_alphabetic() && !ch.is_ascii_punctuation() && !is_separator(ch))
+        {
+            return Err(LetterBouncedError::Board(format!(
+                "Invalid character '{}' in board specification. Use letters, with the sides separated by commas, e.g. yfa,otk,lgw,rni",
+                ch
+            )));
+        }
+
+        let mut changes = Vec::new();
+        let mut ignored: Vec<char> = Vec::new();
+        for ch in spec.chars().filter(|&ch| ch.is_ascii_punctuation() && !is_separator(ch)) {
+            if !ignored.contains(&ch) {
+                ignored.push(ch);
+            }
+        }
+        if !ignored.is_empty() {
+            let list: Vec<String> = ignored.iter().map(|ch| format!("'{}'", ch)).collect();
+            changes.push(format!("Ignored {}", list.join(", ")));
+        }
+
+        let mut sides: Vec<String> = spec
+            .split(is_separator)
+            .map(|side| side.chars().filter(char::is_ascii_alphabetic).collect::<String>().to_lowercase())
+            .filter(|side| !side.is_empty())
             .collect();
+        if sides.is_empty() {
+            return Err(LetterBouncedError::Board("Board specification is empty".to_string()));
+        }
+        if let [letters] = sides.as_slice() {
+            if !letters.len().is_multiple_of(4) {
+                return Err(LetterBouncedError::Board(format!(
+                    "{} letters can't be split evenly into 4 sides. Separate the sides with commas, e.g. yfa,otk,lgw,rni",
+                    letters.len()
+                )));
+            }
+            let side_len = letters.len() / 4;
+            changes.push(format!("Split the {} letters into 4 sides of {}", letters.len(), side_len));
+            sides = (0..4).map(|i| letters[i * side_len..(i + 1) * side_len].to_string()).collect();
+        }
+        Ok(NormalizedSpec { sides, changes })
+    }
+
+    /// The compact form used in links, sides joined by dashes: `otx-gmi-fle-aun`
+    pub fn to_spec_string(&self) -> String {
+        self.sides.join("-")
+    }
+
+    /// Parse the compact form made by `to_spec_string`
+    pub fn from_spec_string(spec: &str) -> Result<Self> {
+        Self::from_spec(spec)
+    }
 
-        Self::from_sides(sides)
+    /// A JSON object holding the sides, e.g. `{"sides":["otx","gmi","fle","aun"]}`, for config files
+    pub fn to_json(&self) -> String {
+        serde_json::json!({ "sides": self.sides }).to_string()
     }
 
-    fn validate_sides_structure(sides: &[String]) -> io::Result<()> {
+    /// Parse the JSON made by `to_json`
+    pub fn from_json(json: &str) -> Result<Self> {
+        #[derive(serde::Deserialize)]
+        struct BoardJson {
+            sides: Vec<String>,
+        }
+
+        let parsed: BoardJson = serde_json::from_str(json)
+            .map_err(|e| LetterBouncedError::Board(format!("Invalid board JSON: {}", e)))?;
+        Self::from_sides(parsed.sides.into_iter().map(|side| side.to_lowercase()).collect())
+    }
+
+    /// Check the first few sides of a board, as they're entered one at a time: everything `from_sides` checks
+    /// except that there are 4 of them, so a mistake can be pointed out as soon as it's made
+    pub fn check_partial_sides(sides: &[String], options: &BoardOptions) -> Result<()> {
+        if sides.len() > 4 {
+            return Err(LetterBouncedError::Board(format!(
+                "Game must contain exactly 4 sides, found {}",
+                sides.len()
+            )));
+        }
+        Self::validate_side_lengths(sides)?;
+        Self::validate_sides_content(sides, options)
+    }
+
+    fn validate_sides_structure(sides: &[String]) -> Result<()> {
         if sides.len() != 4 {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                format!("Game must contain exactly 4 sides, found {}", sides.len()),
-            ));
+            return Err(LetterBouncedError::Board(format!(
+                "Game must contain exactly 4 sides, found {}",
+                sides.len()
+            )));
         }
+        Self::validate_side_lengths(sides)
+    }
 
+    fn validate_side_lengths(sides: &[String]) -> Result<()> {
         if sides.iter().any(|side| side.is_empty()) {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "Empty sides are not allowed",
-            ));
+            return Err(LetterBouncedError::Board("Empty sides are not allowed".to_string()));
         }
 
-        let first_len = sides[0].len();
+        let first_len = sides.first().map_or(0, String::len);
         for (i, side) in sides.iter().enumerate() {
             if side.len() != first_len {
-                return Err(io::Error::new(
-                    io::ErrorKind::InvalidData,
+                return Err(LetterBouncedError::Board(
                     format!("All sides must have the same length. The {} side has length {} but the {} side has length {}", 
                         SIDES_DISPLAY[0], first_len, SIDES_DISPLAY[i], side.len())
                 ));
@@ -64,19 +375,22 @@ impl Board {
         Ok(())
     }
 
-    fn validate_sides_content(sides: &[String]) -> io::Result<()> {
+    fn validate_sides_content(sides: &[String], options: &BoardOptions) -> Result<()> {
         let mut seen_chars: HashMap<char, usize> = HashMap::new();
 
         for (side_num, side) in sides.iter().enumerate() {
             for c in side.chars() {
                 if !c.is_ascii_lowercase() {
-                    return Err(io::Error::new(
-                        io::ErrorKind::InvalidData,
+                    return Err(LetterBouncedError::Board(
                         format!("Invalid character '{}' on the {} side. Only lowercase ASCII letters are allowed", 
                             c, SIDES_DISPLAY[side_num])
                     ));
                 }
 
+                if options.game_rules().allows_duplicate_letters() {
+                    continue;
+                }
+
                 if let Some(previous_side) = seen_chars.insert(c, side_num) {
                     let error = if previous_side == side_num {
                         format!("Duplicate letter '{}' found on the {} side", c, SIDES_DISPLAY[side_num])
@@ -86,7 +400,7 @@ impl Board {
                             c, SIDES_DISPLAY[previous_side], SIDES_DISPLAY[side_num]
                         )
                     };
-                    return Err(io::Error::new(io::ErrorKind::InvalidData, error));
+                    return Err(LetterBouncedError::Board(error));
                 }
             }
         }
@@ -94,15 +408,17 @@ impl Board {
         Ok(())
     }
 
-    fn playable_digraphs(sides: &[String]) -> HashSet<String> {
+    fn playable_digraphs(sides: &[String], rules: GameRules) -> HashSet<Digraph> {
         let mut digraphs = HashSet::new();
+        if rules.allows_doubled_letters() {
+            digraphs.extend(sides.iter().flat_map(|side| side.chars()).map(|c| Digraph::new(c, c)));
+        }
         for (i, side) in sides.iter().enumerate() {
             for c1 in side.chars() {
                 for (j, other_side) in sides.iter().enumerate() {
                     if i != j {
                         for c2 in other_side.chars() {
-                            let digraph = format!("{}{}", c1, c2);
-                            digraphs.insert(digraph);
+                            digraphs.insert(Digraph::new(c1, c2));
                         }
                     }
                 }
@@ -111,19 +427,271 @@ impl Board {
         digraphs
     }
 
+    /**
+     * Whether `word` can be played on the board, whatever the dictionary says, and if not, the first thing wrong
+     * with it. Cheap enough to run on every keystroke: it only looks at the word's own letters.
+     */
+    pub fn is_word_playable(&self, word: &str) -> std::result::Result<(), PlayabilityError> {
+        let letters: Vec<char> = word.to_lowercase().chars().collect();
+        if letters.is_empty() {
+            return Err(PlayabilityError::Empty);
+        }
+        self.trace(&letters).map(|_| ())
+    }
+
+    /// Where each of `letters` is played from, as (side, position along that side), typing them in one run. When
+    /// a letter is on the board more than once, the first copy that lets the rest be played is used. A doubled
+    /// letter played twice in place, as the `DoubledLetters` rules allow, appears twice. Returns None if they
+    /// can't be played.
+    pub fn letter_path(&self, letters: &str) -> Option<Vec<(usize, usize)>> {
+        self.trace(&letters.chars().collect::<Vec<_>>()).ok()
+    }
+
+    // Whether some letter is on more than one side. Only then can two neighbouring pairs of letters each be
+    // playable while needing different copies of the letter between them, so only then is checking a word's
+    // digraphs against the board's not enough.
+    pub(crate) fn has_letters_on_several_sides(&self) -> bool {
+        let mut seen: HashMap<char, usize> = HashMap::new();
+        self.sides.iter().enumerate().any(|(side_num, side)| {
+            side.chars().any(|c| *seen.entry(c).or_insert(side_num) != side_num)
+        })
+    }
+
+    // Every place `letter` is on the board, as (side, position along that side)
+    fn copies_of(&self, letter: char) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.sides.iter().enumerate().flat_map(move |(side_num, side)| {
+            side.chars().enumerate().filter(move |&(_, c)| c == letter).map(move |(position, _)| (side_num, position))
+        })
+    }
+
+    // Follows every copy each letter could be played from, then walks back from the first copy of the last letter
+    // that was reached
+    fn trace(&self, letters: &[char]) -> std::result::Result<Vec<(usize, usize)>, PlayabilityError> {
+        // Each place a letter can be played from, with the index of the place before it in the previous entry
+        let mut reachable: Vec<Vec<((usize, usize), usize)>> = Vec::with_capacity(letters.len());
+        for (position, &letter) in letters.iter().enumerate() {
+            let copies: Vec<(usize, usize)> = self.copies_of(letter).collect();
+            if copies.is_empty() {
+                return Err(PlayabilityError::LetterNotOnBoard { letter, position });
+            }
+            let Some(previous) = reachable.last() else {
+                reachable.push(copies.into_iter().map(|copy| (copy, 0)).collect());
+                continue;
+            };
+            let doubled = self.rules.allows_doubled_letters() && letters[position - 1] == letter;
+            let mut places: Vec<((usize, usize), usize)> = Vec::new();
+            for (from, &(place, _)) in previous.iter().enumerate() {
+                let in_place = doubled.then_some(place);
+                let other_sides = copies.iter().copied().filter(|&(side, _)| side != place.0);
+                for next in in_place.into_iter().chain(other_sides) {
+                    if !places.iter().any(|&(found, _)| found == next) {
+                        places.push((next, from));
+                    }
+                }
+            }
+            if places.is_empty() {
+                let first = letters[position - 1];
+                return Err(if first == letter {
+                    PlayabilityError::DoubledLetter { letter, position: position - 1 }
+                } else {
+                    PlayabilityError::SameSide { first, second: letter, position: position - 1 }
+                });
+            }
+            reachable.push(places);
+        }
+
+        let mut path = Vec::with_capacity(letters.len());
+        let mut index = 0;
+        for places in reachable.iter().rev() {
+            let (place, from) = places[index];
+            path.push(place);
+            index = from;
+        }
+        path.reverse();
+        Ok(path)
+    }
+
+    /**
+     * The letters which can follow `prefix`, typed so far, such that it can still become a playable word in
+     * `words`, for a typing assistant to suggest or a keyboard to enable. Empty if `prefix` itself can't be played.
+     *
+     * `words` is a prefix index of the dictionary, built once with `Dictionary::to_dawg`. Building it from the
+     * board's `playable_dictionary` instead makes it much smaller, and the answers are the same.
+     */
+    pub fn playable_continuations(&self, prefix: &str, words: &Dawg) -> BTreeSet<char> {
+        let prefix = prefix.to_lowercase();
+        if !prefix.is_empty() && self.is_word_playable(&prefix).is_err() {
+            return BTreeSet::new();
+        }
+        let on_board = |letter: &char| self.sides.iter().any(|side| side.contains(*letter));
+        let step = |a: u8, b: u8| self.digraphs.contains(&Digraph::new(a as char, b as char));
+        words.continuations(&prefix, step).into_iter().filter(on_board).collect()
+    }
+
     pub fn playable_dictionary(&self, dictionary: &Dictionary) -> Dictionary {
+        let span = debug_span!("playable_words", board = %self.sides.join(","), playable = field::Empty).entered();
         // Eliminate any digraphs on this board which are totally impossible in the whole dictionary, e.g. 'vz', 'zq'
-        let usable_digraphs: HashSet<&String> =
+        let usable_digraphs: HashSet<&Digraph> =
             self.digraphs.intersection(&dictionary.digraphs).collect();
 
         // Then cut it down to words which are playable on this board
+        let traced = self.has_letters_on_several_sides();
         let playable_words = dictionary
-            .words
+            .words()
             .iter()
             .filter(|word| word.digraphs.iter().all(|d| usable_digraphs.contains(d)))
+            .filter(|word| !traced || self.is_word_playable(&word.word).is_ok())
             .cloned()
             .collect();
 
-        Dictionary::from_words(playable_words)
+        let playable = Dictionary::from_words(playable_words);
+        span.record("playable", playable.words().len());
+        playable
+    }
+
+    /// Quickly look for reasons the board can't be solved with this dictionary, without searching: letters in no
+    /// playable word, or letters stranded in words which can only end an answer.
+    pub fn is_solvable(&self, dictionary: &Dictionary) -> SolvabilityReport {
+        let playable = self.playable_dictionary(dictionary);
+        let mut letters: Vec<char> = self.sides.iter().flat_map(|side| side.chars()).collect();
+        letters.sort_unstable();
+        letters.dedup();
+
+        let unplayable_letters: Vec<char> = letters
+            .iter()
+            .copied()
+            .filter(|&letter| !playable.words().iter().any(|w| w.word.contains(letter)))
+            .collect();
+        if !unplayable_letters.is_empty() {
+            return SolvabilityReport {
+                unplayable_letters,
+                ..Default::default()
+            };
+        }
+
+        // A word whose last letter starts no playable word can only end an answer
+        let first_letters: HashSet<char> = playable.words().iter().map(|w| w.first_letter).collect();
+        let is_final = |word: &Word| !first_letters.contains(&word.last_letter);
+        let only_final: Vec<char> = letters
+            .into_iter()
+            .filter(|&letter| playable.words().iter().filter(|w| w.word.contains(letter)).all(is_final))
+            .collect();
+        let coverable = only_final.is_empty()
+            || playable
+                .words()
+                .iter()
+                .any(|w| is_final(w) && only_final.iter().all(|&letter| w.word.contains(letter)));
+
+        SolvabilityReport {
+            stranded_letters: if coverable { Vec::new() } else { only_final },
+            ..Default::default()
+        }
+    }
+
+    /// Where a letter sits on the standard square layout, with the square's corners at (0, 0) and (1, 1) and y
+    /// increasing downwards. Letters are spaced evenly along each side, away from the corners.
+    pub fn letter_point(&self, side: usize, position: usize) -> (f64, f64) {
+        let count = self.sides[side].len() as f64;
+        let along = (position as f64 + 1.0) / (count + 1.0);
+        // Following the side order in SIDES_DISPLAY
+        match side {
+            0 => (along, 0.0),
+            1 => (1.0, along),
+            2 => (0.0, along),
+            _ => (along, 1.0),
+        }
+    }
+
+    /// Draw the board as a labeled square, for checking it was typed in correctly
+    ///
+    /// ```text
+    ///             top
+    ///          A   B   C
+    ///        +-----------+
+    ///      G |           | D
+    /// left H |           | E right
+    ///      I |           | F
+    ///        +-----------+
+    ///          J   K   L
+    ///           bottom
+    /// ```
+    pub fn render(&self) -> String {
+        let [top, right, left, bottom] = [0, 1, 2, 3].map(|i| self.sides[i].to_uppercase().chars().collect::<Vec<_>>());
+        let side_len = top.len();
+        let inner_width = 4 * side_len - 1;
+        let label_width = SIDES_DISPLAY[2].len();
+        // Columns taken by the left label, the left letters and the space before the box
+        let margin = " ".repeat(label_width + 3);
+        let centered = |text: &str| format!("{}{:^width$}", margin, text, width = inner_width + 2);
+        let letter_row = |letters: &[char]| {
+            let spaced: Vec<String> = letters.iter().map(|c| c.to_string()).collect();
+            format!("{}  {}", margin, spaced.join("   "))
+        };
+        let border = format!("{}+{}+", margin, "-".repeat(inner_width));
+
+        let mut lines = vec![centered(SIDES_DISPLAY[0]), letter_row(&top), border.clone()];
+        for row in 0..side_len {
+            let middle = row == side_len / 2;
+            lines.push(format!(
+                "{:>label_width$} {} |{}| {}{}",
+                if middle { SIDES_DISPLAY[2] } else { "" },
+                left[row],
+                " ".repeat(inner_width),
+                right[row],
+                if middle { format!(" {}", SIDES_DISPLAY[1]) } else { String::new() },
+            ));
+        }
+        lines.push(border);
+        lines.push(letter_row(&bottom));
+        lines.push(centered(SIDES_DISPLAY[3]));
+
+        lines.iter().map(|line| line.trim_end()).collect::<Vec<_>>().join("\n")
     }
 }
+
+/// Draw the board as an SVG image in the style of the NYT puzzle: a square with a dot for each letter, and
+/// the letters just outside it.
+pub fn render_svg(board: &Board, options: &SvgOptions) -> String {
+    let size = options.size as f64;
+    let square = size * 0.6;
+    let corner = (size - square) / 2.0;
+    let dot_radius = size / 50.0;
+    let letter_offset = size / 12.0;
+    let font_size = size / 14.0;
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{size}\" height=\"{size}\" viewBox=\"0 0 {size} {size}\">\n"
+    );
+    svg.push_str(&format!(
+        "  <rect width=\"{size}\" height=\"{size}\" fill=\"{}\"/>\n",
+        options.background
+    ));
+    svg.push_str(&format!(
+        "  <rect x=\"{corner:.1}\" y=\"{corner:.1}\" width=\"{square:.1}\" height=\"{square:.1}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{:.1}\"/>\n",
+        options.foreground,
+        size / 100.0
+    ));
+
+    for (side_num, side) in board.sides.iter().enumerate() {
+        for (i, letter) in side.to_uppercase().chars().enumerate() {
+            let (point_x, point_y) = board.letter_point(side_num, i);
+            let (x, y) = (corner + square * point_x, corner + square * point_y);
+            // Letters sit outside the square, pushed away from whichever side they're on
+            let (letter_x, letter_y) = (
+                x + letter_offset * (2.0 * point_x - 1.0).trunc(),
+                y + letter_offset * (2.0 * point_y - 1.0).trunc(),
+            );
+            svg.push_str(&format!(
+                "  <circle cx=\"{x:.1}\" cy=\"{y:.1}\" r=\"{dot_radius:.1}\" fill=\"{}\" stroke=\"{}\"/>\n",
+                options.background, options.foreground
+            ));
+            svg.push_str(&format!(
+                "  <text x=\"{letter_x:.1}\" y=\"{letter_y:.1}\" font-family=\"sans-serif\" font-size=\"{font_size:.1}\" text-anchor=\"middle\" dominant-baseline=\"central\" fill=\"{}\">{}</text>\n",
+                options.foreground, letter
+            ));
+        }
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
@@ -0,0 +1,269 @@
+use crate::board::Board;
+use crate::dictionary::{Dictionary, Word};
+use crate::solver::{SolveOutcome, Solution, Solver, SolverConfig};
+use tracing::warn;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
+use std::path::PathBuf;
+
+/*
+ * Remember solves, so solving the same board again is instant. People re-solve the daily board over and over while
+ * trying different settings, and most of those solves have been done before.
+ *
+ * A solve is keyed by everything that can change its answer: the board, the solver config and the dictionary.
+ * Boards which differ only in the order of their sides, or of letters within a side, have the same solutions, so
+ * they share a key. The dictionary goes in as its `fingerprint`, a hash of its words, frequencies and tags, so
+ * editing it makes old entries miss rather than go stale. The dictionary remembers it, so only the first solve pays
+ * for hashing every word.
+ *
+ * Recent solves are kept in memory, least recently used dropped first. With a directory, they're also written
+ * there as one small JSON file each, holding the solutions as words; reading one back looks the words up in the
+ * dictionary again. A timeout doesn't change what a finished search finds, so it isn't part of the key, and
+ * searches which timed out aren't cached at all.
+ */
+
+// What a cache file holds. The key is kept so a collision in the file name hash isn't taken for a hit.
+#[derive(Serialize, Deserialize)]
+struct CacheFile {
+    key: String,
+    solutions: Vec<String>,
+}
+
+pub struct SolveCache {
+    capacity: usize,
+    entries: HashMap<String, Vec<Solution>>,
+    // Keys from least to most recently used
+    recency: VecDeque<String>,
+    dir: Option<PathBuf>,
+}
+
+impl SolveCache {
+    /// A cache holding up to `capacity` solves in memory
+    pub fn new(capacity: usize) -> Self {
+        SolveCache {
+            capacity,
+            entries: HashMap::new(),
+            recency: VecDeque::new(),
+            dir: None,
+        }
+    }
+
+    /// Also keep solves in files in `dir`, which is created if need be, so they outlast the process
+    pub fn dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.dir = Some(dir.into());
+        self
+    }
+
+    /// How many solves are held in memory
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Solve the board, or return the solutions from the last time it was solved the same way
+    pub fn solve(&mut self, board: Board, dictionary: &Dictionary, config: SolverConfig) -> SolveOutcome {
+        let key = cache_key(&board, dictionary, &config);
+        if let Some(solutions) = self.get(&key, dictionary) {
+            return SolveOutcome { solutions, timed_out: false };
+        }
+        let outcome = Solver::with_config(board, dictionary, config).solve_with_outcome(None);
+        if !outcome.timed_out {
+            self.insert(key, &outcome.solutions);
+        }
+        outcome
+    }
+
+    /// The solutions cached under `key`, from memory or else from disk. `dictionary` must be the one they were
+    /// found with, since it's the key's to say so, and the solutions are rebuilt from its words.
+    pub fn get(&mut self, key: &str, dictionary: &Dictionary) -> Option<Vec<Solution>> {
+        if let Some(solutions) = self.entries.get(key) {
+            let solutions = solutions.clone();
+            self.touch(key);
+            return Some(solutions);
+        }
+
+        let path = self.path_for(key)?;
+        let text = fs::read_to_string(path).ok()?;
+        let file: CacheFile = serde_json::from_str(&text).ok()?;
+        if file.key != key {
+            return None;
+        }
+        // One pass over the dictionary finds every word, where looking each one up would be a pass apiece
+        let wanted: HashSet<&str> = file.solutions.iter().flat_map(|s| s.split('-')).collect();
+        let found: HashMap<&str, &Word> = dictionary
+            .words()
+            .iter()
+            .filter(|w| wanted.contains(w.word.as_str()))
+            .map(|w| (w.word.as_str(), w))
+            .collect();
+        let solutions = file
+            .solutions
+            .iter()
+            .map(|solution| {
+                let words = solution.split('-').map(|word| found.get(word).map(|&w| w.clone()));
+                words.collect::<Option<Vec<_>>>().map(Solution::new)
+            })
+            .collect::<Option<Vec<_>>>()?;
+        self.remember(key.to_string(), solutions.clone());
+        Some(solutions)
+    }
+
+    /// Cache `solutions` under `key`. A cache file which can't be written is only logged, since the solve itself
+    /// went fine.
+    pub fn insert(&mut self, key: String, solutions: &[Solution]) {
+        if let Some(path) = self.path_for(&key) {
+            let file = CacheFile {
+                key: key.clone(),
+                solutions: solutions.iter().map(|s| s.to_string()).collect(),
+            };
+            let written = path
+                .parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|_| fs::write(&path, serde_json::to_string(&file).unwrap_or_default()));
+            if let Err(e) = written {
+                warn!("Couldn't write solve cache file {}: {}", path.display(), e);
+            }
+        }
+        self.remember(key, solutions.to_vec());
+    }
+
+    fn remember(&mut self, key: String, solutions: Vec<Solution>) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.insert(key.clone(), solutions).is_some() {
+            self.touch(&key);
+            return;
+        }
+        self.recency.push_back(key);
+        while self.entries.len() > self.capacity {
+            if let Some(oldest) = self.recency.pop_front() {
+                self.entries.remove(&oldest);
+            }
+        }
+    }
+
+    fn touch(&mut self, key: &str) {
+        if let Some(position) = self.recency.iter().position(|k| k == key) {
+            let key = self.recency.remove(position).expect("position is in range");
+            self.recency.push_back(key);
+        }
+    }
+
+    fn path_for(&self, key: &str) -> Option<PathBuf> {
+        let dir = self.dir.as_ref()?;
+        Some(dir.join(format!("{:016x}.json", fnv1a(key.as_bytes()))))
+    }
+}
+
+/**
+ * The key a solve is cached under. It's readable, which helps when debugging a miss, but long: the dictionary
+ * fingerprint and every config setting are in it.
+ */
+pub fn cache_key(board: &Board, dictionary: &Dictionary, config: &SolverConfig) -> String {
+    let mut sides: Vec<String> = board
+        .sides
+        .iter()
+        .map(|side| {
+            let mut letters: Vec<char> = side.chars().collect();
+            letters.sort_unstable();
+            letters.into_iter().collect()
+        })
+        .collect();
+    sides.sort_unstable();
+
+    let sorted = |set: &HashSet<String>| {
+        let mut items: Vec<&str> = set.iter().map(String::as_str).collect();
+        items.sort_unstable();
+        items.join(",")
+    };
+    format!(
+        "{} {:?} max={} excluded={} required={} tags={} excluded_tags={} start={:?} words={} scoring={:?} \
+         redundant={} spread={:?} per_first={:?} plurals={} per_length={:?} dictionary={:016x}",
+        sides.join(","),
+        board.rules,
+        config.max_solutions,
+        sorted(&config.excluded_words),
+        sorted(&config.required_words),
+        sorted(&config.required_tags),
+        sorted(&config.excluded_tags),
+        config.start_letter,
+        config.max_words,
+        config.scoring,
+        config.filter_redundant,
+        config.max_rarity_spread,
+        config.max_per_first_word,
+        config.collapse_plurals,
+        config.max_per_length,
+        dictionary.fingerprint(),
+    )
+}
+
+// FNV-1a, since cache file names have to hash the same in every build, which std's hashers don't promise
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(solutions: &[Solution]) -> Vec<String> {
+        solutions.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_memory_cache() {
+        let dictionary = Dictionary::from_text("forklift 20\ntwangy 18\n");
+        let board = Board::from_spec("yfa,otk,lgw,rni").unwrap();
+        let mut cache = SolveCache::new(1);
+
+        let solved = cache.solve(board.clone(), &dictionary, SolverConfig::new());
+        assert_eq!(words(&solved.solutions), vec!["forklift-twangy"]);
+
+        // Shuffling the sides and letters makes the same key
+        let shuffled = Board::from_spec("lwg,rni,ayf,okt").unwrap();
+        let key = cache_key(&shuffled, &dictionary, &SolverConfig::new());
+        assert_eq!(key, cache_key(&board, &dictionary, &SolverConfig::new()));
+        assert_eq!(words(&cache.get(&key, &dictionary).unwrap()), vec!["forklift-twangy"]);
+
+        // but a different config, or dictionary, doesn't
+        let config = SolverConfig::new().max_solutions(3);
+        assert!(cache.get(&cache_key(&board, &dictionary, &config), &dictionary).is_none());
+        let other = Dictionary::from_text("forklift 20\ntwangy 17\n");
+        assert!(cache.get(&cache_key(&board, &other, &SolverConfig::new()), &other).is_none());
+
+        // With room for one solve, the next pushes the first out
+        cache.solve(board, &dictionary, config);
+        assert_eq!(cache.len(), 1);
+        assert!(cache.get(&key, &dictionary).is_none());
+    }
+
+    #[test]
+    fn test_disk_cache() {
+        let dir = std::env::temp_dir().join(format!("letter-bounced-cache-test-{}", std::process::id()));
+        let dictionary = Dictionary::from_text("forklift 20\ntwangy 18\nfork 20\nkilt 20\n");
+        let board = Board::from_spec("yfa,otk,lgw,rni").unwrap();
+        let config = SolverConfig::new();
+        let key = cache_key(&board, &dictionary, &config);
+
+        let found = SolveCache::new(10).dir(&dir).solve(board, &dictionary, config).solutions;
+        assert_eq!(words(&found), vec!["forklift-twangy", "fork-kilt-twangy"]);
+
+        // A new cache, with nothing in memory, reads it back from the file
+        let mut cache = SolveCache::new(10).dir(&dir);
+        let cached = cache.get(&key, &dictionary).unwrap();
+        assert_eq!(cached, found);
+        assert_eq!(cache.len(), 1);
+
+        // Words which aren't in the dictionary any more make a miss, not wrong solutions
+        let mut cache = SolveCache::new(10).dir(&dir);
+        assert!(cache.get(&key, &Dictionary::from_text("forklift 20\n")).is_none());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
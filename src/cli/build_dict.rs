@@ -0,0 +1,777 @@
+use clap::Args;
+use rayon::prelude::*;
+use letter_bounced::board::GameRules;
+use letter_bounced::dictionary::{parse_spelling_variants, Dictionary, ParsePolicy, AMERICAN_TAG, BRITISH_TAG};
+use std::cmp::{max, min, Ordering};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufRead, BufReader, IsTerminal, Lines, Read, Result, Seek, SeekFrom, Write};
+use std::ops::Range;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+
+/*
+ * The build-dict subcommand. Build the standard word-list for boxchar, which will be a list of words which are
+ * playable, along with how frequent they are in english. The word-list will then be sorted by frequency, which
+ * will make it easier to display "good" solutions first.
+ *
+ * We want to create the word list from two sources, which are both large text files.
+ *    - the collins scrabble words, a newline-delimited text file, one word per line, e.g.
+ *          AA
+ *          AAH
+ *          AAHED
+ *          AAHING
+ *          AAHS
+ *    - a dump from google ngrams, a tab-separated newline-delimited text file, with integers representing
+ *      how frequent the word is. Larger is more frequent. The largest number here is ~ 2**36
+ *          a       14219615690
+ *          a!      196012
+ *          a"      84
+ *          a'      47713
+ *
+ * We will iterate through both files simultaneously, outputing lines as appropriate, e.g.
+ *          aba 114620
+ *          abac 5914
+ *          abacas 423
+ *          abaci 41132
+ *          aback 1138210
+ *
+ * The ngrams file is tens of gigabytes, so it's read in parallel: split into one byte range per CPU, cut at line
+ * breaks. Since the file is sorted, each range is a range of words, and the words each thread finds can simply be
+ * put back together in order before merging with the word list.
+ *
+ * We expect the user to then sort the file appropriately with shell tools, e.g.
+ *     $ cargo run -- build-dict --frequencies data/google-ngrams-words-all.txt > /tmp/wordlist.txt
+ *     $ sort -k 2,2rn -k 1 /tmp/wordlist.txt > data/wordlist.txt
+ *
+ * More word lists (ENABLE, a personal list) can be merged in with repeated --scrabble or --wordlist options.
+ *
+ * Languages without an ngrams dump can count frequencies from any directory of plain text instead:
+ *     $ cargo run -- build-dict --corpus texts/ --scrabble data/mots.txt > /tmp/wordlist.txt
+ * The most common word in the corpus scores 31, and each halving of a word's count costs it a point, which is
+ * roughly the shape of the ngrams scores.
+ *
+ * To ship an updated dictionary to clients which already have the old one, make a small patch instead:
+ *     $ cargo run -- build-dict --patch data/dictionary-old.txt data/dictionary.txt > /tmp/update.patch
+ *
+ * `--wordlist nyt` needs the words the NYT accepts. Each day's Letter Boxed page lists them for its board, so save
+ * the pages (or their gameData JSON) as you play, and gather them into the list along with the last one:
+ *     $ cargo run -- build-dict --nyt-words data/nyt-accepted.txt saved/letter-boxed-*.html > /tmp/nyt-accepted.txt
+ *     $ mv /tmp/nyt-accepted.txt data/nyt-accepted.txt
+ *
+ * The CLI loads a binary form of the dictionary faster, since it only reads the words a board can use:
+ *     $ cargo run -- build-dict --binary data/dictionary.txt > data/dictionary.bin
+ *
+ * Offensive words can be left out by passing one or more blocklists with --exclude.
+ *
+ * With --spelling-variants, words British and American English spell differently get a third column tagging them
+ * `british` or `american`, so players can choose a convention with `--spelling`. Similarly --collapse-inflections
+ * tags plurals and verb forms of other words `inflection`, so `--exclude-tag inflection` leaves only base forms.
+ *
+ */
+
+#[derive(Args)]
+pub struct BuildDictArgs {
+    #[arg(long, required_unless_present_any = ["patch", "binary", "corpus", "nyt_words"])]
+    frequencies: Option<String>,
+
+    /// Count word frequencies from the text files in this directory, instead of an ngrams file
+    #[arg(long, value_name = "DIR", conflicts_with = "frequencies")]
+    corpus: Option<String>,
+
+    /// Instead of building, print a patch which turns the BASE dictionary into the NEW one
+    #[arg(long, num_args = 2, value_names = ["BASE", "NEW"], conflicts_with_all = ["frequencies", "corpus"])]
+    patch: Option<Vec<String>>,
+
+    /// Instead of building, print the binary form of a text dictionary, for faster loading with --dictionary
+    #[arg(long, value_name = "DICTIONARY", conflicts_with_all = ["frequencies", "corpus", "patch"])]
+    binary: Option<String>,
+
+    /// Instead of building, print the words the NYT accepts, one per line, gathered from saved Letter Boxed pages
+    /// or their gameData JSON, and earlier lists
+    #[arg(long, num_args = 1.., value_name = "FILE", conflicts_with_all = ["frequencies", "corpus", "patch", "binary"])]
+    nyt_words: Option<Vec<String>>,
+
+    /// Scrabble-style word list, one word per line. May be given more than once.
+    #[arg(long, default_value = "data/collins-scrabble-words-2019.txt")]
+    scrabble: Vec<String>,
+
+    /// Additional word lists to merge in (e.g. ENABLE, or a personal list). Lines may have a frequency score
+    /// after the word, which is used if the word is rarer in the ngrams or missing from them.
+    #[arg(long)]
+    wordlist: Vec<String>,
+
+    /// Shortest word to include
+    #[arg(long, default_value_t = MINIMUM_LENGTH)]
+    min_length: usize,
+
+    /// Keep words with immediately doubled letters, for variant games where a letter can appear on two sides
+    #[arg(long)]
+    allow_doubled_letters: bool,
+
+    /// Blocklist of words to leave out (slurs, offensive terms), one per line. May be given more than once.
+    #[arg(long)]
+    exclude: Vec<String>,
+
+    /// British and American spelling pairs (e.g. data/spelling-variants.txt), to tag `british` and `american`
+    #[arg(long, value_name = "PATH")]
+    spelling_variants: Option<String>,
+
+    /// Tag obvious plurals and verb forms of other listed words (dogs, boxes, baked, baking) `inflection`
+    #[arg(long)]
+    collapse_inflections: bool,
+}
+
+const MINIMUM_LENGTH: usize = 3;
+
+// Frequency scores are packed into a few bits, so they run from 0 to this
+const MAX_FREQUENCY_SCORE: u32 = 31;
+
+// How many ngrams lines to read between progress reports
+const PROGRESS_INTERVAL: usize = 1_000_000;
+
+struct WordRules {
+    min_length: usize,
+    game: GameRules,
+}
+
+impl Default for WordRules {
+    fn default() -> Self {
+        WordRules {
+            min_length: MINIMUM_LENGTH,
+            game: GameRules::Standard,
+        }
+    }
+}
+
+/**
+ * Word has to be of minimum length, and (unless the game rules allow it) have no immediately doubled letters.
+ * BUT is okay, BUTT is not.
+ * It also has to be all lowercase a-z letters, but we assume the Scrabble dictionary has that property already.
+ */
+fn is_playable_word(word: &str, rules: &WordRules) -> bool {
+    word.len() >= rules.min_length && rules.game.allows_word(word)
+}
+
+// Tag for words which are an inflection of another listed word
+const INFLECTION_TAG: &str = "inflection";
+
+// Inflections only count if what's left is at least this long, so that e.g. AS isn't taken for a plural of A
+const MINIMUM_BASE_LENGTH: usize = 3;
+
+/**
+ * Whether `word` is an obvious plural or verb form of another word in `words`: a base plus S, ES (after S, X, Z,
+ * CH or SH), IES for Y, or ED or ING, with or without a dropped E. Irregular forms aren't caught.
+ */
+fn is_inflection(word: &str, words: &BTreeMap<String, Option<u32>>) -> bool {
+    let listed = |base: &str| base.len() >= MINIMUM_BASE_LENGTH && words.contains_key(base);
+    let with_e = |base: &str| listed(&format!("{}e", base));
+
+    if let Some(base) = word.strip_suffix("ies") {
+        if listed(&format!("{}y", base)) {
+            return true;
+        }
+    }
+    let takes_es = |base: &str| ["s", "x", "z", "ch", "sh"].iter().any(|end| base.ends_with(end));
+    if let Some(base) = word.strip_suffix("es") {
+        if takes_es(base) && listed(base) {
+            return true;
+        }
+    }
+    if let Some(base) = word.strip_suffix('s') {
+        if !takes_es(base) && listed(base) {
+            return true;
+        }
+    }
+    if let Some(base) = word.strip_suffix("ed").or_else(|| word.strip_suffix("ing")) {
+        if listed(base) || with_e(base) {
+            return true;
+        }
+    }
+    false
+}
+
+fn path_string_to_line_iterator(path_string: &str) -> Result<Lines<BufReader<File>>> {
+    let path = Path::new(&path_string);
+    let file = File::open(path)?;
+    let reader = BufReader::new(file);
+    let lines = reader.lines();
+    Ok(lines)
+}
+
+/**
+ * Read blocklists: one word per line, case-insensitive. Blank lines and lines starting with '#' are ignored.
+ */
+fn read_excluded_words(lines: impl Iterator<Item = String>, excluded: &mut HashSet<String>) {
+    for line in lines {
+        let word = line.trim();
+        if !word.is_empty() && !word.starts_with('#') {
+            excluded.insert(word.to_lowercase());
+        }
+    }
+}
+
+/**
+ * Merge a word list into `words`. Each line is a word, optionally followed by a frequency score on the same scale
+ * as our output. A word may appear in several lists; we keep the highest score any of them gave it.
+ */
+fn read_wordlist(lines: impl Iterator<Item = String>, words: &mut BTreeMap<String, Option<u32>>) {
+    for line in lines {
+        let mut parts = line.split_whitespace();
+        let Some(word) = parts.next() else {
+            continue;
+        };
+        let score = parts.next().and_then(|s| s.parse::<u32>().ok());
+        let entry = words.entry(word.to_lowercase()).or_insert(None);
+        *entry = max(*entry, score);
+    }
+}
+
+/**
+ * Parse a line of the ngrams file into a word and its frequency score. Lines which aren't a word, a positive count
+ * and valid UTF-8 give None, so a few bad lines in a file this size don't spoil the build.
+ */
+fn parse_ngram_line(line: &[u8]) -> Option<(String, u32)> {
+    let line = std::str::from_utf8(line).ok()?;
+    let mut split = line.split_whitespace();
+    let word = split.next()?.to_string();
+
+    // The largest frequency in this file is about 2**35, so u64 should do it.
+    let frequency: u64 = split.next()?.parse().ok()?;
+    // However, to save a few bytes later when we pack it, we're going to assume the maximum "frequency_score" is just 31.
+    // There are only a few super-short words which are above 31 anyway.
+    Some((word, min(frequency.checked_ilog2()?, MAX_FREQUENCY_SCORE)))
+}
+
+/// What one thread found in its part of the ngrams file
+#[derive(Debug, Default, PartialEq)]
+struct NgramChunk {
+    /// Words from the word lists and their frequency scores, in file order
+    scores: Vec<(String, u32)>,
+    lines: usize,
+    bad_lines: usize,
+    /// Frequency words in none of the word lists
+    not_listed: usize,
+}
+
+/**
+ * Split a file of `len` bytes into `count` byte ranges, moving each boundary forward to the start of a line so no
+ * line is split between two ranges. Ranges may be empty when lines are long.
+ */
+fn line_aligned_ranges(file: &mut (impl BufRead + Seek), len: u64, count: usize) -> Result<Vec<Range<u64>>> {
+    let mut starts = vec![0];
+    for i in 1..count as u64 {
+        let offset = len * i / count as u64;
+        if offset == 0 {
+            starts.push(0);
+            continue;
+        }
+        // Finish the line which the byte before `offset` is on
+        file.seek(SeekFrom::Start(offset - 1))?;
+        let start = offset - 1 + file.skip_until(b'\n')? as u64;
+        starts.push(start.max(*starts.last().unwrap()));
+    }
+    starts.push(len);
+    Ok(starts.windows(2).map(|pair| pair[0]..pair[1]).collect())
+}
+
+/**
+ * Read one range of the ngrams file, keeping the words in the word lists. Every `PROGRESS_INTERVAL` lines it adds
+ * to the count in `lines_read`, and if `progress` is set reports the total so far.
+ */
+fn read_ngram_range(
+    path: &str,
+    range: Range<u64>,
+    words: &BTreeMap<String, Option<u32>>,
+    lines_read: &AtomicUsize,
+    progress: bool,
+) -> Result<NgramChunk> {
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::Start(range.start))?;
+    let mut chunk = NgramChunk::default();
+
+    for line in BufReader::new(file.take(range.end - range.start)).split(b'\n') {
+        let line = line?;
+        chunk.lines += 1;
+        if chunk.lines % PROGRESS_INTERVAL == 0 {
+            let total = lines_read.fetch_add(PROGRESS_INTERVAL, AtomicOrdering::Relaxed) + PROGRESS_INTERVAL;
+            if progress {
+                eprint!("\rRead {} million frequency lines", total / 1_000_000);
+            }
+        }
+        match parse_ngram_line(&line) {
+            Some((word, score)) if words.contains_key(&word) => chunk.scores.push((word, score)),
+            Some(_) => chunk.not_listed += 1,
+            None => chunk.bad_lines += 1,
+        }
+    }
+    Ok(chunk)
+}
+
+/**
+ * Read the whole ngrams file in parallel, one range per rayon thread, putting the results back together in file order.
+ */
+fn read_ngrams(path: &str, words: &BTreeMap<String, Option<u32>>, progress: bool) -> Result<NgramChunk> {
+    let mut file = BufReader::new(File::open(path)?);
+    let len = file.get_ref().metadata()?.len();
+    let ranges = line_aligned_ranges(&mut file, len, rayon::current_num_threads())?;
+
+    let lines_read = AtomicUsize::new(0);
+    let chunks = ranges
+        .into_par_iter()
+        .map(|range| read_ngram_range(path, range, words, &lines_read, progress))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut all = NgramChunk::default();
+    for chunk in chunks {
+        all.scores.extend(chunk.scores);
+        all.lines += chunk.lines;
+        all.bad_lines += chunk.bad_lines;
+        all.not_listed += chunk.not_listed;
+    }
+    if progress && all.lines >= PROGRESS_INTERVAL {
+        eprintln!();
+    }
+    Ok(all)
+}
+
+/**
+ * Count the words in a corpus of plain text. Words are runs of letters, lowercased; anything else separates them.
+ */
+fn count_words(text: &str, counts: &mut HashMap<String, u64>) {
+    for token in text.split(|c: char| !c.is_alphabetic()).filter(|t| !t.is_empty()) {
+        *counts.entry(token.to_lowercase()).or_default() += 1;
+    }
+}
+
+/**
+ * Frequency scores for counted words, sorted by word: the commonest scores 31, and each halving of the count costs
+ * a point, down to 0.
+ */
+fn corpus_scores(counts: HashMap<String, u64>) -> BTreeMap<String, u32> {
+    let top = counts.values().copied().max().unwrap_or(1).ilog2();
+    counts
+        .into_iter()
+        .map(|(word, count)| (word, MAX_FREQUENCY_SCORE.saturating_sub(top - count.ilog2())))
+        .collect()
+}
+
+fn read_corpus(dir: &Path, counts: &mut HashMap<String, u64>) -> Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            read_corpus(&path, counts)?;
+        } else {
+            // Corpora are messy; a stray bad byte shouldn't lose the whole file
+            count_words(&String::from_utf8_lossy(&std::fs::read(&path)?), counts);
+        }
+    }
+    Ok(())
+}
+
+/// Words the merge of word lists and frequencies left out
+#[derive(Debug, Default, PartialEq)]
+struct MergeStats {
+    /// With a frequency, but in none of the word lists (only counted until the word lists run out)
+    not_listed: usize,
+    /// In a word list, but with no frequency from anywhere
+    no_frequency: usize,
+}
+
+/// What a build did with its input, reported on stderr when it's done
+#[derive(Debug, Default, PartialEq)]
+struct BuildStats {
+    frequency_lines: usize,
+    bad_lines: usize,
+    kept: usize,
+    /// Too short, or with doubled letters
+    dropped_by_rule: usize,
+    blocklisted: usize,
+    merge: MergeStats,
+}
+
+impl std::fmt::Display for BuildStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.frequency_lines > 0 {
+            writeln!(f, "Read {} frequency lines, skipping {} which couldn't be parsed", self.frequency_lines, self.bad_lines)?;
+        }
+        writeln!(f, "Kept {} words", self.kept)?;
+        write!(
+            f,
+            "Dropped {} by the word rules, {} blocklisted, {} with no frequency, and {} not in the word lists",
+            self.dropped_by_rule, self.blocklisted, self.merge.no_frequency, self.merge.not_listed
+        )
+    }
+}
+
+/**
+ * Iterate through the very large list of sorted (word, frequency score) pairs alongside our sorted word list,
+ * calling `emit` with each listed word and its frequency score. Words the frequencies don't know about are only
+ * emitted if a word list gave them a score.
+ */
+fn merge_frequencies(
+    words: &BTreeMap<String, Option<u32>>,
+    frequencies: impl Iterator<Item = (String, u32)>,
+    mut emit: impl FnMut(&str, u32),
+) -> MergeStats {
+    let mut stats = MergeStats::default();
+    let mut wordlist = words.iter().peekable();
+    let mut emit_listed = |word: &str, listed_score: Option<u32>, stats: &mut MergeStats| match listed_score {
+        Some(score) => emit(word, score),
+        None => stats.no_frequency += 1,
+    };
+
+    for (frequencies_word, frequency_score) in frequencies {
+        if wordlist.peek().is_none() {
+            break;
+        }
+
+        loop {
+            let Some(&(word, listed_score)) = wordlist.peek() else {
+                stats.not_listed += 1;
+                break;
+            };
+            match word.as_str().cmp(frequencies_word.as_str()) {
+                Ordering::Less => {
+                    emit_listed(word, *listed_score, &mut stats);
+                    wordlist.next();
+                }
+                Ordering::Equal => {
+                    emit_listed(word, Some(max(frequency_score, listed_score.unwrap_or(0))), &mut stats);
+                    wordlist.next();
+                    break;
+                }
+                Ordering::Greater => {
+                    stats.not_listed += 1;
+                    break;
+                }
+            }
+        }
+    }
+
+    for (word, listed_score) in wordlist {
+        emit_listed(word, *listed_score, &mut stats);
+    }
+    stats
+}
+
+// Each day's Letter Boxed page carries the words it accepts for that board, as the `dictionary` array of its
+// gameData. A file without one is taken to be a list made earlier, one word per line.
+fn accepted_words(text: &str) -> std::result::Result<Vec<String>, String> {
+    const KEY: &str = "\"dictionary\"";
+    let words = match text.find(KEY) {
+        Some(start) => {
+            let array = text[start + KEY.len()..].trim_start().strip_prefix(':').unwrap_or_default().trim_start();
+            match serde_json::Deserializer::from_str(array).into_iter::<Vec<String>>().next() {
+                Some(Ok(words)) => words,
+                _ => return Err("the game data's dictionary isn't a list of words".to_string()),
+            }
+        }
+        None => text.lines().map(str::trim).filter(|line| !line.is_empty()).map(String::from).collect(),
+    };
+    Ok(words
+        .into_iter()
+        .map(|word| word.to_lowercase())
+        .filter(|word| word.chars().all(|c| c.is_ascii_lowercase()))
+        .collect())
+}
+
+pub fn run(args: &BuildDictArgs) -> letter_bounced::error::Result<()> {
+    // Files shipped to players shouldn't quietly lose words, so a bad line stops the build
+    if let Some(paths) = &args.patch {
+        let base = Dictionary::from_path_with_policy(&paths[0], ParsePolicy::Strict)?;
+        let new = Dictionary::from_path_with_policy(&paths[1], ParsePolicy::Strict)?;
+        print!("{}", base.patch_to(&new));
+        return Ok(());
+    }
+
+    if let Some(paths) = &args.nyt_words {
+        let mut words = BTreeSet::new();
+        for path in paths {
+            words.extend(accepted_words(&std::fs::read_to_string(path)?).map_err(|e| {
+                letter_bounced::error::LetterBouncedError::DictionaryParse(format!("{}: {}", path, e))
+            })?);
+        }
+        for word in words {
+            println!("{}", word);
+        }
+        return Ok(());
+    }
+
+    if let Some(path) = &args.binary {
+        let dictionary = Dictionary::from_path_with_policy(path, ParsePolicy::Strict)?;
+        std::io::stdout().write_all(&dictionary.to_binary()?)?;
+        return Ok(());
+    }
+
+    let rules = WordRules {
+        min_length: args.min_length,
+        // Either variant keeps every doubled word, since a board could have the letter on two sides
+        game: if args.allow_doubled_letters { GameRules::DuplicateLetters } else { GameRules::Standard },
+    };
+
+    let mut excluded_words = HashSet::new();
+    for path in &args.exclude {
+        read_excluded_words(path_string_to_line_iterator(path)?.map_while(Result::ok), &mut excluded_words);
+    }
+
+    let mut words = BTreeMap::new();
+    for path in args.scrabble.iter().chain(&args.wordlist) {
+        read_wordlist(path_string_to_line_iterator(path)?.map_while(Result::ok), &mut words);
+    }
+
+    let mut spelling_tags = HashMap::new();
+    if let Some(path) = &args.spelling_variants {
+        for (british, american) in parse_spelling_variants(&std::fs::read_to_string(path)?)? {
+            spelling_tags.insert(british, BRITISH_TAG);
+            spelling_tags.insert(american, AMERICAN_TAG);
+        }
+    }
+
+    let mut stats = BuildStats::default();
+    let emit = |word: &str, frequency_score| {
+        if !is_playable_word(word, &rules) {
+            stats.dropped_by_rule += 1;
+        } else if excluded_words.contains(word) {
+            stats.blocklisted += 1;
+        } else {
+            let mut tags: Vec<&str> = spelling_tags.get(word).copied().into_iter().collect();
+            if args.collapse_inflections && is_inflection(word, &words) {
+                tags.push(INFLECTION_TAG);
+            }
+            if tags.is_empty() {
+                println!("{} {}", word, frequency_score);
+            } else {
+                println!("{} {} {}", word, frequency_score, tags.join(","));
+            }
+            stats.kept += 1;
+        }
+    };
+    if let Some(dir) = &args.corpus {
+        let mut counts = HashMap::new();
+        read_corpus(Path::new(dir), &mut counts)?;
+        stats.merge = merge_frequencies(&words, corpus_scores(counts).into_iter(), emit);
+    } else {
+        // The ngrams file is many gigabytes, so show it's getting somewhere
+        let progress = std::io::stderr().is_terminal();
+        let ngrams = read_ngrams(args.frequencies.as_deref().unwrap_or_default(), &words, progress)?;
+        stats.frequency_lines = ngrams.lines;
+        stats.bad_lines = ngrams.bad_lines;
+        stats.merge = merge_frequencies(&words, ngrams.scores.into_iter(), emit);
+        stats.merge.not_listed += ngrams.not_listed;
+    }
+
+    eprintln!("{}", stats);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_playable_word() {
+        let rules = WordRules::default();
+
+        // adjacent repeated letters
+        assert!(!is_playable_word("peer", &rules));
+        assert!(!is_playable_word("book", &rules));
+        assert!(!is_playable_word("coffee", &rules));
+        assert!(!is_playable_word("llama", &rules));
+
+        // too short
+        assert!(!is_playable_word("an", &rules));
+        assert!(!is_playable_word("", &rules));
+
+        // okay
+        assert!(is_playable_word("dojo", &rules));
+        assert!(is_playable_word("word", &rules));
+    }
+
+    #[test]
+    fn test_is_playable_word_variant_rules() {
+        let rules = WordRules {
+            min_length: 5,
+            game: GameRules::DoubledLetters,
+        };
+
+        assert!(is_playable_word("coffee", &rules));
+        assert!(is_playable_word("llama", &rules));
+        assert!(!is_playable_word("book", &rules));
+        assert!(!is_playable_word("dojo", &rules));
+    }
+
+    fn lines(strs: &[&str]) -> impl Iterator<Item = String> {
+        strs.iter().map(|s| s.to_string()).collect::<Vec<_>>().into_iter()
+    }
+
+    #[test]
+    fn test_merge_wordlists() {
+        let mut words = BTreeMap::new();
+        read_wordlist(lines(&["AAH", "DOJO", "ZAX"]), &mut words);
+        read_wordlist(lines(&["dojo 20", "neologism 9", "yeet"]), &mut words);
+
+        let frequencies = ["aah 1024", "dojo 4096", "qi 8", "yeet 64", "zax 16"];
+        let frequencies = frequencies.iter().filter_map(|line| parse_ngram_line(line.as_bytes()));
+        let mut merged = Vec::new();
+        let stats = merge_frequencies(&words, frequencies, |word, score| merged.push((word.to_string(), score)));
+
+        assert_eq!(
+            merged,
+            vec![
+                ("aah".to_string(), 10),
+                // the personal list scores dojo higher than the ngrams do
+                ("dojo".to_string(), 20),
+                // not in the ngrams, but the personal list gave it a score
+                ("neologism".to_string(), 9),
+                ("yeet".to_string(), 6),
+                ("zax".to_string(), 4),
+            ]
+        );
+        assert_eq!(stats, MergeStats { not_listed: 1, no_frequency: 0 });
+    }
+
+    #[test]
+    fn test_merge_edge_cases() {
+        let mut words = BTreeMap::new();
+        read_wordlist(lines(&["abc", "mmm", "zzz"]), &mut words);
+        let mut merged = Vec::new();
+
+        // No frequencies at all: nothing is scored, so nothing is emitted
+        let stats = merge_frequencies(&words, std::iter::empty(), |word, score| merged.push((word.to_string(), score)));
+        assert!(merged.is_empty());
+        assert_eq!(stats, MergeStats { not_listed: 0, no_frequency: 3 });
+
+        // Frequency words before, between and after the listed ones, and the word list running out first
+        let frequencies = [("aaa", 1), ("abc", 2), ("def", 3), ("zzz", 4), ("zzzz", 5)];
+        let frequencies = frequencies.iter().map(|(word, score)| (word.to_string(), *score));
+        let stats = merge_frequencies(&words, frequencies, |word, score| merged.push((word.to_string(), score)));
+        assert_eq!(merged, vec![("abc".to_string(), 2), ("zzz".to_string(), 4)]);
+        // zzzz isn't looked at once the word list is done
+        assert_eq!(stats, MergeStats { not_listed: 2, no_frequency: 1 });
+    }
+
+    #[test]
+    fn test_line_aligned_ranges() {
+        let text = "aah 1024\ndojo 4096\nqi 8\nyeet 64\nzax 16\n";
+        let len = text.len() as u64;
+        for count in 1..=8 {
+            let ranges = line_aligned_ranges(&mut std::io::Cursor::new(text), len, count).unwrap();
+            assert_eq!(ranges.len(), count);
+            assert_eq!(ranges.first().unwrap().start, 0);
+            assert_eq!(ranges.last().unwrap().end, len);
+            for range in &ranges {
+                let part = &text[range.start as usize..range.end as usize];
+                assert!(part.is_empty() || part.ends_with('\n'), "{:?} splits a line", part);
+            }
+        }
+
+        let ranges = line_aligned_ranges(&mut std::io::Cursor::new(""), 0, 4).unwrap();
+        assert!(ranges.iter().all(|range| range.is_empty()));
+    }
+
+    #[test]
+    fn test_accepted_words() {
+        let page = r#"<script>window.gameData = {"sides":["YFA","OTK","LGW","RNI"],"dictionary":["FORKLIFT","TWANGY"]}</script>"#;
+        assert_eq!(accepted_words(page).unwrap(), vec!["forklift", "twangy"]);
+        assert_eq!(accepted_words("fork\n\nkilt\nnot-a-word\n").unwrap(), vec!["fork", "kilt"]);
+        assert!(accepted_words(r#"{"dictionary": "FORKLIFT"}"#).is_err());
+    }
+
+    #[test]
+    fn test_read_ngrams() {
+        let path = std::env::temp_dir().join(format!("letter-bounced-ngrams-{}.txt", std::process::id()));
+        let lines: Vec<String> = (0..500).map(|i| format!("w{:03} {}", i, i + 1)).collect();
+        std::fs::write(&path, lines.join("\nbad line\n")).unwrap();
+
+        let mut words = BTreeMap::new();
+        read_wordlist(lines.iter().step_by(7).map(|line| line[..4].to_string()), &mut words);
+        let ngrams = read_ngrams(path.to_str().unwrap(), &words, false).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(ngrams.lines, 999);
+        assert_eq!(ngrams.bad_lines, 499);
+        assert_eq!(ngrams.scores.len(), words.len());
+        assert_eq!(ngrams.not_listed, 500 - words.len());
+        // In file order, whichever thread read them
+        assert!(ngrams.scores.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        assert_eq!(ngrams.scores[1], ("w007".to_string(), 3));
+    }
+
+    #[test]
+    fn test_parse_ngram_line() {
+        assert_eq!(parse_ngram_line(b"aback 1138210"), Some(("aback".to_string(), 20)));
+        assert_eq!(parse_ngram_line(b"the 99999999999999"), Some(("the".to_string(), 31)));
+
+        for bad in [&b""[..], b"aback", b"aback lots", b"aback 0", b"aback -5", b"\xffback 10"] {
+            assert_eq!(parse_ngram_line(bad), None, "{:?}", String::from_utf8_lossy(bad));
+        }
+    }
+
+    #[test]
+    fn test_build_stats_summary() {
+        let stats = BuildStats {
+            frequency_lines: 100,
+            bad_lines: 2,
+            kept: 40,
+            dropped_by_rule: 5,
+            blocklisted: 1,
+            merge: MergeStats { not_listed: 50, no_frequency: 3 },
+        };
+        assert_eq!(
+            stats.to_string(),
+            "Read 100 frequency lines, skipping 2 which couldn't be parsed\n\
+             Kept 40 words\n\
+             Dropped 5 by the word rules, 1 blocklisted, 3 with no frequency, and 50 not in the word lists"
+        );
+    }
+
+    #[test]
+    fn test_corpus_scores() {
+        let mut counts = HashMap::new();
+        let common = "The cat sat. ".repeat(8);
+        count_words(&format!("{}Le chat était là, THE dog-house!", common), &mut counts);
+
+        assert_eq!(counts["the"], 9);
+        assert_eq!(counts["était"], 1);
+        assert_eq!(counts["dog"], 1);
+        assert!(!counts.contains_key("dog-house"));
+
+        let scores = corpus_scores(counts);
+        assert_eq!(scores["the"], 31);
+        assert_eq!(scores["cat"], 31);
+        // Seen once, which is three halvings below 9, rounding down
+        assert_eq!(scores["chat"], 28);
+
+        let mut words = BTreeMap::new();
+        read_wordlist(lines(&["cat", "chat", "gnu 5"]), &mut words);
+        let mut merged = Vec::new();
+        merge_frequencies(&words, scores.into_iter(), |word, score| merged.push((word.to_string(), score)));
+        assert_eq!(merged, vec![("cat".to_string(), 31), ("chat".to_string(), 28), ("gnu".to_string(), 5)]);
+    }
+
+    #[test]
+    fn test_is_inflection() {
+        let mut words = BTreeMap::new();
+        read_wordlist(lines(&["bake", "box", "dog", "glass", "pony", "walk", "as", "bus"]), &mut words);
+
+        for inflection in ["dogs", "boxes", "glasses", "ponies", "walked", "walking", "baked", "baking", "buses"] {
+            assert!(is_inflection(inflection, &words), "{} should be an inflection", inflection);
+        }
+        // Not a base plus an ending, too short a base, or a base which isn't listed
+        for word in ["dog", "glass", "ass", "bus", "doges", "cats", "boxs", "dogged"] {
+            assert!(!is_inflection(word, &words), "{} shouldn't be an inflection", word);
+        }
+    }
+
+    #[test]
+    fn test_read_excluded_words() {
+        let lines = ["# slurs", "", "Badword", "  other  "].iter().map(|s| s.to_string());
+        let mut excluded = HashSet::new();
+        read_excluded_words(lines, &mut excluded);
+
+        assert_eq!(excluded.len(), 2);
+        assert!(excluded.contains("badword"));
+        assert!(excluded.contains("other"));
+    }
+}
@@ -0,0 +1,153 @@
+use clap::parser::ValueSource;
+use clap::ArgMatches;
+use serde::Deserialize;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use crate::{BoardArgs, SolveArgs, WordList};
+
+/*
+ * Defaults for the command line, read from ~/.config/letterbounced/config.toml (or $XDG_CONFIG_HOME's
+ * letterbounced/config.toml). Every setting is optional, and anything given on the command line wins:
+ *
+ *     dictionary = "/usr/share/letterbounced/dictionary.bin"
+ *     max-solutions = 50
+ *     format = "plain"
+ *     tags = ["common"]
+ *     exclude-tags = ["scrabble-only"]
+ *     wordlist = "nyt"
+ */
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Config {
+    pub dictionary: Option<String>,
+    pub max_solutions: Option<u16>,
+    pub format: Option<Format>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub exclude_tags: Vec<String>,
+    pub wordlist: Option<String>,
+}
+
+/// How solve prints solutions: `ranked` lines with scores when writing to a terminal, always `plain` ones, or `csv`
+/// rows for a spreadsheet
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    Plain,
+    Ranked,
+    Csv,
+}
+
+impl std::str::FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "plain" => Ok(Format::Plain),
+            "ranked" => Ok(Format::Ranked),
+            "csv" => Ok(Format::Csv),
+            _ => Err(format!("Unknown format '{}'. Choose plain, ranked or csv", s)),
+        }
+    }
+}
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// The config file, read the first time it's needed. A missing file is an empty config; one that can't be read
+/// or parsed is reported, and ends the program.
+pub fn get() -> &'static Config {
+    CONFIG.get_or_init(|| {
+        let Some(path) = path() else {
+            return Config::default();
+        };
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Config::default(),
+            Err(e) => {
+                eprintln!("Error reading {}: {}", path.display(), e);
+                std::process::exit(1);
+            }
+        };
+        parse(&text).unwrap_or_else(|e| {
+            eprintln!("Error in {}: {}", path.display(), e);
+            std::process::exit(1);
+        })
+    })
+}
+
+fn path() -> Option<PathBuf> {
+    let config_home = match std::env::var_os("XDG_CONFIG_HOME").filter(|dir| !dir.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+    };
+    Some(config_home.join("letterbounced").join("config.toml"))
+}
+
+fn parse(text: &str) -> Result<Config, String> {
+    let config: Config = toml::from_str(text).map_err(|e| e.to_string())?;
+    if let Some(wordlist) = &config.wordlist {
+        wordlist.parse::<WordList>()?;
+    }
+    if config.max_solutions == Some(0) {
+        return Err("max-solutions must be at least 1".to_string());
+    }
+    Ok(config)
+}
+
+// Only options left at their defaults take the config's values
+fn from_command_line(matches: &ArgMatches, id: &str) -> bool {
+    matches.value_source(id) == Some(ValueSource::CommandLine)
+}
+
+impl Config {
+    pub fn apply_to_board(&self, args: &mut BoardArgs, matches: &ArgMatches) {
+        if let (Some(wordlist), false) = (&self.wordlist, from_command_line(matches, "wordlist")) {
+            // Checked when the file was parsed
+            args.wordlist = wordlist.parse().unwrap_or(args.wordlist);
+        }
+    }
+
+    pub fn apply_to_solve(&self, args: &mut SolveArgs, matches: &ArgMatches) {
+        self.apply_to_board(&mut args.board, matches);
+        if let (Some(max_solutions), false) = (self.max_solutions, from_command_line(matches, "max_solutions")) {
+            args.max_solutions = max_solutions;
+        }
+        if args.format.is_none() && !args.plain {
+            args.format = self.format;
+        }
+        if args.tags.is_empty() {
+            args.tags = self.tags.clone();
+        }
+        if args.exclude_tags.is_empty() {
+            args.exclude_tags = self.exclude_tags.clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let config = parse("dictionary = \"words.txt\"\nmax-solutions = 20\nformat = \"plain\"\ntags = [\"common\"]\n")
+            .unwrap();
+        assert_eq!(config.dictionary.as_deref(), Some("words.txt"));
+        assert_eq!(config.max_solutions, Some(20));
+        assert_eq!(config.format, Some(Format::Plain));
+        assert_eq!(config.tags, vec!["common"]);
+        assert!(config.exclude_tags.is_empty());
+        assert!(config.wordlist.is_none());
+
+        assert!(parse("").unwrap().dictionary.is_none());
+        assert!(parse("wordlist = \"nyt\"").is_ok());
+        assert!(parse("wordlist = \"scrabble\"").unwrap_err().contains("Unknown word list"));
+        assert_eq!(parse("format = \"csv\"").unwrap().format, Some(Format::Csv));
+        assert!(parse("format = \"json\"").is_err());
+        assert!(parse("max-solutions = 0").is_err());
+        // Misspelled settings shouldn't be silently ignored
+        assert!(parse("max_solution = 10").is_err());
+    }
+}
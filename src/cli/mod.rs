@@ -0,0 +1,3 @@
+// Modules only the letter-bounced binary uses, kept apart from the library's
+pub mod build_dict;
+pub mod config;
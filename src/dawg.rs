@@ -0,0 +1,387 @@
+use crate::error::{LetterBouncedError, Result};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/*
+ * A DAWG (directed acyclic word graph) is a trie where identical suffix subtrees are shared. For an English
+ * wordlist that collapses all the "-ing", "-ation", "-ers" endings, so it is far smaller than the flat word list,
+ * and it lets us answer prefix questions ("can anything start with 'qx'?") by walking a few nodes instead of
+ * scanning every word.
+ *
+ * The DAWG only stores the spelling of words; frequencies stay in the `Dictionary`.
+ */
+
+const MAGIC: &[u8; 4] = b"DAWG";
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Node {
+    terminal: bool,
+    // Sorted by letter
+    edges: Vec<(u8, u32)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dawg {
+    nodes: Vec<Node>,
+    root: u32,
+    word_count: usize,
+}
+
+// Plain trie used while building; minimized into a Dawg afterwards
+#[derive(Default)]
+struct TrieNode {
+    terminal: bool,
+    children: BTreeMap<u8, usize>,
+}
+
+impl Dawg {
+    pub fn from_words<I, S>(words: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut trie = vec![TrieNode::default()];
+        let mut word_count = 0;
+        for word in words {
+            let mut current = 0;
+            for &b in word.as_ref().as_bytes() {
+                current = match trie[current].children.get(&b) {
+                    Some(&next) => next,
+                    None => {
+                        trie.push(TrieNode::default());
+                        let next = trie.len() - 1;
+                        trie[current].children.insert(b, next);
+                        next
+                    }
+                };
+            }
+            if !trie[current].terminal {
+                trie[current].terminal = true;
+                word_count += 1;
+            }
+        }
+
+        let mut nodes = Vec::new();
+        let mut register = HashMap::new();
+        let root = Self::minimize(&trie, 0, &mut nodes, &mut register);
+
+        Dawg {
+            nodes,
+            root,
+            word_count,
+        }
+    }
+
+    // Post-order walk: children are canonicalized first, so two subtrees are equal exactly when their nodes are.
+    fn minimize(
+        trie: &[TrieNode],
+        index: usize,
+        nodes: &mut Vec<Node>,
+        register: &mut HashMap<Node, u32>,
+    ) -> u32 {
+        let edges = trie[index]
+            .children
+            .iter()
+            .map(|(&b, &child)| (b, Self::minimize(trie, child, nodes, register)))
+            .collect();
+        let node = Node {
+            terminal: trie[index].terminal,
+            edges,
+        };
+        if let Some(&existing) = register.get(&node) {
+            return existing;
+        }
+        let id = nodes.len() as u32;
+        nodes.push(node.clone());
+        register.insert(node, id);
+        id
+    }
+
+    fn child(&self, node: u32, b: u8) -> Option<u32> {
+        let edges = &self.nodes[node as usize].edges;
+        edges
+            .binary_search_by_key(&b, |&(letter, _)| letter)
+            .ok()
+            .map(|i| edges[i].1)
+    }
+
+    fn walk(&self, prefix: &str) -> Option<u32> {
+        prefix
+            .bytes()
+            .try_fold(self.root, |node, b| self.child(node, b))
+    }
+
+    pub fn len(&self) -> usize {
+        self.word_count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.word_count == 0
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn contains(&self, word: &str) -> bool {
+        self.walk(word)
+            .is_some_and(|node| self.nodes[node as usize].terminal)
+    }
+
+    /// True if at least one word starts with `prefix` (including the prefix itself being a word)
+    pub fn contains_prefix(&self, prefix: &str) -> bool {
+        self.walk(prefix).is_some()
+    }
+
+    /// All words starting with `prefix`, in alphabetical order
+    pub fn words_with_prefix(&self, prefix: &str) -> Vec<String> {
+        let mut words = Vec::new();
+        if let Some(node) = self.walk(prefix) {
+            let mut buffer = prefix.as_bytes().to_vec();
+            self.collect_words(node, &mut buffer, &mut words);
+        }
+        words
+    }
+
+    /**
+     * The letters which can come after `prefix` on the way to at least one word, when every step from one letter
+     * to the next must be allowed by `step`, as on a board where some letters can't follow others. Steps within
+     * `prefix` aren't checked. Shared nodes are only explored once per letter leading into them, so this takes
+     * time in proportion to the graph, not to the number of words.
+     */
+    pub fn continuations(&self, prefix: &str, step: impl Fn(u8, u8) -> bool) -> BTreeSet<char> {
+        let Some(node) = self.walk(prefix) else {
+            return BTreeSet::new();
+        };
+        let mut reaches = HashMap::new();
+        self.nodes[node as usize]
+            .edges
+            .iter()
+            .filter(|&&(b, _)| prefix.bytes().last().is_none_or(|last| step(last, b)))
+            .filter(|&&(b, child)| self.reaches_word(child, b, &step, &mut reaches))
+            .map(|&(b, _)| b as char)
+            .collect()
+    }
+
+    // Whether a word ends at `node`, or beyond it by allowed steps, having just come through `last`
+    fn reaches_word(
+        &self,
+        node: u32,
+        last: u8,
+        step: &impl Fn(u8, u8) -> bool,
+        reaches: &mut HashMap<(u32, u8), bool>,
+    ) -> bool {
+        if let Some(&known) = reaches.get(&(node, last)) {
+            return known;
+        }
+        let found = self.nodes[node as usize].terminal
+            || self.nodes[node as usize]
+                .edges
+                .iter()
+                .any(|&(b, child)| step(last, b) && self.reaches_word(child, b, step, reaches));
+        reaches.insert((node, last), found);
+        found
+    }
+
+    fn collect_words(&self, node: u32, buffer: &mut Vec<u8>, words: &mut Vec<String>) {
+        let node = &self.nodes[node as usize];
+        if node.terminal {
+            words.push(String::from_utf8_lossy(buffer).into_owned());
+        }
+        for &(b, child) in &node.edges {
+            buffer.push(b);
+            self.collect_words(child, buffer, words);
+            buffer.pop();
+        }
+    }
+
+    /// Serialize as: magic, then root, word count and node count as little-endian u32s, then per node a u8 terminal
+    /// flag, a u8 edge count, and (u8 letter, little-endian u32 child) pairs. Nodes come after all their children,
+    /// and each node's edges are in letter order.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&self.root.to_le_bytes());
+        bytes.extend_from_slice(&(self.word_count as u32).to_le_bytes());
+        bytes.extend_from_slice(&(self.nodes.len() as u32).to_le_bytes());
+        for node in &self.nodes {
+            bytes.push(node.terminal as u8);
+            bytes.push(node.edges.len() as u8);
+            for &(b, child) in &node.edges {
+                bytes.push(b);
+                bytes.extend_from_slice(&child.to_le_bytes());
+            }
+        }
+        bytes
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Result<Self> {
+        let mut reader = ByteReader { data, pos: 0 };
+        if reader.take(4)? != MAGIC {
+            return Err(LetterBouncedError::DictionaryParse("Not a DAWG: bad magic bytes".to_string()));
+        }
+        let root = reader.u32()?;
+        let word_count = reader.u32()? as usize;
+        let node_count = reader.u32()?;
+
+        // Checking that children come before their parents rules out cycles, which would send the recursive walks
+        // round forever, and letter order is what `child`'s binary search needs
+        let mut nodes = Vec::new();
+        for index in 0..node_count {
+            let terminal = reader.u8()? != 0;
+            let edge_count = reader.u8()?;
+            let mut edges: Vec<(u8, u32)> = Vec::with_capacity(edge_count as usize);
+            for _ in 0..edge_count {
+                let b = reader.u8()?;
+                let child = reader.u32()?;
+                if child >= node_count {
+                    return Err(LetterBouncedError::DictionaryParse(format!("DAWG edge points to missing node {}", child)));
+                }
+                if child >= index {
+                    return Err(LetterBouncedError::DictionaryParse(format!(
+                        "DAWG node {} has an edge back to node {}",
+                        index, child
+                    )));
+                }
+                if edges.last().is_some_and(|&(previous, _)| previous >= b) {
+                    return Err(LetterBouncedError::DictionaryParse(format!(
+                        "DAWG node {} has edges out of letter order",
+                        index
+                    )));
+                }
+                edges.push((b, child));
+            }
+            nodes.push(Node { terminal, edges });
+        }
+        if root >= node_count {
+            return Err(LetterBouncedError::DictionaryParse(format!("DAWG root {} is out of range", root)));
+        }
+
+        Ok(Dawg {
+            nodes,
+            root,
+            word_count,
+        })
+    }
+}
+
+struct ByteReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8]> {
+        let end = self.pos + n;
+        if end > self.data.len() {
+            return Err(LetterBouncedError::DictionaryParse("Unexpected end of DAWG data".to_string()));
+        }
+        let slice = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Dawg {
+        Dawg::from_words(["tap", "taps", "top", "tops", "dojo", "do"])
+    }
+
+    #[test]
+    fn test_contains() {
+        let dawg = sample();
+        assert_eq!(dawg.len(), 6);
+        assert!(dawg.contains("taps"));
+        assert!(dawg.contains("do"));
+        assert!(!dawg.contains("ta"));
+        assert!(!dawg.contains("dojos"));
+    }
+
+    #[test]
+    fn test_prefix_queries() {
+        let dawg = sample();
+        assert!(dawg.contains_prefix("ta"));
+        assert!(!dawg.contains_prefix("tx"));
+        assert_eq!(dawg.words_with_prefix("t"), vec!["tap", "taps", "top", "tops"]);
+        assert_eq!(dawg.words_with_prefix("do"), vec!["do", "dojo"]);
+    }
+
+    #[test]
+    fn test_continuations() {
+        let dawg = sample();
+        let anything = |_, _| true;
+        assert_eq!(dawg.continuations("t", anything), BTreeSet::from(['a', 'o']));
+        assert_eq!(dawg.continuations("", anything), BTreeSet::from(['d', 't']));
+        // "do" is a word already, but "dojo" goes on
+        assert_eq!(dawg.continuations("do", anything), BTreeSet::from(['j']));
+        assert!(dawg.continuations("tops", anything).is_empty());
+        assert!(dawg.continuations("x", anything).is_empty());
+
+        // If P can't follow A, "ta" leads nowhere, so A isn't offered after T
+        let no_ap = |a, b| (a, b) != (b'a', b'p');
+        assert_eq!(dawg.continuations("t", no_ap), BTreeSet::from(['o']));
+    }
+
+    #[test]
+    fn test_suffixes_are_shared() {
+        // "tap"/"top" and "taps"/"tops" share the "p", "ps" tails
+        let dawg = Dawg::from_words(["tap", "taps", "top", "tops"]);
+        // root, t, a|o (one node reached by both letters), p, s
+        assert_eq!(dawg.node_count(), 5);
+    }
+
+    #[test]
+    fn test_bytes_round_trip() {
+        let dawg = sample();
+        let restored = Dawg::from_bytes(&dawg.to_bytes()).unwrap();
+        assert_eq!(restored, dawg);
+    }
+
+    #[test]
+    fn test_from_bytes_truncated() {
+        let bytes = sample().to_bytes();
+        assert!(Dawg::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(Dawg::from_bytes(b"nope").is_err());
+    }
+
+    // A DAWG with the given nodes, each a terminal flag and (letter, child) edges, rooted at the last node
+    fn raw_bytes(nodes: &[(bool, &[(u8, u32)])]) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&(nodes.len() as u32 - 1).to_le_bytes());
+        bytes.extend_from_slice(&1u32.to_le_bytes());
+        bytes.extend_from_slice(&(nodes.len() as u32).to_le_bytes());
+        for &(terminal, edges) in nodes {
+            bytes.push(terminal as u8);
+            bytes.push(edges.len() as u8);
+            for &(b, child) in edges {
+                bytes.push(b);
+                bytes.extend_from_slice(&child.to_le_bytes());
+            }
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_from_bytes_rejects_bad_graphs() {
+        assert!(Dawg::from_bytes(&raw_bytes(&[(true, &[]), (false, &[(b'a', 0), (b'b', 0)])])).is_ok());
+
+        // A node which loops back to itself, or to a later node which leads back to it
+        assert!(Dawg::from_bytes(&raw_bytes(&[(true, &[(b'a', 0)])])).is_err());
+        assert!(Dawg::from_bytes(&raw_bytes(&[(false, &[(b'a', 1)]), (true, &[(b'b', 0)])])).is_err());
+
+        // Edges which aren't in strictly increasing letter order
+        assert!(Dawg::from_bytes(&raw_bytes(&[(true, &[]), (false, &[(b'b', 0), (b'a', 0)])])).is_err());
+        assert!(Dawg::from_bytes(&raw_bytes(&[(true, &[]), (false, &[(b'a', 0), (b'a', 0)])])).is_err());
+    }
+}
@@ -1,7 +1,58 @@
-use std::collections::{HashSet};
-use std::fs::File;
-use std::io::{self, BufRead, BufReader};
+use crate::cache::fnv1a;
+use crate::dawg::Dawg;
+use crate::error::{LetterBouncedError, Result};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
+use std::sync::OnceLock;
+use tracing::{debug_span, field};
+
+/// Tags for the two spellings of a word British and American English spell differently, like `colour` and `color`
+pub const BRITISH_TAG: &str = "british";
+pub const AMERICAN_TAG: &str = "american";
+
+/**
+ * How familiar a word is, from its frequency score. The scale is logarithmic, with a point for each doubling of how
+ * often the word is used, so the raw numbers mean little to players. Labels order from obscure up to everyday, so the
+ * rarest word in a solution is the smallest label.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FrequencyLabel {
+    Obscure,
+    Rare,
+    Uncommon,
+    Common,
+    Everyday,
+}
+
+impl FrequencyLabel {
+    pub fn of(frequency: i8) -> Self {
+        match frequency {
+            22.. => FrequencyLabel::Everyday,
+            18..=21 => FrequencyLabel::Common,
+            15..=17 => FrequencyLabel::Uncommon,
+            12..=14 => FrequencyLabel::Rare,
+            _ => FrequencyLabel::Obscure,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            FrequencyLabel::Everyday => "everyday",
+            FrequencyLabel::Common => "common",
+            FrequencyLabel::Uncommon => "uncommon",
+            FrequencyLabel::Rare => "rare",
+            FrequencyLabel::Obscure => "obscure",
+        }
+    }
+}
+
+impl std::fmt::Display for FrequencyLabel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(self.as_str())
+    }
+}
 
 /**
  * Note that we depend on the wordlist already being filtered to words which are
@@ -12,53 +63,284 @@ use std::path::Path;
 pub struct Word {
     pub word: String,
     pub frequency: i8,
-    pub digraphs: HashSet<String>,
+    pub digraphs: HashSet<Digraph>,
+    // Worked out once here, since chaining and redaction checks ask for them constantly
+    pub first_letter: char,
+    pub last_letter: char,
+    /// Length in letters
+    pub length: usize,
+    /// Labels from the dictionary file like `common`, `british` or `scrabble-only`, for filtering. Usually empty.
+    pub tags: Vec<String>,
+    /// Which dictionary of a `Dictionary::layered` stack the word came from, 0 for the first (or only) one
+    pub layer: u8,
 }
 
 impl Word {
     /// Extract digraphs (consecutive letter pairs) from a word
-    fn extract_digraphs(word: &str) -> HashSet<String> {
+    fn extract_digraphs(word: &str) -> HashSet<Digraph> {
         let chars: Vec<char> = word.chars().collect();
-        let mut digraphs = HashSet::new();
+        chars.windows(2).map(|pair| Digraph::new(pair[0], pair[1])).collect()
+    }
 
-        for i in 0..chars.len() - 1 {
-            let digraph = format!("{}{}", chars[i], chars[i + 1]);
-            digraphs.insert(digraph);
+    /// Like `new`, but an empty word is an error instead of a panic, for words which come from players or callers
+    pub fn try_new(word: String, frequency: i8) -> Result<Self> {
+        if word.is_empty() {
+            return Err(LetterBouncedError::InvalidPlay("Words can't be empty".to_string()));
         }
-
-        digraphs
+        Ok(Self::new(word, frequency))
     }
 
-    /// Create a new Word with the given word string and frequency
+    /// Create a new Word with the given word string and frequency. The word must not be empty; see `try_new`.
     pub fn new(word: String, frequency: i8) -> Self {
+        let first_letter = word.chars().next().expect("words can't be empty");
         let digraphs = Self::extract_digraphs(&word);
+        let last_letter = word.chars().last().unwrap();
+        let length = word.chars().count();
         Word {
             word,
             frequency,
             digraphs,
+            first_letter,
+            last_letter,
+            length,
+            tags: Vec::new(),
+            layer: 0,
+        }
+    }
+
+    pub fn with_tags<I, S>(mut self, tags: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.tags.extend(tags.into_iter().map(|t| t.as_ref().to_lowercase()));
+        self
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+
+    /// How familiar the word is, from its frequency
+    pub fn frequency_label(&self) -> FrequencyLabel {
+        FrequencyLabel::of(self.frequency)
+    }
+}
+
+/// Two neighbouring letters packed into one number, `(first - 'a') * 26 + (second - 'a')`, so all 676 pairs fit
+/// in a u16 and sets of them hash cheaply. Pairs involving anything other than a lowercase ASCII letter can't be
+/// played on any board, and all pack to `Digraph::UNPLAYABLE`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Digraph(u16);
+
+impl Digraph {
+    pub const UNPLAYABLE: Digraph = Digraph(26 * 26);
+
+    pub fn new(first: char, second: char) -> Self {
+        match (Self::letter_index(first), Self::letter_index(second)) {
+            (Some(a), Some(b)) => Digraph(a * 26 + b),
+            _ => Self::UNPLAYABLE,
+        }
+    }
+
+    fn letter_index(c: char) -> Option<u16> {
+        c.is_ascii_lowercase().then(|| c as u16 - 'a' as u16)
+    }
+
+    /// The packed value, always below 676 except for `UNPLAYABLE`
+    pub fn index(self) -> u16 {
+        self.0
+    }
+
+    /// The two letters, or None for `UNPLAYABLE`
+    pub fn letters(self) -> Option<(char, char)> {
+        let letter = |i: u16| (b'a' + i as u8) as char;
+        (self != Self::UNPLAYABLE).then(|| (letter(self.0 / 26), letter(self.0 % 26)))
+    }
+}
+
+/// Packs a two-letter string like "oj"; anything else is `UNPLAYABLE`
+impl From<&str> for Digraph {
+    fn from(pair: &str) -> Self {
+        let mut chars = pair.chars();
+        match (chars.next(), chars.next(), chars.next()) {
+            (Some(first), Some(second), None) => Digraph::new(first, second),
+            _ => Digraph::UNPLAYABLE,
         }
     }
 }
 
+impl std::fmt::Display for Digraph {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.letters() {
+            Some((first, second)) => write!(f, "{}{}", first, second),
+            None => write!(f, "??"),
+        }
+    }
+}
+
+/// Filters for `Dictionary::query`. Build with `WordQuery::new()` and the chained setters; a word must pass
+/// every filter which is set.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WordQuery {
+    /// Substrings which must all appear in the word
+    pub contains: Vec<String>,
+    pub starts_with: Option<String>,
+    pub ends_with: Option<String>,
+    pub min_length: Option<usize>,
+    pub max_length: Option<usize>,
+    /// Crossword-style pattern like "f??gl?ve": same length as the word, with `?` matching any letter
+    pub pattern: Option<String>,
+}
+
+impl WordQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn contains(mut self, substring: &str) -> Self {
+        self.contains.push(substring.to_lowercase());
+        self
+    }
+
+    pub fn starts_with(mut self, prefix: &str) -> Self {
+        self.starts_with = Some(prefix.to_lowercase());
+        self
+    }
+
+    pub fn ends_with(mut self, suffix: &str) -> Self {
+        self.ends_with = Some(suffix.to_lowercase());
+        self
+    }
+
+    /// Inclusive length bounds; either may be left open
+    pub fn length(mut self, min_length: Option<usize>, max_length: Option<usize>) -> Self {
+        self.min_length = min_length;
+        self.max_length = max_length;
+        self
+    }
+
+    pub fn pattern(mut self, pattern: &str) -> Self {
+        self.pattern = Some(pattern.to_lowercase());
+        self
+    }
+
+    fn matches_pattern(pattern: &str, word: &str) -> bool {
+        pattern.len() == word.len() && pattern.chars().zip(word.chars()).all(|(p, c)| p == '?' || p == c)
+    }
+
+    pub fn matches(&self, word: &str) -> bool {
+        let length = word.chars().count();
+        self.contains.iter().all(|substring| word.contains(substring.as_str()))
+            && self.starts_with.as_ref().is_none_or(|prefix| word.starts_with(prefix.as_str()))
+            && self.ends_with.as_ref().is_none_or(|suffix| word.ends_with(suffix.as_str()))
+            && self.min_length.is_none_or(|min| length >= min)
+            && self.max_length.is_none_or(|max| length <= max)
+            && self.pattern.as_ref().is_none_or(|pattern| Self::matches_pattern(pattern, word))
+    }
+}
+
+/// What changed between two dictionaries, from `Dictionary::diff`. Added and removed words are most frequent
+/// first, and frequency shifts biggest first, so the changes players are likeliest to notice lead.
+#[derive(Debug, Default, PartialEq)]
+pub struct DictionaryDiff {
+    /// Words only in the newer dictionary, with their frequencies
+    pub added: Vec<(String, i8)>,
+    /// Words only in the older dictionary, with their frequencies
+    pub removed: Vec<(String, i8)>,
+    /// Words in both whose frequency changed: the word, its old frequency and its new one
+    pub shifted: Vec<(String, i8, i8)>,
+}
+
+impl DictionaryDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.shifted.is_empty()
+    }
+}
+
+/// What loading a text dictionary does with a line which isn't a word and a frequency. Blank lines are always skipped.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ParsePolicy {
+    /// Fail with the first bad line
+    Strict,
+    /// Skip bad lines, keeping them in `Dictionary::warnings` for the caller to report
+    #[default]
+    Lenient,
+    /// Skip bad lines and say nothing
+    Silent,
+}
+
+/// A line skipped while loading a dictionary
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseWarning {
+    /// Counting from 1
+    pub line: usize,
+    pub text: String,
+}
+
+impl std::fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Invalid format on line {}: {}", self.line, self.text)
+    }
+}
+
+/// Words are kept most frequent first, so each frequency's words form one contiguous tier and the words above any
+/// difficulty are a prefix; see `words_at_least`. The words can only be changed through methods which keep that
+/// order.
 #[derive(Debug)]
 pub struct Dictionary {
-    pub words: Vec<Word>,
-    pub digraphs: HashSet<String>,
+    words: Vec<Word>,
+    pub digraphs: HashSet<Digraph>,
+    /// Lines skipped when this dictionary was loaded under `ParsePolicy::Lenient`. Dictionaries derived from it,
+    /// by filtering or layering, start with none.
+    pub warnings: Vec<ParseWarning>,
+    // A hash of the words, worked out the first time it's asked for and forgotten whenever they change
+    fingerprint: OnceLock<u64>,
+    // Likewise the index behind `fuzzy_index`
+    fuzzy_index: OnceLock<BkTree>,
 }
 
 impl Dictionary {
     const DEFAULT_FREQUENCY: i8 = 15;
-    pub fn from_words(words: Vec<Word>) -> Self {
+    pub fn from_words(mut words: Vec<Word>) -> Self {
+        // Built dictionary files are already in order, so usually this is just the check. The sort is stable, so
+        // words within a tier keep their order.
+        if !words.is_sorted_by(|a, b| a.frequency >= b.frequency) {
+            words.sort_by_key(|w| std::cmp::Reverse(w.frequency));
+        }
+
         let mut valid_digraphs = HashSet::new();
 
         for word in &words {
-            valid_digraphs.extend(word.digraphs.iter().cloned());
+            valid_digraphs.extend(word.digraphs.iter().copied());
         }
 
         Dictionary {
             words,
             digraphs: valid_digraphs,
+            warnings: Vec::new(),
+            fingerprint: OnceLock::new(),
+            fuzzy_index: OnceLock::new(),
+        }
+    }
+
+    /// Stack dictionaries, highest priority first, like a personal word list over the NYT's over Collins. A word in
+    /// more than one takes its frequency and tags from the first which has it, and `Word::layer` says which that
+    /// was. There can be at most 256 layers.
+    pub fn layered(layers: Vec<Dictionary>) -> Self {
+        let mut seen = HashSet::new();
+        let mut words = Vec::new();
+        for (layer, dictionary) in layers.into_iter().enumerate() {
+            let layer = u8::try_from(layer).expect("at most 256 dictionary layers");
+            for mut word in dictionary.words {
+                if seen.insert(word.word.clone()) {
+                    word.layer = layer;
+                    words.push(word);
+                }
+            }
         }
+        // Equally frequent words keep their layer order, since the sort in from_words is stable
+        Self::from_words(words)
     }
 
     // This is only used for tests, and so it has a fake frequency
@@ -70,66 +352,667 @@ impl Dictionary {
         Self::from_words(word_frequencies)
     }
 
+    // A line is a word, its frequency, and optionally its tags separated by commas, like `colour 22 british`
     fn parse_word_line(line: &str) -> Option<Word> {
         let mut parts = line.split_whitespace();
-        match (parts.next(), parts.next()) {
-            (Some(word_str), Some(frequency_str)) => match frequency_str.parse::<i8>() {
-                Ok(frequency) => Some(Word::new(word_str.to_string(), frequency)),
+        match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(word_str), Some(frequency_str), tags, None) => match frequency_str.parse::<i8>() {
+                Ok(frequency) => Some(
+                    Word::new(word_str.to_string(), frequency)
+                        .with_tags(tags.into_iter().flat_map(|t| t.split(',')).filter(|t| !t.is_empty())),
+                ),
                 Err(_) => None,
             },
             _ => None,
         }
     }
 
+    /// Parse a text dictionary, quietly skipping any bad lines
     pub fn from_text(text: &str) -> Self {
-        let words: Vec<Word> = text
-            .lines()
-            .filter_map(Self::parse_word_line)
-            .collect();
-        Self::from_words(words)
+        Self::from_text_with_policy(text, ParsePolicy::Silent).expect("only strict parsing fails")
     }
 
-    pub fn from_bytes(data: &[u8]) -> Result<Self, String> {
-        // Convert bytes to string and use existing text parsing
-        // This provides a foundation for future binary format support
-        match std::str::from_utf8(data) {
-            Ok(text) => Ok(Self::from_text(text)),
-            Err(e) => Err(format!("Invalid UTF-8 data: {}", e)),
+    pub fn from_text_with_policy(text: &str, policy: ParsePolicy) -> Result<Self> {
+        let mut words = Vec::new();
+        let mut warnings = Vec::new();
+        for (line_num, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match Self::parse_word_line(line) {
+                Some(word) => words.push(word),
+                None => {
+                    let warning = ParseWarning { line: line_num + 1, text: line.to_string() };
+                    match policy {
+                        ParsePolicy::Strict => return Err(LetterBouncedError::DictionaryParse(warning.to_string())),
+                        ParsePolicy::Lenient => warnings.push(warning),
+                        ParsePolicy::Silent => {}
+                    }
+                }
+            }
         }
+        let mut dictionary = Self::from_words(words);
+        dictionary.warnings = warnings;
+        Ok(dictionary)
+    }
+
+    /// Parse a dictionary in either the text or the binary format, keeping any bad lines in `warnings`
+    pub fn from_bytes(data: &[u8]) -> Result<Self> {
+        Self::from_bytes_with_policy(data, ParsePolicy::Lenient)
+    }
+
+    pub fn from_bytes_with_policy(data: &[u8], policy: ParsePolicy) -> Result<Self> {
+        let span = debug_span!("load_dictionary", bytes = data.len(), words = field::Empty).entered();
+        let dictionary = if data.starts_with(crate::mapped::MAGIC) {
+            Self::from_binary(data)?
+        } else {
+            match std::str::from_utf8(data) {
+                Ok(text) => Self::from_text_with_policy(text, policy)?,
+                Err(e) => return Err(LetterBouncedError::DictionaryParse(format!("Invalid UTF-8 data: {}", e))),
+            }
+        };
+        span.record("words", dictionary.words.len());
+        Ok(dictionary)
+    }
+
+    /// Load a text dictionary, keeping any bad lines in `warnings`
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_path_with_policy(path, ParsePolicy::Lenient)
+    }
+
+    pub fn from_path_with_policy<P: AsRef<Path>>(path: P, policy: ParsePolicy) -> Result<Self> {
+        let span = debug_span!("load_dictionary", path = %path.as_ref().display(), words = field::Empty).entered();
+        let text = std::fs::read_to_string(path)?;
+        let dictionary = Self::from_text_with_policy(&text, policy)?;
+        span.record("words", dictionary.words.len());
+        Ok(dictionary)
+    }
+
+    /// The default dictionary, compiled into the binary by the `embedded-dictionary` feature
+    #[cfg(feature = "embedded-dictionary")]
+    pub fn embedded() -> Result<Self> {
+        use flate2::read::GzDecoder;
+        use std::io::Read;
+
+        static COMPRESSED: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/dictionary.txt.gz"));
+        let span = debug_span!("load_dictionary", path = "embedded", words = field::Empty).entered();
+        let mut text = String::new();
+        GzDecoder::new(COMPRESSED).read_to_string(&mut text)?;
+        let dictionary = Self::from_text(&text);
+        span.record("words", dictionary.words.len());
+        Ok(dictionary)
+    }
+
+    /// Every word, most frequent first
+    pub fn words(&self) -> &[Word] {
+        &self.words
+    }
+
+    /// Take the words, most frequent first
+    pub fn into_words(self) -> Vec<Word> {
+        self.words
+    }
+
+    /// The words at least as frequent as `min_frequency`: the tiers down to that frequency, without copying
+    pub fn words_at_least(&self, min_frequency: i8) -> &[Word] {
+        let end = self.words.partition_point(|w| w.frequency >= min_frequency);
+        &self.words[..end]
+    }
+
+    /**
+     * A hash of everything about the words that the solver uses: their spellings, frequencies and tags, in order.
+     * It's the same in every build, so it can name files. Hashing a big dictionary takes a while, so it's only done
+     * once, until the words change.
+     */
+    pub fn fingerprint(&self) -> u64 {
+        *self.fingerprint.get_or_init(|| {
+            let mut bytes = Vec::new();
+            for word in &self.words {
+                bytes.extend_from_slice(word.word.as_bytes());
+                bytes.push(b' ');
+                bytes.push(word.frequency as u8);
+                for tag in &word.tags {
+                    bytes.push(b',');
+                    bytes.extend_from_slice(tag.as_bytes());
+                }
+                bytes.push(b'\n');
+            }
+            fnv1a(&bytes)
+        })
+    }
+
+    /// Whether any word has tags, since most dictionaries have none and tag filters would then match nothing
+    pub fn has_tags(&self) -> bool {
+        self.words.iter().any(|w| !w.tags.is_empty())
+    }
+
+    /// Give `tag` to each of `words` which is in the dictionary, like marking the words some publisher accepts.
+    /// Returns how many words were tagged.
+    pub fn tag_words<I, S>(&mut self, words: I, tag: &str) -> usize
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let words: HashSet<String> = words.into_iter().map(|w| w.as_ref().to_lowercase()).collect();
+        self.fingerprint.take();
+        let mut tagged = 0;
+        for word in self.words.iter_mut().filter(|w| words.contains(&w.word)) {
+            if !word.has_tag(tag) {
+                word.tags.push(tag.to_string());
+            }
+            tagged += 1;
+        }
+        tagged
+    }
+
+    /// The entry for `word`, if the dictionary has it
+    pub fn lookup(&self, word: &str) -> Option<&Word> {
+        self.words.iter().find(|w| w.word == word)
+    }
+
+    /// A new dictionary containing only words with `tag`
+    pub fn filter_by_tag(&self, tag: &str) -> Dictionary {
+        Self::from_words(self.words.iter().filter(|w| w.has_tag(tag)).cloned().collect())
+    }
+
+    /// A new dictionary without the words tagged `tag`
+    pub fn exclude_tag(&self, tag: &str) -> Dictionary {
+        Self::from_words(self.words.iter().filter(|w| !w.has_tag(tag)).cloned().collect())
+    }
+
+    /// Tag the British and American spellings of each (british, american) pair, as read by
+    /// `parse_spelling_variants`. Returns how many words were tagged.
+    pub fn tag_spelling_variants(&mut self, pairs: &[(String, String)]) -> usize {
+        self.tag_words(pairs.iter().map(|(british, _)| british), BRITISH_TAG)
+            + self.tag_words(pairs.iter().map(|(_, american)| american), AMERICAN_TAG)
+    }
+
+    /// A new dictionary containing only words at least as frequent as `min_frequency`
+    pub fn filter_by_frequency(&self, min_frequency: i8) -> Dictionary {
+        Self::from_words(self.words_at_least(min_frequency).to_vec())
     }
 
-    pub fn from_path<P: AsRef<Path>>(path: P) -> io::Result<Self> {
-        let file = File::open(path)?;
-        let reader = BufReader::new(file);
-        let words: Vec<Word> = reader
-            .lines()
-            .map_while(Result::ok)
-            .enumerate()
-            .filter_map(|(line_num, s)| {
-                Self::parse_word_line(&s).or_else(|| {
-                    eprintln!("Invalid format on line {}: {}", line_num + 1, s);
-                    None
-                })
+    /// Teach the dictionary extra words, e.g. ones the NYT accepts but our wordlist lacks. Words already present
+    /// take the new frequency, keeping their tags and layer. Words are inserted after all words at least as frequent, so the tiers stay in
+    /// order. Empty words are skipped. Returns how many words were new.
+    pub fn extend_with_words(&mut self, words: &[(&str, i8)]) -> usize {
+        self.fingerprint.take();
+        self.fuzzy_index.take();
+        let mut added = 0;
+        for &(word, frequency) in words.iter().filter(|(word, _)| !word.is_empty()) {
+            let word = word.to_lowercase();
+            // A word already present keeps its tags and layer, and only moves to its new frequency's tier
+            let new_word = match self.words.iter().position(|w| w.word == word) {
+                Some(position) => Word {
+                    frequency,
+                    ..self.words.remove(position)
+                },
+                None => {
+                    added += 1;
+                    Word::new(word, frequency)
+                }
+            };
+            self.digraphs.extend(new_word.digraphs.iter().copied());
+            let position = self.words.partition_point(|w| w.frequency >= frequency);
+            self.words.insert(position, new_word);
+        }
+        added
+    }
+
+    /// A patch which turns this dictionary into `newer`. Each line is `+word frequency` (then any tags, as in a
+    /// dictionary file) for a word which was added or whose frequency or tags changed, or `-word` for a word which
+    /// was removed.
+    pub fn patch_to(&self, newer: &Dictionary) -> String {
+        let old_entries: HashMap<&str, (i8, &[String])> =
+            self.words.iter().map(|w| (w.word.as_str(), (w.frequency, w.tags.as_slice()))).collect();
+        let new_entries: HashMap<&str, (i8, &[String])> =
+            newer.words.iter().map(|w| (w.word.as_str(), (w.frequency, w.tags.as_slice()))).collect();
+
+        let mut removed: Vec<&str> = old_entries
+            .keys()
+            .filter(|w| !new_entries.contains_key(*w))
+            .copied()
+            .collect();
+        removed.sort();
+        let mut added: Vec<(&str, (i8, &[String]))> = new_entries
+            .iter()
+            .filter(|(w, f)| old_entries.get(*w) != Some(f))
+            .map(|(w, f)| (*w, *f))
+            .collect();
+        added.sort();
+
+        let mut patch = String::new();
+        for word in removed {
+            patch.push_str(&format!("-{}\n", word));
+        }
+        for (word, (frequency, tags)) in added {
+            if tags.is_empty() {
+                patch.push_str(&format!("+{} {}\n", word, frequency));
+            } else {
+                patch.push_str(&format!("+{} {} {}\n", word, frequency, tags.join(",")));
+            }
+        }
+        patch
+    }
+
+    /// Compare this dictionary with `newer`: the words added and removed, and whose frequency changed. Unlike
+    /// `patch_to`, this is for people to read, so changes to tags alone are left out.
+    pub fn diff(&self, newer: &Dictionary) -> DictionaryDiff {
+        let old_frequencies: HashMap<&str, i8> = self.words.iter().map(|w| (w.word.as_str(), w.frequency)).collect();
+        let new_frequencies: HashMap<&str, i8> = newer.words.iter().map(|w| (w.word.as_str(), w.frequency)).collect();
+
+        // Both lists are already most frequent first, then alphabetical in built dictionaries
+        let only_in = |words: &[Word], other: &HashMap<&str, i8>| -> Vec<(String, i8)> {
+            words.iter().filter(|w| !other.contains_key(w.word.as_str())).map(|w| (w.word.clone(), w.frequency)).collect()
+        };
+        let mut shifted: Vec<(String, i8, i8)> = newer
+            .words
+            .iter()
+            .filter_map(|w| match old_frequencies.get(w.word.as_str()) {
+                Some(&old) if old != w.frequency => Some((w.word.clone(), old, w.frequency)),
+                _ => None,
             })
             .collect();
-        Ok(Self::from_words(words))
+        shifted.sort_by(|a, b| {
+            let shift = |(_, old, new): &(String, i8, i8)| (*new as i16 - *old as i16).abs();
+            shift(b).cmp(&shift(a)).then_with(|| a.0.cmp(&b.0))
+        });
+
+        DictionaryDiff {
+            added: only_in(&newer.words, &old_frequencies),
+            removed: only_in(&self.words, &new_frequencies),
+            shifted,
+        }
+    }
+
+    /// Apply a patch made by `patch_to`. The result is sorted like a built dictionary file: most frequent first,
+    /// then alphabetically.
+    pub fn apply_patch(&mut self, patch_bytes: &[u8]) -> Result<()> {
+        let patch = std::str::from_utf8(patch_bytes).map_err(|e| LetterBouncedError::DictionaryParse(format!("Invalid UTF-8 in patch: {}", e)))?;
+
+        let mut removed = HashSet::new();
+        let mut added: HashMap<String, Word> = HashMap::new();
+        for (line_num, line) in patch.lines().enumerate() {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(word) = line.strip_prefix('-') {
+                removed.insert(word.trim().to_string());
+            } else if let Some(word) = line.strip_prefix('+').and_then(Self::parse_word_line) {
+                added.insert(word.word.clone(), word);
+            } else {
+                return Err(LetterBouncedError::DictionaryParse(format!(
+                    "Invalid patch line {}: {}",
+                    line_num + 1,
+                    line
+                )));
+            }
+        }
+
+        let mut words: Vec<Word> = std::mem::take(&mut self.words)
+            .into_iter()
+            .filter(|w| !removed.contains(&w.word) && !added.contains_key(&w.word))
+            .collect();
+        words.extend(added.into_values());
+        words.sort_by(|a, b| b.frequency.cmp(&a.frequency).then_with(|| a.word.cmp(&b.word)));
+
+        *self = Self::from_words(words);
+        Ok(())
     }
+
+    /// Build a DAWG of this dictionary's words, for prefix queries and compact serialization
+    pub fn to_dawg(&self) -> Dawg {
+        Dawg::from_words(self.words.iter().map(|w| w.word.as_str()))
+    }
+
+    /// Words matching the query, in dictionary order
+    pub fn query<'a>(&'a self, query: &'a WordQuery) -> impl Iterator<Item = &'a Word> + 'a {
+        self.words.iter().filter(|w| query.matches(&w.word))
+    }
+
+    /// Index this dictionary's words for "did you mean" lookups. It's built the first time it's asked for and
+    /// kept until the words change.
+    pub fn fuzzy_index(&self) -> &BkTree {
+        self.fuzzy_index.get_or_init(|| BkTree::new(self.words.iter().map(|w| w.word.as_str())))
+    }
+}
+
+/// Words stored end to end in one buffer and referred to by index. Much smaller than a `Vec<Word>`, with no
+/// per-word allocations and no digraph sets, so the solver, which holds a board's playable words for the whole
+/// search, keeps them in one of these. `Dictionary` stays a `Vec<Word>`: filtering and analysis want each word's
+/// digraphs, and callers borrow its words as a slice. Turn an entry back into a `Word` with `to_word` when it's
+/// needed.
+#[derive(Debug, Clone, Default)]
+pub struct WordArena {
+    text: String,
+    // ends[i] is where word i stops in `text`; it starts where word i - 1 stopped
+    ends: Vec<u32>,
+    frequencies: Vec<i8>,
+    layers: Vec<u8>,
+    // Tags by word index. Most words have none, so only those which do are in here.
+    tags: HashMap<u32, Vec<String>>,
+    // Word indices in alphabetical order, for `position`. Built on first use, and dropped when a word is added.
+    alphabetical: OnceLock<Vec<u32>>,
 }
 
+// Equal if they hold the same words, whether or not either has built its alphabetical index yet
+impl PartialEq for WordArena {
+    fn eq(&self, other: &Self) -> bool {
+        (&self.text, &self.ends, &self.frequencies, &self.layers, &self.tags)
+            == (&other.text, &other.ends, &other.frequencies, &other.layers, &other.tags)
+    }
+}
+
+impl WordArena {
+    /// Add a word, returning its index
+    pub fn push(&mut self, word: &str, frequency: i8) -> usize {
+        self.alphabetical.take();
+        self.text.push_str(word);
+        self.ends.push(self.text.len() as u32);
+        self.frequencies.push(frequency);
+        self.layers.push(0);
+        self.ends.len() - 1
+    }
+
+    /// Add a word, keeping its layer and tags as well as its frequency. Returns its index.
+    pub fn push_word(&mut self, word: &Word) -> usize {
+        let index = self.push(&word.word, word.frequency);
+        self.set_layer(index, word.layer);
+        self.set_tags(index, word.tags.clone());
+        index
+    }
+
+    pub fn set_layer(&mut self, index: usize, layer: u8) {
+        self.layers[index] = layer;
+    }
+
+    pub fn layer(&self, index: usize) -> u8 {
+        self.layers[index]
+    }
+
+    pub fn set_tags(&mut self, index: usize, tags: Vec<String>) {
+        if tags.is_empty() {
+            self.tags.remove(&(index as u32));
+        } else {
+            self.tags.insert(index as u32, tags);
+        }
+    }
+
+    pub fn tags(&self, index: usize) -> &[String] {
+        self.tags.get(&(index as u32)).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn len(&self) -> usize {
+        self.ends.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ends.is_empty()
+    }
+
+    pub fn word(&self, index: usize) -> &str {
+        let start = if index == 0 { 0 } else { self.ends[index - 1] as usize };
+        &self.text[start..self.ends[index] as usize]
+    }
+
+    pub fn frequency(&self, index: usize) -> i8 {
+        self.frequencies[index]
+    }
+
+    /// The index of `word`, if it's in the arena
+    pub fn position(&self, word: &str) -> Option<usize> {
+        let alphabetical = self.alphabetical.get_or_init(|| {
+            let mut indices: Vec<u32> = (0..self.len() as u32).collect();
+            indices.sort_by(|&a, &b| self.word(a as usize).cmp(self.word(b as usize)));
+            indices
+        });
+        alphabetical
+            .binary_search_by(|&index| self.word(index as usize).cmp(word))
+            .ok()
+            .map(|found| alphabetical[found] as usize)
+    }
+
+    /// The word at `index` as a `Word`, with its frequency, layer and tags. Its digraphs, which the arena doesn't
+    /// keep, are worked out again.
+    pub fn to_word(&self, index: usize) -> Word {
+        Word {
+            layer: self.layers[index],
+            tags: self.tags(index).to_vec(),
+            ..Word::new(self.word(index).to_string(), self.frequency(index))
+        }
+    }
+
+    /// Every word and its frequency, in index order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, i8)> + '_ {
+        (0..self.len()).map(|index| (self.word(index), self.frequency(index)))
+    }
+}
+
+/// Read a spelling variants file: on each line a British spelling and then the American one, like `colour color`.
+/// Blank lines and lines starting with '#' are skipped.
+pub fn parse_spelling_variants(text: &str) -> Result<Vec<(String, String)>> {
+    let mut pairs = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match line.split_whitespace().collect::<Vec<_>>()[..] {
+            [british, american] => pairs.push((british.to_lowercase(), american.to_lowercase())),
+            _ => {
+                return Err(LetterBouncedError::DictionaryParse(format!(
+                    "Invalid spelling variant on line {}: expected a British and an American spelling",
+                    number + 1
+                )))
+            }
+        }
+    }
+    Ok(pairs)
+}
+
+/// Levenshtein distance: how many single-letter insertions, deletions or substitutions turn `a` into `b`
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, ca) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+/**
+ * A BK-tree: each node's children are keyed by their edit distance from it, and the triangle inequality lets a
+ * lookup skip every subtree whose key is too far from the distance to the node. Finding the words within 2 edits of a
+ * typo visits a small fraction of the dictionary.
+ */
+#[derive(Debug)]
+pub struct BkTree {
+    nodes: Vec<BkNode>,
+}
+
+#[derive(Debug)]
+struct BkNode {
+    word: Box<str>,
+    // (distance from this node's word, index of the child node)
+    children: Vec<(usize, usize)>,
+}
+
+impl BkTree {
+    pub fn new<'a>(words: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut tree = BkTree { nodes: Vec::new() };
+        for word in words {
+            tree.insert(word);
+        }
+        tree
+    }
+
+    fn insert(&mut self, word: &str) {
+        let new_index = self.nodes.len();
+        if new_index > 0 {
+            let mut node = 0;
+            loop {
+                let distance = edit_distance(word, &self.nodes[node].word);
+                if distance == 0 {
+                    return;
+                }
+                match self.nodes[node].children.iter().find(|(d, _)| *d == distance) {
+                    Some(&(_, child)) => node = child,
+                    None => {
+                        self.nodes[node].children.push((distance, new_index));
+                        break;
+                    }
+                }
+            }
+        }
+        self.nodes.push(BkNode { word: word.into(), children: Vec::new() });
+    }
+
+    /// Words within `max_distance` edits of `word`, closest first. Words equally close keep the order they were
+    /// added in, so for a dictionary the most common come first.
+    pub fn within(&self, word: &str, max_distance: usize) -> Vec<(&str, usize)> {
+        let mut found = Vec::new();
+        let mut pending = if self.nodes.is_empty() { vec![] } else { vec![0] };
+        while let Some(node) = pending.pop() {
+            let distance = edit_distance(word, &self.nodes[node].word);
+            if distance <= max_distance {
+                found.push((node, distance));
+            }
+            pending.extend(
+                self.nodes[node]
+                    .children
+                    .iter()
+                    .filter(|(d, _)| d.abs_diff(distance) <= max_distance)
+                    .map(|&(_, child)| child),
+            );
+        }
+
+        found.sort_unstable_by_key(|&(node, distance)| (distance, node));
+        found.into_iter().map(|(node, distance)| (&*self.nodes[node].word, distance)).collect()
+    }
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_word_ends() {
+        let word = Word::new("forklift".to_string(), 14);
+        assert_eq!(word.first_letter, 'f');
+        assert_eq!(word.last_letter, 't');
+        assert_eq!(word.length, 8);
+
+        let word = Word::new("a".to_string(), 30);
+        assert_eq!((word.first_letter, word.last_letter, word.length), ('a', 'a', 1));
+
+        assert_eq!(Word::try_new("a".to_string(), 30).unwrap(), word);
+        assert!(matches!(Word::try_new(String::new(), 30), Err(LetterBouncedError::InvalidPlay(_))));
+        assert!(Word::new("a".to_string(), 30).digraphs.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "words can't be empty")]
+    fn test_empty_word_panics() {
+        Word::new(String::new(), 30);
+    }
+
+    #[test]
+    fn test_frequency_labels() {
+        let labels: Vec<&str> = [31, 22, 21, 18, 17, 15, 14, 12, 11, 0, -3].map(|f| FrequencyLabel::of(f).as_str()).to_vec();
+        let expected = [
+            "everyday", "everyday", "common", "common", "uncommon", "uncommon", "rare", "rare", "obscure", "obscure",
+            "obscure",
+        ];
+        assert_eq!(labels, expected);
+        assert_eq!(Word::new("forklift".to_string(), 14).frequency_label(), FrequencyLabel::Rare);
+        assert!(FrequencyLabel::Obscure < FrequencyLabel::Rare && FrequencyLabel::Common < FrequencyLabel::Everyday);
+        assert_eq!(format!("{:<8}|", FrequencyLabel::Rare), "rare    |");
+    }
+
+    #[test]
+    fn test_word_arena() {
+        let dictionary = Dictionary::from_text("and 31\nforklift 14\nzax 4 scrabble-only\n");
+        let mut arena = WordArena::default();
+        for word in &dictionary.words {
+            arena.push_word(word);
+        }
+
+        assert_eq!(arena.len(), 3);
+        assert_eq!(arena.word(0), "and");
+        assert_eq!(arena.word(1), "forklift");
+        assert_eq!(arena.frequency(2), 4);
+        assert_eq!(arena.position("zax"), Some(2));
+        assert_eq!(arena.position("and"), Some(0));
+        assert_eq!(arena.position("za"), None);
+        assert_eq!(arena.to_word(1), dictionary.words[1]);
+        assert_eq!(arena.tags(2), ["scrabble-only"]);
+        assert_eq!(arena.to_word(2), dictionary.words[2]);
+        assert_eq!(arena.iter().collect::<Vec<_>>(), vec![("and", 31), ("forklift", 14), ("zax", 4)]);
+
+        // Adding a word after a lookup still finds it
+        let before = arena.clone();
+        arena.push("aardvark", 10);
+        assert_eq!(arena.position("aardvark"), Some(3));
+        assert_ne!(arena, before);
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("forklift", "forklift"), 0);
+        assert_eq!(edit_distance("", "dojo"), 4);
+        assert_eq!(edit_distance("twangy", "twang"), 1);
+    }
+
+    #[test]
+    fn test_fuzzy_index() {
+        let mut dictionary = Dictionary::from_text("twang 25\ntwangy 20\nforklift 14\ntwig 12\ntangy 10\n");
+        let index = dictionary.fuzzy_index();
+
+        assert_eq!(index.within("twangi", 1), vec![("twang", 1), ("twangy", 1)]);
+        assert_eq!(
+            index.within("twangi", 2),
+            vec![("twang", 1), ("twangy", 1), ("tangy", 2)]
+        );
+        assert!(index.within("zzz", 2).is_empty());
+        assert!(std::ptr::eq(index, dictionary.fuzzy_index()));
+
+        // New words are found once they're added
+        dictionary.extend_with_words(&[("twangs", 16)]);
+        assert_eq!(dictionary.fuzzy_index().within("twangi", 1), vec![("twang", 1), ("twangy", 1), ("twangs", 1)]);
+    }
+
     #[test]
     fn test_extract_digraphs_simple() {
-        let expected_digraphs: HashSet<String> = ["PI", "IR", "RA", "AT", "TE"]
+        let expected_digraphs: HashSet<Digraph> = ["pi", "ir", "ra", "at", "te"]
             .iter()
-            .map(|s| s.to_string())
+            .map(|&s| Digraph::from(s))
             .collect();
 
-        let word = Word::new("PIRATE".to_string(), 15);
+        let word = Word::new("pirate".to_string(), 15);
         assert_eq!(word.digraphs, expected_digraphs);
+
+        // Letters no board can hold all pack to the same digraph
+        let word = Word::new("PIRATE".to_string(), 15);
+        assert_eq!(word.digraphs, HashSet::from([Digraph::UNPLAYABLE]));
+    }
+
+    #[test]
+    fn test_digraph_packing() {
+        assert_eq!(Digraph::new('a', 'a').index(), 0);
+        assert_eq!(Digraph::new('z', 'z').index(), 675);
+        assert_eq!(Digraph::from("oj").letters(), Some(('o', 'j')));
+        assert_eq!(Digraph::from("oj").to_string(), "oj");
+        assert_eq!(Digraph::from("ojo"), Digraph::UNPLAYABLE);
+        assert_eq!(Digraph::new('e', '\''), Digraph::UNPLAYABLE);
+        assert_eq!(Digraph::UNPLAYABLE.letters(), None);
     }
 
     #[test]
@@ -139,23 +1022,236 @@ mod tests {
 
         let dictionary = Dictionary::from_bytes(bytes).expect("Should parse valid UTF-8");
 
+        // The file is out of order, so it comes back sorted into frequency tiers, most frequent first
         assert_eq!(dictionary.words.len(), 3);
-        assert_eq!(dictionary.words[0].word, "hello");
-        assert_eq!(dictionary.words[0].frequency, 25);
-        assert_eq!(dictionary.words[1].word, "world");
-        assert_eq!(dictionary.words[1].frequency, 30);
+        assert_eq!(dictionary.words[0].word, "world");
+        assert_eq!(dictionary.words[0].frequency, 30);
+        assert_eq!(dictionary.words[1].word, "hello");
+        assert_eq!(dictionary.words[1].frequency, 25);
         assert_eq!(dictionary.words[2].word, "test");
         assert_eq!(dictionary.words[2].frequency, 15);
     }
 
+    #[test]
+    fn test_fingerprint() {
+        let mut dictionary = Dictionary::from_text("forklift 20\ntwangy 18\n");
+        let fingerprint = dictionary.fingerprint();
+        assert_eq!(dictionary.fingerprint(), fingerprint);
+        assert_eq!(Dictionary::from_text("forklift 20\ntwangy 18\n").fingerprint(), fingerprint);
+
+        // Changing the words forgets the old fingerprint
+        dictionary.tag_words(["twangy"], "rare");
+        let tagged = dictionary.fingerprint();
+        assert_ne!(tagged, fingerprint);
+        dictionary.extend_with_words(&[("zax", 4)]);
+        assert_ne!(dictionary.fingerprint(), tagged);
+    }
+
     #[test]
     fn test_from_bytes_invalid_utf8() {
         let invalid_bytes = vec![0xFF, 0xFE, 0xFD]; // Invalid UTF-8
 
         let result = Dictionary::from_bytes(&invalid_bytes);
 
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("Invalid UTF-8"));
+        assert!(matches!(
+            result,
+            Err(LetterBouncedError::DictionaryParse(message)) if message.contains("Invalid UTF-8")
+        ));
+    }
+
+    #[test]
+    fn test_parse_policy() {
+        let text = "forklift 20\n\ntwangy\nfoxglove 16 \nzax lots\n";
+
+        let dictionary = Dictionary::from_text_with_policy(text, ParsePolicy::Lenient).unwrap();
+        assert_eq!(dictionary.words.len(), 2);
+        let lines: Vec<usize> = dictionary.warnings.iter().map(|w| w.line).collect();
+        assert_eq!(lines, vec![3, 5]);
+        assert_eq!(dictionary.warnings[1].to_string(), "Invalid format on line 5: zax lots");
+
+        assert!(Dictionary::from_text_with_policy(text, ParsePolicy::Silent).unwrap().warnings.is_empty());
+        assert!(matches!(
+            Dictionary::from_text_with_policy(text, ParsePolicy::Strict),
+            Err(LetterBouncedError::DictionaryParse(message)) if message.contains("line 3")
+        ));
+        assert!(Dictionary::from_bytes(text.as_bytes()).unwrap().warnings.len() == 2);
+        // Filtered dictionaries don't carry the warnings along
+        assert!(dictionary.filter_by_frequency(0).warnings.is_empty());
+    }
+
+    #[test]
+    fn test_filter_by_frequency() {
+        let dictionary = Dictionary::from_text("dojo 20\ndoe 18\nfoxglove 16\nzax 4\n");
+        let filtered = dictionary.filter_by_frequency(17);
+
+        let words: Vec<&str> = filtered.words.iter().map(|w| w.word.as_str()).collect();
+        assert_eq!(words, vec!["dojo", "doe"]);
+        assert!(filtered.digraphs.contains(&Digraph::from("oj")));
+        assert!(!filtered.digraphs.contains(&Digraph::from("za")));
+    }
+
+    #[test]
+    fn test_words_at_least() {
+        let dictionary = Dictionary::from_text("doe 18\ndojo 20\nzax 4\nfoxglove 16\ndoh 18\n");
+
+        let words: Vec<&str> = dictionary.words.iter().map(|w| w.word.as_str()).collect();
+        assert_eq!(words, vec!["dojo", "doe", "doh", "foxglove", "zax"]);
+        let tier: Vec<&str> = dictionary.words_at_least(18).iter().map(|w| w.word.as_str()).collect();
+        assert_eq!(tier, vec!["dojo", "doe", "doh"]);
+        assert_eq!(dictionary.words_at_least(4).len(), 5);
+        assert!(dictionary.words_at_least(21).is_empty());
+    }
+
+    #[cfg(feature = "embedded-dictionary")]
+    #[test]
+    fn test_embedded() {
+        let dictionary = Dictionary::embedded().unwrap();
+        assert!(dictionary.words.len() > 100_000);
+        assert_eq!(dictionary.words[0].word, "and");
+    }
+
+    #[test]
+    fn test_extend_with_words() {
+        let mut dictionary = Dictionary::from_text("and 31\ndojo 20\nzax 4\n");
+        let added = dictionary.extend_with_words(&[("Qajaq", 10), ("zax", 25), ("", 10)]);
+
+        assert_eq!(added, 1);
+        let words: Vec<&str> = dictionary.words.iter().map(|w| w.word.as_str()).collect();
+        assert_eq!(words, vec!["and", "zax", "dojo", "qajaq"]);
+        assert!(dictionary.digraphs.contains(&Digraph::from("aj")));
+    }
+
+    #[test]
+    fn test_extend_with_words_keeps_tags() {
+        let mut dictionary = Dictionary::from_text("and 31\ncolour 18 nyt,british\nzax 4\n");
+        assert_eq!(dictionary.extend_with_words(&[("colour", 25)]), 0);
+
+        let colour = dictionary.words.iter().find(|w| w.word == "colour").unwrap();
+        assert_eq!(colour.frequency, 25);
+        assert_eq!(colour.tags, vec!["nyt", "british"]);
+        assert_eq!(dictionary.filter_by_tag("nyt").words.len(), 1);
     }
-}
 
+    #[test]
+    fn test_patch_round_trip() {
+        let mut old = Dictionary::from_text("and 31\ndojo 20\nfoxglove 16\nzax 4\n");
+        let new = Dictionary::from_text("and 31\ndojo 21\nequity 18\nfoxglove 16\n");
+
+        let patch = old.patch_to(&new);
+        assert_eq!(patch, "-zax\n+dojo 21\n+equity 18\n");
+
+        old.apply_patch(patch.as_bytes()).unwrap();
+        assert_eq!(old.words, new.words);
+        assert_eq!(old.digraphs, new.digraphs);
+    }
+
+    #[test]
+    fn test_diff() {
+        let old = Dictionary::from_text("and 31\ndojo 20\nfoxglove 16\nqi 12\nzax 4\n");
+        let new = Dictionary::from_text("and 31 common\ndojo 21\nequity 18\nfoxglove 12\nqi 12\n");
+
+        let diff = old.diff(&new);
+        assert_eq!(diff.added, vec![("equity".to_string(), 18)]);
+        assert_eq!(diff.removed, vec![("zax".to_string(), 4)]);
+        // Biggest shift first; AND only gained a tag
+        assert_eq!(diff.shifted, vec![("foxglove".to_string(), 16, 12), ("dojo".to_string(), 20, 21)]);
+        assert!(!diff.is_empty());
+        assert!(old.diff(&old).is_empty());
+    }
+
+    #[test]
+    fn test_word_tags() {
+        let dictionary = Dictionary::from_text("colour 22 british,Common\nqi 12 scrabble-only\ndojo 20\nbad 20 a b\n");
+
+        let tags: Vec<(&str, &[String])> = dictionary.words.iter().map(|w| (w.word.as_str(), w.tags.as_slice())).collect();
+        assert_eq!(
+            tags,
+            vec![
+                ("colour", &["british".to_string(), "common".to_string()][..]),
+                ("dojo", &[][..]),
+                ("qi", &["scrabble-only".to_string()][..]),
+            ]
+        );
+        assert!(dictionary.words[0].has_tag("common"));
+        assert!(dictionary.has_tags());
+        assert!(!Dictionary::from_text("dojo 20\n").has_tags());
+
+        let mut old = Dictionary::from_text("colour 22\ndojo 20\n");
+        let patch = old.patch_to(&dictionary);
+        assert_eq!(patch, "+colour 22 british,common\n+qi 12 scrabble-only\n");
+        old.apply_patch(patch.as_bytes()).unwrap();
+        assert_eq!(old.words, dictionary.words);
+    }
+
+    #[test]
+    fn test_layered() {
+        let personal = Dictionary::from_text("qajaq 20\ndojo 25 mine\n");
+        let base = Dictionary::from_text("and 31\ndojo 20\nzax 4\n");
+        let dictionary = Dictionary::layered(vec![personal, base]);
+
+        let words: Vec<(&str, i8, u8)> = dictionary.words.iter().map(|w| (w.word.as_str(), w.frequency, w.layer)).collect();
+        assert_eq!(words, vec![("and", 31, 1), ("dojo", 25, 0), ("qajaq", 20, 0), ("zax", 4, 1)]);
+        assert_eq!(dictionary.lookup("dojo").unwrap().tags, vec!["mine"]);
+        assert!(dictionary.lookup("quiz").is_none());
+        let mut arena = WordArena::default();
+        arena.push_word(&dictionary.words[3]);
+        assert_eq!(arena.to_word(0).layer, 1);
+    }
+
+    #[test]
+    fn test_tag_words() {
+        let mut dictionary = Dictionary::from_text("and 31\ndojo 20 nyt\nqi 12\n");
+        assert_eq!(dictionary.tag_words(["AND", "dojo", "zax"], "nyt"), 2);
+
+        let words: Vec<String> = dictionary.filter_by_tag("nyt").words.into_iter().map(|w| w.word).collect();
+        assert_eq!(words, vec!["and", "dojo"]);
+        assert_eq!(dictionary.words[1].tags, vec!["nyt"]);
+    }
+
+    #[test]
+    fn test_spelling_variants() {
+        let pairs = parse_spelling_variants("# British American\ncolour color\n\nCentre  center\n").unwrap();
+        assert_eq!(pairs, vec![("colour".to_string(), "color".to_string()), ("centre".to_string(), "center".to_string())]);
+        assert!(parse_spelling_variants("colour color\ngrey\n").is_err());
+
+        let mut dictionary = Dictionary::from_text("color 22\ncolour 20\ncenter 20\nqi 12\n");
+        assert_eq!(dictionary.tag_spelling_variants(&pairs), 3);
+        let words = |dictionary: Dictionary| dictionary.words.into_iter().map(|w| w.word).collect::<Vec<_>>();
+        assert_eq!(words(dictionary.exclude_tag(BRITISH_TAG)), vec!["color", "center", "qi"]);
+        assert_eq!(words(dictionary.exclude_tag(AMERICAN_TAG)), vec!["colour", "qi"]);
+    }
+
+    #[test]
+    fn test_apply_patch_invalid_line() {
+        let mut dictionary = Dictionary::from_text("dojo 20\n");
+        let result = dictionary.apply_patch(b"+dojo\n");
+
+        assert!(matches!(
+            result,
+            Err(LetterBouncedError::DictionaryParse(message)) if message.contains("line 1")
+        ));
+    }
+
+    #[test]
+    fn test_to_dawg() {
+        let dictionary = Dictionary::from_text("dojo 20\ndoe 18\nfoxglove 16\n");
+        let dawg = dictionary.to_dawg();
+
+        assert_eq!(dawg.len(), 3);
+        assert!(dawg.contains("foxglove"));
+        assert_eq!(dawg.words_with_prefix("do"), vec!["doe", "dojo"]);
+    }
+
+    #[test]
+    fn test_query() {
+        let dictionary = Dictionary::from_text("fixture 20\nfax 18\nflax 12\nquixotic 10\n");
+        let found = |query: WordQuery| dictionary.query(&query).map(|w| w.word.clone()).collect::<Vec<_>>();
+
+        assert_eq!(found(WordQuery::new().contains("x")), vec!["fixture", "fax", "flax", "quixotic"]);
+        assert_eq!(found(WordQuery::new().starts_with("f").ends_with("x")), vec!["fax", "flax"]);
+        assert_eq!(found(WordQuery::new().contains("x").length(Some(4), Some(7))), vec!["fixture", "flax"]);
+        assert_eq!(found(WordQuery::new().contains("qu").contains("ix")), vec!["quixotic"]);
+        assert_eq!(found(WordQuery::new().pattern("f??x")), vec!["flax"]);
+        assert_eq!(found(WordQuery::new().pattern("F?X")), vec!["fax"]);
+    }
+}
@@ -0,0 +1,79 @@
+use std::io;
+use thiserror::Error;
+
+/*
+ * The one error type for everything the library can fail at, so callers can match on what went wrong instead of
+ * picking through message strings. The messages are the ones the library has always printed.
+ */
+
+#[derive(Debug, Error)]
+pub enum LetterBouncedError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    /// The sides don't make a playable board
+    #[error("{0}")]
+    Board(String),
+
+    /// Dictionary, patch or DAWG data which can't be parsed
+    #[error("{0}")]
+    DictionaryParse(String),
+
+    /// JSON which can't be read or written
+    #[error("Invalid JSON: {0}")]
+    Serialization(#[from] serde_json::Error),
+
+    /// A SolverConfig which can't produce any solutions
+    #[error("{0}")]
+    SolverConfig(String),
+
+    /// Words said to have been played which can't be played on the board
+    #[error("{0}")]
+    InvalidPlay(String),
+
+    /// A word in a player's answer which isn't a playable dictionary word, with close ones they may have meant
+    #[error("'{word}' isn't a word that can be played on this board{}", did_you_mean(.suggestions))]
+    UnknownWord { word: String, suggestions: Vec<String> },
+
+    /// The wasm module's dictionary can only be initialized once; use `replace_dictionary` to swap it
+    #[error("Dictionary already initialized")]
+    DictionaryAlreadyInitialized,
+
+    /// A wasm solve was requested before any dictionary was loaded
+    #[error("Dictionary not initialized")]
+    DictionaryNotInitialized,
+
+    /// A board which can't have any solutions with the dictionary, and why
+    #[error("{0}")]
+    UnsolvableBoard(String),
+
+    /// The board generator was asked for something impossible, or couldn't find a board in time
+    #[error("{0}")]
+    BoardGeneration(String),
+
+    /// A puzzle archive request which doesn't make sense, like an unknown or badly formed date
+    #[error("{0}")]
+    Archive(String),
+
+    /// A solver pack from a different version, or whose data doesn't fit together
+    #[error("Invalid solver pack: {0}")]
+    InvalidPack(String),
+
+    /// A tournament entries file which can't be parsed, or an unknown ruleset
+    #[error("{0}")]
+    Tournament(String),
+
+    /// A screenshot which can't be read as a board
+    #[error("Can't read the board from the image: {0}")]
+    Image(String),
+}
+
+fn did_you_mean(suggestions: &[String]) -> String {
+    if suggestions.is_empty() {
+        String::new()
+    } else {
+        format!(". Did you mean {}?", suggestions.join(", "))
+    }
+}
+
+pub type Result<T> = std::result::Result<T, LetterBouncedError>;
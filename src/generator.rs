@@ -0,0 +1,800 @@
+use crate::board::Board;
+use crate::dictionary::{Dictionary, Word};
+use crate::error::{LetterBouncedError, Result};
+use crate::playable::PlayableIndex;
+use crate::solver::Solver;
+use std::collections::HashSet;
+use std::fmt;
+
+/*
+ * Make new practice boards. We work backwards from an answer, the way a puzzle setter would: pick a common word,
+ * pick a second common word which chains from it and brings the distinct letters up to exactly the size of the
+ * board, then deal those letters onto the sides so that neither word ever stays on one side. The pair guarantees
+ * the board has at least one friendly two-word solution; the solver then checks it has as many as asked for.
+ *
+ * Generation is deterministic for a given seed and dictionary, so a seed can be shared like a puzzle number.
+ *
+ * The config can also constrain the board's letters, for themed or handcrafted puzzles: letters it must have, how
+ * many rare letters it may have, and how many vowels. These only decide which word pairs are tried, so they cost
+ * nothing once a pair is found, but tight ones can use up `max_attempts` quickly.
+ *
+ * `improve` works on an existing board instead, suggesting single-letter swaps which make it easier or harder, and
+ * `from_words` starts from the answers a puzzle setter has in mind, building a board they can all be played on.
+ * `reverse` goes further, looking for boards on which a whole chain of words is an answer, and optionally the best.
+ */
+
+// A board can't have more than 26 distinct letters
+const MAX_SIDE_LENGTH: usize = 6;
+
+// `improve` stops counting a board's two-word solutions here
+const MAX_COUNTED_SOLUTIONS: u16 = u16::MAX;
+
+/// Letters counted by `GeneratorConfig::max_rare_letters`: the ones worth 5 or more in Scrabble
+pub const RARE_LETTERS: &str = "jkqxz";
+
+/// Letters counted by `GeneratorConfig::vowels`. Y isn't one, though it often does the job of one.
+pub const VOWELS: &str = "aeiou";
+
+// `from_words` fills spare places on a board with these, most common first, so the filler helps rather than hinders
+const PADDING_LETTERS: &str = "etaoinsrhldcumfpgwybvkxjqz";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneratorConfig {
+    side_length: usize,
+    min_two_word_solutions: u16,
+    min_frequency: i8,
+    max_attempts: usize,
+    required_letters: Vec<char>,
+    max_rare_letters: Option<usize>,
+    min_vowels: usize,
+    max_vowels: usize,
+    unique_two_word: bool,
+}
+
+impl Default for GeneratorConfig {
+    fn default() -> Self {
+        GeneratorConfig {
+            side_length: 3,
+            min_two_word_solutions: 1,
+            min_frequency: 18,
+            max_attempts: 1000,
+            required_letters: Vec::new(),
+            max_rare_letters: None,
+            min_vowels: 0,
+            max_vowels: usize::MAX,
+            unique_two_word: false,
+        }
+    }
+}
+
+impl GeneratorConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Letters per side; the NYT puzzle has 3
+    pub fn side_length(mut self, side_length: usize) -> Self {
+        self.side_length = side_length;
+        self
+    }
+
+    /// Only accept boards with at least this many two-word solutions
+    pub fn min_two_word_solutions(mut self, min_two_word_solutions: u16) -> Self {
+        self.min_two_word_solutions = min_two_word_solutions;
+        self
+    }
+
+    /// How common the two words the board is built around must be. Solutions are still counted over the
+    /// whole dictionary.
+    pub fn min_frequency(mut self, min_frequency: i8) -> Self {
+        self.min_frequency = min_frequency;
+        self
+    }
+
+    /// Give up after trying this many word pairs
+    pub fn max_attempts(mut self, max_attempts: usize) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Letters the board must have, like "qz". Case doesn't matter.
+    pub fn required_letters(mut self, letters: &str) -> Self {
+        self.required_letters = letters.chars().map(|c| c.to_ascii_lowercase()).collect();
+        self
+    }
+
+    /// The most letters from `RARE_LETTERS` the board may have
+    pub fn max_rare_letters(mut self, max_rare_letters: usize) -> Self {
+        self.max_rare_letters = Some(max_rare_letters);
+        self
+    }
+
+    /// How many letters from `VOWELS` the board must have, inclusive
+    pub fn vowels(mut self, min: usize, max: usize) -> Self {
+        self.min_vowels = min;
+        self.max_vowels = max;
+        self
+    }
+
+    /// Only accept boards with exactly one two-word solution, the pair the board was built around, and no
+    /// one-word solution. Most boards have dozens, so this takes many more attempts.
+    pub fn unique_two_word(mut self, unique_two_word: bool) -> Self {
+        self.unique_two_word = unique_two_word;
+        self
+    }
+
+    // Whether a board with these letters would meet the letter constraints
+    fn allows_letters(&self, letters: &HashSet<char>) -> bool {
+        let vowels = count_from(letters, VOWELS);
+        self.required_letters.iter().all(|c| letters.contains(c))
+            && self.max_rare_letters.is_none_or(|max| count_from(letters, RARE_LETTERS) <= max)
+            && (self.min_vowels..=self.max_vowels).contains(&vowels)
+    }
+
+    // Whether some board could still meet the letter constraints if these letters were on it. Only the maximums
+    // can rule a word out early; the other word may bring the required letters and vowels.
+    fn could_allow_letters(&self, letters: &HashSet<char>) -> bool {
+        self.max_rare_letters.is_none_or(|max| count_from(letters, RARE_LETTERS) <= max)
+            && count_from(letters, VOWELS) <= self.max_vowels
+    }
+
+    fn check(&self) -> Result<()> {
+        if self.side_length == 0 || self.side_length > MAX_SIDE_LENGTH {
+            return Err(LetterBouncedError::BoardGeneration(format!(
+                "Side length must be between 1 and {}",
+                MAX_SIDE_LENGTH
+            )));
+        }
+        let letter_count = self.side_length * 4;
+        let required: HashSet<char> = self.required_letters.iter().copied().collect();
+        if let Some(bad) = required.iter().find(|c| !c.is_ascii_lowercase()) {
+            return Err(LetterBouncedError::BoardGeneration(format!("'{}' isn't a letter", bad)));
+        }
+        if required.len() > letter_count {
+            return Err(LetterBouncedError::BoardGeneration(format!(
+                "Can't fit {} required letters on a board of {}",
+                required.len(),
+                letter_count
+            )));
+        }
+        if let Some(max) = self.max_rare_letters {
+            if count_from(&required, RARE_LETTERS) > max {
+                return Err(LetterBouncedError::BoardGeneration(format!(
+                    "The required letters include more than {} rare letter{}",
+                    max,
+                    if max == 1 { "" } else { "s" }
+                )));
+            }
+        }
+        if self.unique_two_word && self.min_two_word_solutions > 1 {
+            return Err(LetterBouncedError::BoardGeneration(format!(
+                "A board with a unique two-word solution can't have {} of them",
+                self.min_two_word_solutions
+            )));
+        }
+        if self.min_vowels > self.max_vowels || self.min_vowels > letter_count {
+            return Err(LetterBouncedError::BoardGeneration(format!(
+                "No board of {} letters can have between {} and {} vowels",
+                letter_count, self.min_vowels, self.max_vowels
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// A small, fast PRNG (SplitMix64). Good enough for shuffling letters, and the same on every platform.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in 0..n; n must not be 0
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+/// Generate a board from `seed`. Fails if the config is impossible or no suitable board turned up within
+/// `max_attempts` word pairs.
+pub fn generate_board(dictionary: &Dictionary, seed: u64, config: &GeneratorConfig) -> Result<Board> {
+    config.check()?;
+    let letter_count = config.side_length * 4;
+
+    let seed_words: Vec<&Word> = dictionary
+        .words()
+        .iter()
+        .filter(|w| w.frequency >= config.min_frequency && !has_doubled_letter(&w.word))
+        .filter(|w| {
+            let letters = distinct_letters(&w.word);
+            letters.len() < letter_count && config.could_allow_letters(&letters)
+        })
+        .collect();
+    if seed_words.is_empty() {
+        return Err(LetterBouncedError::BoardGeneration(
+            "No words in the dictionary are common enough to build a board around".to_string(),
+        ));
+    }
+    // With required letters, start from a word with one of them, or a pair with all of them would be rare luck
+    let first_words: Vec<&Word> = if config.required_letters.is_empty() {
+        seed_words.clone()
+    } else {
+        seed_words.iter().copied().filter(|w| config.required_letters.iter().any(|&c| w.word.contains(c))).collect()
+    };
+    if first_words.is_empty() {
+        return Err(LetterBouncedError::BoardGeneration(
+            "No common enough words have any of the required letters".to_string(),
+        ));
+    }
+
+    let mut rng = Rng(seed);
+    for _ in 0..config.max_attempts {
+        let first = first_words[rng.below(first_words.len())];
+        let first_letters = distinct_letters(&first.word);
+        let last_letter = first.last_letter;
+
+        let seconds: Vec<&Word> = seed_words
+            .iter()
+            .filter(|w| w.first_letter == last_letter)
+            .filter(|w| {
+                let letters: HashSet<char> = first_letters.union(&distinct_letters(&w.word)).copied().collect();
+                letters.len() == letter_count && config.allows_letters(&letters)
+            })
+            .copied()
+            .collect();
+        if seconds.is_empty() {
+            continue;
+        }
+        let second = seconds[rng.below(seconds.len())];
+
+        let Some(sides) = deal_letters(&[&first.word, &second.word], config.side_length, &mut rng) else {
+            continue;
+        };
+        let board = Board::from_sides(sides)?;
+
+        let wanted = config.min_two_word_solutions;
+        if wanted > 0 {
+            let solver = Solver::new(board.clone(), dictionary, wanted);
+            if solver.solve_exact_length(2).len() < wanted as usize {
+                continue;
+            }
+        }
+        if config.unique_two_word && !has_unique_two_word_solution(&board, dictionary) {
+            continue;
+        }
+        return Ok(board);
+    }
+
+    Err(LetterBouncedError::BoardGeneration(format!(
+        "No board found after {} attempts",
+        config.max_attempts
+    )))
+}
+
+/// A board built by `from_words`
+#[derive(Debug, Clone)]
+pub struct ThemedBoard {
+    pub board: Board,
+    /// Letters added to fill the board, which none of the words use
+    pub padding: Vec<char>,
+}
+
+/**
+ * Build a board on which every one of `words` can be played, or explain why there isn't one. The board is as small
+ * as the words allow, but never smaller than the standard 3 letters a side; places the words' letters don't fill
+ * get common letters. Neighbouring letters in each word go on different sides, which is the same dealing problem
+ * `generate_board` solves for its pair of words.
+ */
+pub fn from_words(words: &[&str]) -> Result<ThemedBoard> {
+    let error = |message: String| Err(LetterBouncedError::BoardGeneration(message));
+    let words = playable_words(words)?;
+
+    let letters: HashSet<char> = words.iter().flat_map(|w| w.chars()).collect();
+    if letters.len() > MAX_SIDE_LENGTH * 4 {
+        return error(format!(
+            "The words have {} different letters, but a board has room for at most {}",
+            letters.len(),
+            MAX_SIDE_LENGTH * 4
+        ));
+    }
+
+    let word_refs: Vec<&str> = words.iter().map(String::as_str).collect();
+    let smallest = letters.len().div_ceil(4).max(3);
+    for side_length in smallest..=MAX_SIDE_LENGTH {
+        let Some(mut sides) = deal_letters(&word_refs, side_length, &mut Rng(0)) else {
+            continue;
+        };
+        let mut padding = Vec::new();
+        let mut spare = PADDING_LETTERS.chars().filter(|c| !letters.contains(c));
+        for side in sides.iter_mut() {
+            while side.len() < side_length {
+                let letter = spare.next().expect("a board has fewer places than the alphabet has letters");
+                side.push(letter);
+                padding.push(letter);
+            }
+        }
+        let board = Board::from_sides(sides)?;
+        return Ok(ThemedBoard { board, padding });
+    }
+    error(format!(
+        "The letters of {} can't be split over four sides so that no two neighbouring letters share a side",
+        words.join(", ").to_uppercase()
+    ))
+}
+
+/// Which boards `reverse` accepts
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReverseConfig {
+    max_boards: usize,
+    no_shorter: bool,
+    unique: bool,
+    max_attempts: usize,
+}
+
+impl Default for ReverseConfig {
+    fn default() -> Self {
+        ReverseConfig {
+            max_boards: 5,
+            no_shorter: false,
+            unique: false,
+            max_attempts: 1000,
+        }
+    }
+}
+
+impl ReverseConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop after finding this many boards
+    pub fn max_boards(mut self, max_boards: usize) -> Self {
+        self.max_boards = max_boards;
+        self
+    }
+
+    /// Only accept boards with no solution of fewer words than the chain
+    pub fn no_shorter(mut self, no_shorter: bool) -> Self {
+        self.no_shorter = no_shorter;
+        self
+    }
+
+    /// Only accept boards where the chain is the only solution of its length, and none is shorter
+    pub fn unique(mut self, unique: bool) -> Self {
+        self.unique = unique;
+        self
+    }
+
+    /// Give up after trying this many ways of dealing the chain's letters
+    pub fn max_attempts(mut self, max_attempts: usize) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+}
+
+/**
+ * Find boards on which `chain` is a solution. A solution uses every letter on the board, so the board's letters
+ * are exactly the chain's, and they have to fill four equal sides. Each attempt deals them onto the sides
+ * differently, from `seed`; boards which are the same but for the order of letters and sides are only counted once.
+ *
+ * The chain's words needn't be in `dictionary`, which is only used to check the `no_shorter` and `unique`
+ * conditions. Fails if the chain can't be a solution on any board; finding no board which meets the conditions
+ * within `max_attempts` just gives an empty list.
+ */
+pub fn reverse(chain: &[&str], dictionary: &Dictionary, seed: u64, config: &ReverseConfig) -> Result<Vec<Board>> {
+    let error = |message: String| Err(LetterBouncedError::BoardGeneration(message));
+    let words = playable_words(chain)?;
+    for pair in words.windows(2) {
+        if pair[0].chars().last() != pair[1].chars().next() {
+            return error(format!(
+                "{} doesn't start with the letter {} ends with",
+                pair[1].to_uppercase(),
+                pair[0].to_uppercase()
+            ));
+        }
+    }
+    let letters: HashSet<char> = words.iter().flat_map(|w| w.chars()).collect();
+    if !letters.len().is_multiple_of(4) || letters.len() > MAX_SIDE_LENGTH * 4 {
+        return error(format!(
+            "The chain has {} different letters, and a board's letters fill four sides of 1 to {}",
+            letters.len(),
+            MAX_SIDE_LENGTH
+        ));
+    }
+    let side_length = letters.len() / 4;
+
+    let word_refs: Vec<&str> = words.iter().map(String::as_str).collect();
+    let mut rng = Rng(seed);
+    let mut seen = HashSet::new();
+    let mut boards = Vec::new();
+    for _ in 0..config.max_attempts {
+        if boards.len() >= config.max_boards {
+            break;
+        }
+        let Some(mut sides) = deal_letters(&word_refs, side_length, &mut rng) else {
+            // The dealing backtracks through every arrangement, so no other attempt will find one either
+            return error(format!(
+                "The letters of {} can't be split over four sides so that no two neighbouring letters share a side",
+                words.join("-").to_uppercase()
+            ));
+        };
+        for side in sides.iter_mut() {
+            let mut letters: Vec<char> = side.chars().collect();
+            letters.sort_unstable();
+            *side = letters.into_iter().collect();
+        }
+        let mut canonical = sides.clone();
+        canonical.sort_unstable();
+        if !seen.insert(canonical) {
+            continue;
+        }
+
+        let board = Board::from_sides(sides)?;
+        if meets_reverse_conditions(&board, dictionary, &words, config) {
+            boards.push(board);
+        }
+    }
+    Ok(boards)
+}
+
+fn meets_reverse_conditions(board: &Board, dictionary: &Dictionary, chain: &[String], config: &ReverseConfig) -> bool {
+    if !config.no_shorter && !config.unique {
+        return true;
+    }
+    let playable = board.playable_dictionary(dictionary);
+    let solver = Solver::new(board.clone(), &playable, 2);
+    if (1..chain.len()).any(|length| !solver.solve_exact_length(length).is_empty()) {
+        return false;
+    }
+    !config.unique
+        || solver.solve_exact_length(chain.len()).iter().all(|solution| {
+            solution.words.iter().map(|w| w.word.as_str()).eq(chain.iter().map(String::as_str))
+        })
+}
+
+// Lowercase `words`, checking that a board could have each of them
+fn playable_words(words: &[&str]) -> Result<Vec<String>> {
+    let error = |message: String| Err(LetterBouncedError::BoardGeneration(message));
+    if words.is_empty() {
+        return error("No words to build a board from".to_string());
+    }
+    let words: Vec<String> = words.iter().map(|w| w.to_lowercase()).collect();
+    for word in &words {
+        if let Some(bad) = word.chars().find(|c| !c.is_ascii_lowercase()) {
+            return error(format!("{} has '{}', which isn't a letter", word.to_uppercase(), bad));
+        }
+        if let Some(pair) = word.as_bytes().windows(2).find(|pair| pair[0] == pair[1]) {
+            return error(format!(
+                "{} has a doubled {}, and a letter can't follow itself",
+                word.to_uppercase(),
+                (pair[0] as char).to_ascii_uppercase()
+            ));
+        }
+    }
+    Ok(words)
+}
+
+/// A single-letter swap suggested by `improve`
+#[derive(Debug, Clone)]
+pub struct Mutation {
+    /// The board letter to replace
+    pub from: char,
+    pub to: char,
+    /// The board with the swap made
+    pub board: Board,
+    pub two_word_solutions: usize,
+}
+
+impl fmt::Display for Mutation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "replace {} with {} for {} two-word solution{}",
+            self.from.to_ascii_uppercase(),
+            self.to.to_ascii_uppercase(),
+            self.two_word_solutions,
+            if self.two_word_solutions == 1 { "" } else { "s" }
+        )
+    }
+}
+
+/**
+ * Suggest single-letter swaps which bring the board's number of two-word solutions closer to `target`, closest
+ * first. Each board letter is tried against every letter not on the board, which is 168 solves for a standard
+ * board, so a `PlayableIndex` keeps the playable words up to date between them instead of refiltering the dictionary.
+ */
+pub fn improve(board: &Board, dictionary: &Dictionary, target: usize) -> Vec<Mutation> {
+    let distance = |count: usize| count.abs_diff(target);
+    let current = distance(two_word_solutions(board, dictionary));
+    let board_letters: HashSet<char> = board.sides.iter().flat_map(|side| side.chars()).collect();
+    let mut index = PlayableIndex::new(board.clone(), dictionary);
+
+    let mut mutations = Vec::new();
+    for (side, letters) in board.sides.iter().enumerate() {
+        for (position, from) in letters.chars().enumerate() {
+            for to in ('a'..='z').filter(|c| !board_letters.contains(c)) {
+                if index.replace_letter(side, position, to).is_err() {
+                    continue;
+                }
+                let count = two_word_solutions(index.board(), &index.playable_dictionary());
+                if distance(count) < current {
+                    let board = index.board().clone();
+                    mutations.push(Mutation { from, to, board, two_word_solutions: count });
+                }
+            }
+            index.replace_letter(side, position, from).expect("putting the original letter back is always valid");
+        }
+    }
+    mutations.sort_by_key(|mutation| distance(mutation.two_word_solutions));
+    mutations
+}
+
+fn two_word_solutions(board: &Board, dictionary: &Dictionary) -> usize {
+    Solver::new(board.clone(), dictionary, MAX_COUNTED_SOLUTIONS).solve_exact_length(2).len()
+}
+
+// Whether the board has exactly one two-word solution and no one-word one. A search which stops at the second
+// solution is still a complete enumeration for the boards this accepts: it only stops early on ones it rejects.
+// With no one-word solution, no two-word solution can be redundant either, so none are filtered out unseen.
+fn has_unique_two_word_solution(board: &Board, dictionary: &Dictionary) -> bool {
+    let solver = Solver::new(board.clone(), dictionary, 2);
+    solver.solve_exact_length(1).is_empty() && solver.solve_exact_length(2).len() == 1
+}
+
+fn has_doubled_letter(word: &str) -> bool {
+    word.as_bytes().windows(2).any(|pair| pair[0] == pair[1])
+}
+
+fn distinct_letters(word: &str) -> HashSet<char> {
+    word.chars().collect()
+}
+
+fn count_from(letters: &HashSet<char>, from: &str) -> usize {
+    from.chars().filter(|c| letters.contains(c)).count()
+}
+
+/**
+ * Put every letter of `words` on one of four sides of `side_length` letters, so that no two neighbouring letters
+ * in any word share a side. This is graph colouring with a capacity per colour; with at most 24 letters, plain
+ * backtracking in a random order is quick.
+ */
+fn deal_letters(words: &[&str], side_length: usize, rng: &mut Rng) -> Option<Vec<String>> {
+    let mut letters: Vec<char> = words.iter().flat_map(|w| w.chars()).collect::<HashSet<_>>().into_iter().collect();
+    letters.sort_unstable();
+    rng.shuffle(&mut letters);
+
+    let neighbours: HashSet<(char, char)> = words
+        .iter()
+        .flat_map(|w| w.chars().zip(w.chars().skip(1)))
+        .flat_map(|(a, b)| [(a, b), (b, a)])
+        .collect();
+
+    let mut side_order = [0, 1, 2, 3];
+    rng.shuffle(&mut side_order);
+
+    let mut sides: Vec<Vec<char>> = vec![Vec::new(); 4];
+    if deal_from(&letters, &neighbours, side_length, &side_order, &mut sides) {
+        Some(sides.into_iter().map(|side| side.into_iter().collect()).collect())
+    } else {
+        None
+    }
+}
+
+fn deal_from(
+    letters: &[char],
+    neighbours: &HashSet<(char, char)>,
+    side_length: usize,
+    side_order: &[usize; 4],
+    sides: &mut [Vec<char>],
+) -> bool {
+    let Some((&letter, rest)) = letters.split_first() else {
+        return true;
+    };
+
+    for &side in side_order {
+        let fits = sides[side].len() < side_length
+            && sides[side].iter().all(|&other| !neighbours.contains(&(letter, other)));
+        if fits {
+            sides[side].push(letter);
+            if deal_from(rest, neighbours, side_length, side_order, sides) {
+                return true;
+            }
+            sides[side].pop();
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dictionary::Digraph;
+
+    fn dictionary() -> Dictionary {
+        Dictionary::from_text("forklift 20\ntwangy 20\nfork 20\nkilt 20\ntwang 20\nglow 20\n")
+    }
+
+    #[test]
+    fn test_generate_board_is_solvable_and_repeatable() {
+        let dictionary = dictionary();
+        let config = GeneratorConfig::new().min_two_word_solutions(1);
+
+        let board = generate_board(&dictionary, 7, &config).unwrap();
+        let letters: HashSet<char> = board.sides.iter().flat_map(|s| s.chars()).collect();
+        assert_eq!(letters, distinct_letters("forklifttwangy"));
+        assert!(!Solver::new(board.clone(), &dictionary, 1).solve_exact_length(2).is_empty());
+
+        let again = generate_board(&dictionary, 7, &config).unwrap();
+        assert_eq!(board.sides, again.sides);
+    }
+
+    #[test]
+    fn test_generate_board_gives_up() {
+        let dictionary = dictionary();
+
+        let config = GeneratorConfig::new().min_two_word_solutions(2).max_attempts(50);
+        assert!(matches!(
+            generate_board(&dictionary, 1, &config),
+            Err(LetterBouncedError::BoardGeneration(_))
+        ));
+
+        let config = GeneratorConfig::new().side_length(7);
+        assert!(generate_board(&dictionary, 1, &config).is_err());
+    }
+
+    #[test]
+    fn test_unique_two_word() {
+        let board = Board::from_sides(vec!["ab".to_string(), "cd".to_string(), "ef".to_string(), "gh".to_string()]).unwrap();
+        let words = |text: &str| Dictionary::from_text(text);
+        assert!(has_unique_two_word_solution(&board, &words("acegb 20\nbdfh 20\n")));
+        // HDFA-ACEGB is a second solution
+        assert!(!has_unique_two_word_solution(&board, &words("acegb 20\nbdfh 20\nhdfa 20\n")));
+        // One word covers the board
+        assert!(!has_unique_two_word_solution(&board, &words("acegb 20\nbdfh 20\nacegbdfh 20\n")));
+
+        // forklift-twangy is the only pair which covers its boards
+        let dictionary = dictionary();
+        let config = GeneratorConfig::new().unique_two_word(true);
+        let board = generate_board(&dictionary, 7, &config).unwrap();
+        assert_eq!(Solver::new(board, &dictionary, 10).solve_exact_length(2).len(), 1);
+
+        let config = GeneratorConfig::new().unique_two_word(true).min_two_word_solutions(2);
+        assert!(generate_board(&dictionary, 7, &config).is_err());
+    }
+
+    #[test]
+    fn test_letter_constraints() {
+        let dictionary = Dictionary::from_text("forklift 20\ntwangy 20\nquartz 20\nzombies 20\n");
+        let letters = |board: &Board| -> HashSet<char> { board.sides.iter().flat_map(|s| s.chars()).collect() };
+
+        // Only quartz-zombies has a Q
+        let config = GeneratorConfig::new().required_letters("Q").max_attempts(200);
+        let board = generate_board(&dictionary, 5, &config).unwrap();
+        assert_eq!(letters(&board), distinct_letters("quartzzombies"));
+
+        // forklift-twangy has one rare letter and three vowels; quartz-zombies has two and five
+        let config = GeneratorConfig::new().max_rare_letters(1).max_attempts(200);
+        for seed in 0..10 {
+            assert_eq!(letters(&generate_board(&dictionary, seed, &config).unwrap()), distinct_letters("forklifttwangy"));
+        }
+        let config = GeneratorConfig::new().vowels(4, 5).max_attempts(200);
+        for seed in 0..10 {
+            assert!(count_from(&letters(&generate_board(&dictionary, seed, &config).unwrap()), VOWELS) >= 4);
+        }
+
+        for config in [
+            GeneratorConfig::new().required_letters("qz").max_rare_letters(1),
+            GeneratorConfig::new().required_letters("abcdefghijklm"),
+            GeneratorConfig::new().required_letters("q!"),
+            GeneratorConfig::new().vowels(4, 3),
+        ] {
+            assert!(matches!(
+                generate_board(&dictionary, 1, &config),
+                Err(LetterBouncedError::BoardGeneration(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_from_words() {
+        let themed = from_words(&["fork", "kilt", "glow"]).unwrap();
+        assert!(themed.board.sides.iter().all(|side| side.len() == 3));
+        assert_eq!(themed.padding, vec!['e', 'a', 'n']);
+        let playable = themed.board.playable_dictionary(&dictionary());
+        for word in ["fork", "kilt", "glow"] {
+            assert!(playable.words().iter().any(|w| w.word == word), "{} should be playable", word);
+        }
+
+        // 16 letters need a bigger board, and fill it
+        let themed = from_words(&["forklift", "twangy", "Jump"]).unwrap();
+        assert_eq!(themed.board.sides[0].len(), 4);
+        assert!(themed.padding.is_empty());
+
+        for (words, reason) in [
+            (vec![], "No words"),
+            (vec!["balloon"], "doubled L"),
+            (vec!["it's"], "isn't a letter"),
+            (vec!["abcdefghijklmnopqrstuvwxy"], "at most 24"),
+            // A, B, C, D and E all neighbour each other, and there are only four sides
+            (vec!["abcdeacebdae"], "can't be split"),
+        ] {
+            match from_words(&words) {
+                Err(LetterBouncedError::BoardGeneration(message)) => {
+                    assert!(message.contains(reason), "{:?}: {}", words, message)
+                }
+                other => panic!("{:?} should fail, got {:?}", words, other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_reverse() {
+        let dictionary = dictionary();
+        let boards = reverse(&["forklift", "twangy"], &dictionary, 1, &ReverseConfig::new().max_boards(3)).unwrap();
+        assert_eq!(boards.len(), 3);
+        for board in &boards {
+            let solver = Solver::new(board.clone(), &dictionary, 1);
+            assert!(solver.validate_solution(&["forklift", "twangy"]).is_ok());
+        }
+        let specs: HashSet<String> = boards.iter().map(|b| b.to_spec_string()).collect();
+        assert_eq!(specs.len(), 3);
+
+        // fork-kilt-twangy covers the same letters as forklift-twangy, which is shorter where it can be played
+        let dictionary = Dictionary::from_text("forklift 20\ntwangy 20\nfork 20\nkilt 20\n");
+        let config = ReverseConfig::new().max_boards(20);
+        let chain = ["fork", "kilt", "twangy"];
+        let all = reverse(&chain, &dictionary, 1, &config).unwrap();
+        let no_shorter = reverse(&chain, &dictionary, 1, &config.clone().no_shorter(true)).unwrap();
+        let plays_shorter = |board: &Board| {
+            Solver::new(board.clone(), &dictionary, 1).validate_solution(&["forklift", "twangy"]).is_ok()
+        };
+        assert!(all.iter().any(plays_shorter));
+        assert_eq!(no_shorter.len(), 20);
+        assert!(!no_shorter.iter().any(plays_shorter));
+        let boards = reverse(&["forklift", "twangy"], &dictionary, 1, &config.max_boards(3).unique(true)).unwrap();
+        assert_eq!(boards.len(), 3);
+
+        for chain in [vec!["fork", "twangy"], vec!["fork", "kilt"], vec!["balloon"]] {
+            assert!(reverse(&chain, &dictionary, 1, &ReverseConfig::new()).is_err(), "{:?}", chain);
+        }
+    }
+
+    #[test]
+    fn test_improve() {
+        let dictionary = dictionary();
+        // twangy can't be played without the Y
+        let board = Board::from_spec("qfa,otk,lgw,rni").unwrap();
+
+        let mutations = improve(&board, &dictionary, 1);
+        assert_eq!(mutations.len(), 1);
+        assert_eq!(mutations[0].board.sides, vec!["yfa", "otk", "lgw", "rni"]);
+        assert_eq!(mutations[0].to_string(), "replace Q with Y for 1 two-word solution");
+
+        // Nothing gets it any closer to 5 than the Y does
+        let board = Board::from_spec("yfa,otk,lgw,rni").unwrap();
+        assert!(improve(&board, &dictionary, 5).is_empty());
+    }
+
+    #[test]
+    fn test_deal_letters_keeps_neighbours_apart() {
+        let mut rng = Rng(3);
+        let sides = deal_letters(&["forklift", "twangy"], 3, &mut rng).unwrap();
+        assert!(sides.iter().all(|side| side.len() == 3));
+
+        let board = Board::from_sides(sides).unwrap();
+        for pair in ["fo", "or", "rk", "kl", "li", "if", "ft", "tw", "wa", "an", "ng", "gy"] {
+            assert!(board.digraphs.contains(&Digraph::from(pair)), "{} shares a side", pair);
+        }
+    }
+}
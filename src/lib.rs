@@ -1,6 +1,22 @@
+pub mod analysis;
+pub mod cache;
 pub mod board;
+pub mod dawg;
 pub mod dictionary;
+pub mod error;
+pub mod generator;
+pub mod playable;
+pub mod puzzles;
 pub mod solver;
+pub mod tournament;
+
+#[cfg(any(test, feature = "proptest"))]
+pub mod strategies;
+
+pub mod mapped;
+
+#[cfg(feature = "ocr")]
+pub mod ocr;
 
 #[cfg(target_arch = "wasm32")]
 pub mod wasm;
@@ -1,151 +1,1848 @@
-use letter_bounced::{board::Board, solver::Solver, dictionary::Dictionary}; // using our library!
-use clap::Parser;
-use log::debug;
-use std::{collections::HashSet, path::Path};
+// using our library!
+use letter_bounced::analysis;
+use letter_bounced::board::{self, Board, BoardOptions, GameRules, SvgOptions};
+use letter_bounced::cache::{self, SolveCache};
+use letter_bounced::dictionary::{
+    parse_spelling_variants, Dictionary, DictionaryDiff, FrequencyLabel, WordQuery, AMERICAN_TAG, BRITISH_TAG,
+};
+use letter_bounced::error::LetterBouncedError;
+use letter_bounced::generator::{self, GeneratorConfig, ReverseConfig};
+use letter_bounced::mapped;
+use letter_bounced::puzzles::{self, ArchivedPuzzle, PuzzleArchive};
+use letter_bounced::solver::{ScoringStrategy, Solution, SolveStats, Solver, SolverConfig};
+use letter_bounced::tournament::{Ruleset, Tournament};
+#[cfg(feature = "ocr")]
+use letter_bounced::ocr;
+use clap::{Args, CommandFactory, FromArgMatches, Parser, Subcommand};
+use rayon::prelude::*;
+use serde::Serialize;
+use tracing::debug;
+use tracing_subscriber::{fmt::format::FmtSpan, EnvFilter};
+use std::{io::{IsTerminal, Write}, path::Path, time::Duration};
+
+mod cli;
+
+use cli::build_dict;
+use cli::config::{self, Format};
 
 #[derive(Parser)]
 #[command(name = "letter-bounced")]
 #[command(about = "A Rust word game application for Letter Boxed puzzles")]
-struct Args {
-    /// Game specification as comma-separated sides (e.g., "ABC,DEF,GHI,JKL")
+#[command(args_conflicts_with_subcommands = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    solve: SolveArgs,
+}
+
+// Solving is the default, so `letter-bounced yfa,otk,lgw,rni` and `letter-bounced solve yfa,otk,lgw,rni` are the same
+#[derive(Subcommand)]
+enum Command {
+    /// Solve a board (what happens when no command is given)
+    Solve(Box<SolveArgs>),
+    /// Report playable words, bottleneck letters, digraph coverage, and solution counts for a board
+    Analyze(AnalyzeArgs),
+    /// Make up a new board
+    Generate(GenerateArgs),
+    /// Find boards on which a chain of words is a solution
+    Reverse(ReverseArgs),
+    /// Play a board, checking each word as you type it
+    Play(BoardArgs),
+    /// Time the solver over a corpus of past puzzles
+    Bench(BenchArgs),
+    /// List dictionary words matching some filters
+    Words(WordsArgs),
+    /// List dictionary words matching a crossword-style pattern like f??gl?ve
+    Pattern(PatternArgs),
+    /// Compare the answers recorded in the puzzle archive with the solver's
+    Stats(StatsArgs),
+    /// Check and rank several players' answers to several boards, and print a leaderboard
+    Tournament(TournamentArgs),
+    /// Build the dictionary from word lists and Google NGrams, or make a patch or binary form of one
+    BuildDict(build_dict::BuildDictArgs),
+    /// Compare two dictionaries, text or binary: the words added and removed, and whose frequency changed
+    DictDiff(DictDiffArgs),
+    /// Print a completion script for bash, zsh, fish, elvish or powershell
+    Completions(CompletionsArgs),
+}
+
+#[derive(Args)]
+struct TournamentArgs {
+    /// File of boards and answers: `board yfa,otk,lgw,rni` lines, then `<player> <board number> <words>` lines
+    entries: String,
+
+    /// How answers to a board are ranked: fewest-words, fewest-letters or rarest-words
+    #[arg(long, default_value = "fewest-words")]
+    ruleset: Ruleset,
+
+    #[command(flatten)]
+    dictionary: DictionaryArgs,
+
+    /// Only print the leaderboard, not each board's answers
+    #[arg(long, short)]
+    quiet: bool,
+}
+
+#[derive(Args)]
+struct DictDiffArgs {
+    /// The dictionary before, e.g. the one currently shipped
+    old: String,
+
+    /// The dictionary after, e.g. one just rebuilt from new ngrams data
+    new: String,
+
+    /// Only list frequency changes of at least this many points
+    #[arg(long, value_name = "N", default_value_t = 1)]
+    min_shift: u8,
+
+    /// List at most N words in each section
+    #[arg(long, value_name = "N")]
+    limit: Option<usize>,
+
+    /// Only print how many words were added, removed and changed
+    #[arg(long)]
+    summary: bool,
+}
+
+#[derive(Args)]
+struct CompletionsArgs {
+    shell: clap_complete::Shell,
+}
+
+#[derive(Args)]
+struct GenerateArgs {
+    /// The same seed and dictionary always give the same board [default: a random seed]
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Letters per side [default: 3]
+    #[arg(long)]
+    side_length: Option<usize>,
+
+    /// Only accept boards with at least this many two-word solutions [default: 1]
+    #[arg(long)]
+    min_two_word_solutions: Option<u16>,
+
+    /// How common the two words the board is built around must be [default: 18]
+    #[arg(long)]
+    min_frequency: Option<i8>,
+
+    /// Letters the board must have, like QZ
+    #[arg(long, value_name = "LETTERS")]
+    require_letters: Option<String>,
+
+    /// The most rare letters (J, K, Q, X and Z) the board may have
+    #[arg(long, value_name = "N")]
+    max_rare_letters: Option<usize>,
+
+    /// The fewest vowels the board may have
+    #[arg(long, value_name = "N")]
+    min_vowels: Option<usize>,
+
+    /// The most vowels the board may have
+    #[arg(long, value_name = "N")]
+    max_vowels: Option<usize>,
+
+    /// Only make boards with exactly one two-word solution, checked by searching them all
+    #[arg(long, conflicts_with = "min_two_word_solutions")]
+    unique_two_word: bool,
+
+    /// Give up after trying this many pairs of words [default: 1000]
+    #[arg(long, value_name = "N")]
+    attempts: Option<usize>,
+
+    #[command(flatten)]
+    dictionary: DictionaryArgs,
+
+    /// Instead of making a new board, suggest single-letter swaps to this one (a spec like yfa,otk,lgw,rni)
+    #[arg(long, value_name = "BOARD", requires = "target", conflicts_with_all = ["seed", "side_length", "min_two_word_solutions", "min_frequency", "require_letters", "max_rare_letters", "min_vowels", "max_vowels", "unique_two_word"])]
+    improve: Option<String>,
+
+    /// Instead of making a random board, make one on which all these words can be played (comma-separated)
+    #[arg(long, value_name = "WORDS", value_delimiter = ',', conflicts_with_all = ["seed", "side_length", "min_two_word_solutions", "min_frequency", "require_letters", "max_rare_letters", "min_vowels", "max_vowels", "unique_two_word", "improve"])]
+    from_words: Vec<String>,
+
+    /// With --improve, how many two-word solutions the board should have
+    #[arg(long, requires = "improve")]
+    target: Option<usize>,
+
+    /// Only print the board, as a spec like yfa-otk-lgw-rni
+    #[arg(long, short)]
+    quiet: bool,
+}
+
+#[derive(Args)]
+struct ReverseArgs {
+    /// The chain of words, like forklift-twangy
+    chain: String,
+
+    /// Find at most this many boards
+    #[arg(long, default_value_t = 5)]
+    boards: usize,
+
+    /// Only boards where no solution has fewer words than the chain
+    #[arg(long)]
+    no_shorter: bool,
+
+    /// Only boards where the chain is the only solution of its length, and none is shorter
+    #[arg(long)]
+    unique: bool,
+
+    /// Give up after dealing the chain's letters this many ways
+    #[arg(long, default_value_t = 1000)]
+    attempts: usize,
+
+    /// The same seed, chain and dictionary always give the same boards [default: a random seed]
+    #[arg(long)]
+    seed: Option<u64>,
+
+    #[command(flatten)]
+    dictionary: DictionaryArgs,
+}
+
+#[derive(Args)]
+struct StatsArgs {
+    #[arg(long, default_value = DEFAULT_ARCHIVE_PATH)]
+    archive: String,
+}
+
+#[derive(Args)]
+struct DictionarySearchArgs {
+    #[command(flatten)]
+    dictionary: DictionaryArgs,
+
+    /// Ignore words with a frequency score below this
+    #[arg(long)]
+    min_frequency: Option<i8>,
+
+    /// Only words playable on this board (comma-separated sides)
+    #[arg(long)]
+    playable_on: Option<String>,
+
+    /// Print at most this many words
+    #[arg(long)]
+    limit: Option<usize>,
+}
+
+#[derive(Args)]
+struct WordsArgs {
+    #[command(flatten)]
+    search: DictionarySearchArgs,
+
+    /// Only words containing this string. May be given more than once.
+    #[arg(long)]
+    contains: Vec<String>,
+
+    #[arg(long)]
+    starts_with: Option<String>,
+
+    #[arg(long)]
+    ends_with: Option<String>,
+
+    /// Word length, or an inclusive range like 7..9, 7.. or ..9
+    #[arg(long, value_parser = parse_length_range)]
+    length: Option<(Option<usize>, Option<usize>)>,
+}
+
+#[derive(Args)]
+struct PatternArgs {
+    /// Letters and ? for unknown letters, e.g. "f??gl?ve"
+    pattern: String,
+
+    #[command(flatten)]
+    search: DictionarySearchArgs,
+}
+
+fn parse_length_range(arg: &str) -> Result<(Option<usize>, Option<usize>), String> {
+    let parse_bound = |bound: &str| -> Result<Option<usize>, String> {
+        if bound.is_empty() {
+            Ok(None)
+        } else {
+            bound.parse().map(Some).map_err(|e| format!("Invalid length '{}': {}", bound, e))
+        }
+    };
+    match arg.split_once("..") {
+        Some((min, max)) => Ok((parse_bound(min)?, parse_bound(max.trim_start_matches('='))?)),
+        None => {
+            let length = parse_bound(arg)?;
+            Ok((length, length))
+        }
+    }
+}
+
+#[derive(Args)]
+struct BenchArgs {
+    /// File of board specs, one per line [default: the bundled data/bench_boards.txt]
+    #[arg(long)]
+    boards: Option<String>,
+
+    #[command(flatten)]
+    dictionary: DictionaryArgs,
+
+    #[arg(long, default_value_t = 500u16)]
+    max_solutions: u16,
+
+    /// Solve each board this many times
+    #[arg(long, default_value_t = 3)]
+    runs: usize,
+
+    /// Print the results as a single JSON object
+    #[arg(long)]
+    json: bool,
+}
+
+const DEFAULT_ARCHIVE_PATH: &str = "data/puzzles.json";
+
+// Exit codes, so scripts can tell outcomes apart. Anything else which goes wrong, including bad arguments, exits 1.
+const EXIT_NO_SOLUTIONS: i32 = 2;
+const EXIT_INVALID_BOARD: i32 = 3;
+const EXIT_DICTIONARY_ERROR: i32 = 4;
+
+// How many of the solver's solutions to keep with an archived puzzle
+const ARCHIVED_SOLUTIONS: usize = 10;
+
+const BENCH_BOARDS: &str = include_str!("../data/bench_boards.txt");
+
+#[derive(Args)]
+struct DictionaryArgs {
+    /// Path to the dictionary, text or binary [default: the config file's, data/dictionary.txt, or the embedded
+    /// dictionary if built with one]
+    #[arg(long = "dictionary", value_name = "DICTIONARY")]
+    path: Option<String>,
+}
+
+#[derive(Args)]
+struct BoardArgs {
+    /// Game specification as comma-separated sides (e.g., "ABC,DEF,GHI,JKL"). Semicolons, dashes or spaces
+    /// also work as separators, and letters with no separators ("ABCDEFGHIJKL") are split evenly into 4 sides
     board_spec: Option<String>,
 
     #[arg(long)]
     board: Option<String>,
 
-    #[arg(long, default_value = "data/dictionary.txt")]
-    dictionary: String,
+    /// Read the board's letters from a PNG screenshot of the puzzle. Needs the `ocr` feature.
+    #[arg(long, value_name = "PATH")]
+    from_image: Option<String>,
+
+    #[command(flatten)]
+    dictionary: DictionaryArgs,
+
+    /// Ignore words with a frequency score below this
+    #[arg(long)]
+    min_frequency: Option<i8>,
+
+    /// Allow the same letter to appear more than once on the board (for variant games). Short for
+    /// --rules duplicate-letters.
+    #[arg(long, conflicts_with = "rules")]
+    allow_duplicate_letters: bool,
+
+    /// Which rules the board is played under: standard (the NYT's), duplicate-letters, or doubled-letters where
+    /// a letter may also follow itself
+    #[arg(long, default_value = "standard")]
+    rules: GameRules,
+
+    /// Add a word to the dictionary, optionally with a frequency score (e.g. "qajaq" or "qajaq:12")
+    #[arg(long, value_parser = parse_added_word)]
+    add_word: Vec<(String, i8)>,
+
+    /// A personal word list, in the dictionary format, layered over the dictionary: its words are always allowed
+    /// and its frequencies win. May be given more than once; earlier lists take priority.
+    #[arg(long, value_name = "PATH")]
+    personal: Vec<String>,
+
+    /// Which words count: `all` the dictionary's, or only those the `nyt` game accepts
+    #[arg(long, default_value = "all")]
+    wordlist: WordList,
+
+    /// Words the NYT accepts, one per line, for --wordlist nyt. Not needed if the dictionary already tags them `nyt`.
+    #[arg(long, value_name = "PATH", default_value = DEFAULT_NYT_WORDS_PATH)]
+    nyt_words: String,
+
+    /// Which spellings of words like colour/color to allow: `us`, `uk`, or `both`
+    #[arg(long, default_value = "both")]
+    spelling: Spelling,
+
+    /// British and American spelling pairs, one pair per line, for --spelling. Not needed if the dictionary already
+    /// tags them `british` and `american`.
+    #[arg(long, value_name = "PATH", default_value = DEFAULT_SPELLING_VARIANTS_PATH)]
+    spelling_variants: String,
+}
+
+/// Which of the dictionary's words a board may use. Collins has many words the NYT game rejects, so solutions
+/// with them would be misleading to someone playing there.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum WordList {
+    All,
+    Nyt,
+}
+
+impl std::str::FromStr for WordList {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "all" => Ok(WordList::All),
+            "nyt" => Ok(WordList::Nyt),
+            _ => Err(format!("Unknown word list '{}'. Choose all or nyt", s)),
+        }
+    }
+}
+
+const DEFAULT_NYT_WORDS_PATH: &str = "data/nyt-accepted.txt";
+const NYT_TAG: &str = "nyt";
+
+// Tag the words in the accepted-words list, then keep only tagged words. A dictionary built with the tags already
+// in it doesn't need the list.
+fn nyt_dictionary(mut dictionary: Dictionary, path: &str) -> letter_bounced::error::Result<Dictionary> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => {
+            let tagged = dictionary.tag_words(text.split_whitespace(), NYT_TAG);
+            debug!(tagged, "Tagged the dictionary words in the NYT accepted-words list");
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound && dictionary.words().iter().any(|w| w.has_tag(NYT_TAG)) => {}
+        Err(e) => {
+            return Err(LetterBouncedError::DictionaryParse(format!(
+                "Can't read the NYT accepted-words list {}: {}. Gather one from saved game pages with \
+                 build-dict --nyt-words.",
+                path, e
+            )))
+        }
+    }
+    Ok(dictionary.filter_by_tag(NYT_TAG))
+}
+
+/// Which spelling of words British and American English spell differently a board may use
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Spelling {
+    Us,
+    Uk,
+    Both,
+}
+
+impl std::str::FromStr for Spelling {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "us" => Ok(Spelling::Us),
+            "uk" => Ok(Spelling::Uk),
+            "both" => Ok(Spelling::Both),
+            _ => Err(format!("Unknown spelling '{}'. Choose us, uk or both", s)),
+        }
+    }
+}
+
+const DEFAULT_SPELLING_VARIANTS_PATH: &str = "data/spelling-variants.txt";
+
+// Tag the spelling variants, then drop the other country's spellings. A dictionary built with the tags already in
+// it doesn't need the variants file.
+fn spelling_dictionary(mut dictionary: Dictionary, spelling: Spelling, path: &str) -> letter_bounced::error::Result<Dictionary> {
+    let excluded = match spelling {
+        Spelling::Us => BRITISH_TAG,
+        Spelling::Uk => AMERICAN_TAG,
+        Spelling::Both => return Ok(dictionary),
+    };
+    match std::fs::read_to_string(path) {
+        Ok(text) => {
+            let tagged = dictionary.tag_spelling_variants(&parse_spelling_variants(&text)?);
+            debug!(tagged, "Tagged the dictionary words which are spelling variants");
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound && has_spelling_tags(&dictionary) => {}
+        Err(e) => {
+            return Err(LetterBouncedError::DictionaryParse(format!(
+                "Can't read the spelling variants {}: {}",
+                path, e
+            )))
+        }
+    }
+    Ok(dictionary.exclude_tag(excluded))
+}
+
+fn has_spelling_tags(dictionary: &Dictionary) -> bool {
+    dictionary.words().iter().any(|w| w.has_tag(BRITISH_TAG) || w.has_tag(AMERICAN_TAG))
+}
+
+// Frequency given to added words which don't specify one; middling, so they aren't ranked first or last
+const ADDED_WORD_FREQUENCY: i8 = 15;
+
+impl BoardArgs {
+    fn board_options(&self) -> BoardOptions {
+        let rules = if self.allow_duplicate_letters { GameRules::DuplicateLetters } else { self.rules };
+        BoardOptions::new(rules)
+    }
+}
+
+fn parse_added_word(arg: &str) -> Result<(String, i8), String> {
+    let (word, frequency) = match arg.split_once(':') {
+        Some((word, frequency)) => (word, frequency.parse().map_err(|e| format!("Invalid frequency: {}", e))?),
+        None => (arg, ADDED_WORD_FREQUENCY),
+    };
+    if word.is_empty() || !word.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(format!("Invalid word '{}'. Only A-Z and a-z are allowed.", word));
+    }
+    Ok((word.to_lowercase(), frequency))
+}
+
+fn parse_timeout(arg: &str) -> Result<Duration, String> {
+    let seconds: f64 = arg.parse().map_err(|e| format!("Invalid timeout: {}", e))?;
+    Duration::try_from_secs_f64(seconds)
+        .map_err(|_| format!("Invalid timeout '{}'. It must be a number of seconds, 0 or more.", arg))
+}
+
+#[derive(Args)]
+struct AnalyzeArgs {
+    #[command(flatten)]
+    board: BoardArgs,
+
+    /// Also write a Graphviz graph of the playable words, and which can follow which, to this path
+    #[arg(long)]
+    export_dot: Option<String>,
+
+    /// Only draw words which appear in a solution
+    #[arg(long, requires = "export_dot")]
+    solution_words_only: bool,
+}
+
+#[derive(Args)]
+struct SolveArgs {
+    #[command(flatten)]
+    board: BoardArgs,
 
     #[arg(long, default_value_t = 500u16)]
     max_solutions: u16,
+
+    /// File of words (one per line) which must never appear in solutions
+    #[arg(long)]
+    exclude_words: Option<String>,
+
+    /// Only show solutions which use this word. May be given more than once.
+    #[arg(long)]
+    must_include: Vec<String>,
+
+    /// Only use words with this tag in the dictionary, e.g. `common`; may be repeated
+    #[arg(long = "tag", value_name = "TAG")]
+    tags: Vec<String>,
+
+    /// Never use words with this tag, e.g. `scrabble-only`; may be repeated
+    #[arg(long = "exclude-tag", value_name = "TAG")]
+    exclude_tags: Vec<String>,
+
+    /// The first word must start with this letter
+    #[arg(long)]
+    start_with: Option<char>,
+
+    /// Longest solutions to look for, in words
+    #[arg(long, default_value_t = 4)]
+    max_words: usize,
+
+    /// How to order solutions: score, fewest-words, fewest-letters or elegance (fewest revisited letters)
+    #[arg(long, default_value = "score")]
+    sort: ScoringStrategy,
+
+    /// Also show solutions which still work with some words taken out
+    #[arg(long, visible_alias = "include-redundant")]
+    keep_redundant: bool,
+
+    /// Drop solutions whose rarest word is more than N frequency points below their most common word
+    #[arg(long, value_name = "N")]
+    max_rarity_spread: Option<u8>,
+
+    /// Show at most N solutions starting with any one word
+    #[arg(long, value_name = "N")]
+    diverse: Option<u16>,
+
+    /// Leave out solutions which only differ from a better one by S plurals, like dog-growl and dog-growls
+    #[arg(long)]
+    collapse_plurals: bool,
+
+    /// Show how many letters each solution takes to type, and how many of them are revisits
+    #[arg(long)]
+    show_letters: bool,
+
+    /// Show each solution's score out of 100, and the frequency, letter efficiency and length points it's made of
+    #[arg(long)]
+    explain_scores: bool,
+
+    /// Print bare word-word lines even on a terminal, instead of ranked lines with scores. Short for --format plain.
+    #[arg(long)]
+    plain: bool,
+
+    /// How to print solutions: ranked (the default on a terminal), plain, or csv with a header row
+    #[arg(long, value_name = "FORMAT", conflicts_with_all = ["plain", "batch", "pangrams_only", "set_cover"])]
+    format: Option<Format>,
+
+    /// Write the solutions (or --batch's results) to this file instead of stdout. The file is replaced in one step,
+    /// so readers never see it half-written.
+    #[arg(long, short, value_name = "PATH")]
+    output: Option<String>,
+
+    /// Keep the other boards' results already in --output, replacing only this board's
+    #[arg(long, requires = "output", conflicts_with = "batch")]
+    append: bool,
+
+    /// Don't color words by how common they are. Setting NO_COLOR does the same.
+    #[arg(long)]
+    no_color: bool,
+
+    /// Stop searching after this many seconds and print what has been found so far
+    #[arg(long, value_parser = parse_timeout, allow_negative_numbers = true)]
+    timeout: Option<Duration>,
+
+    /// Only print single words which use every letter on the board
+    #[arg(long)]
+    pangrams_only: bool,
+
+    /// Ignore chaining: print the smallest sets of words which cover every letter, one set per line
+    #[arg(long, conflicts_with = "pangrams_only")]
+    set_cover: bool,
+
+    /// Apply --max-solutions to each word count separately instead of to the whole list
+    #[arg(long)]
+    per_word_count: bool,
+
+    /// Search every word count up to --max-words, keeping at most N of each, then show the best --max-solutions
+    /// of them together
+    #[arg(long, value_name = "N", conflicts_with = "per_word_count")]
+    max_per_length: Option<u16>,
+
+    /// Print counters from inside the search to stderr: positions visited, and candidate words tried and pruned
+    /// at each position in the chain
+    #[arg(long, conflicts_with_all = ["per_word_count", "cache_dir", "batch", "pangrams_only", "set_cover"])]
+    stats: bool,
+
+    /// Remember solves in this directory, so solving the same board the same way again is instant
+    #[arg(long, value_name = "DIR", conflicts_with = "per_word_count")]
+    cache_dir: Option<String>,
+
+    /// Also save the solver's prepared data for this board as JSON, for clients to solve it without a dictionary
+    #[arg(long, value_name = "PATH")]
+    save_pack: Option<String>,
+
+    /// Also write an SVG image of the board to this path
+    #[arg(long)]
+    export_svg: Option<String>,
+
+    /// Solve every board spec in this file (one per line) in parallel, printing results as JSON Lines
+    #[arg(long, conflicts_with_all = ["board_spec", "board", "pangrams_only", "set_cover", "export_svg"])]
+    batch: Option<String>,
+
+    /// Puzzle archive used by --save-puzzle and --replay
+    #[arg(long, default_value = DEFAULT_ARCHIVE_PATH)]
+    archive: String,
+
+    /// Save the board and its best solutions to the archive, under this date (YYYY-MM-DD) or today's
+    #[arg(long, num_args = 0..=1, default_missing_value = "", conflicts_with_all = ["batch", "pangrams_only", "set_cover"])]
+    save_puzzle: Option<String>,
+
+    /// Solve the board archived for this date (YYYY-MM-DD) instead of giving one
+    #[arg(long, conflicts_with_all = ["board_spec", "board", "batch"])]
+    replay: Option<String>,
+
+    /// Record the words you played (e.g. "forklift-twangy") with --save-puzzle or --replay, for the stats subcommand
+    #[arg(long)]
+    my_answer: Option<String>,
+
+    /// Print only the solutions: no board drawing, and no notes about timeouts or why nothing was found.
+    /// Errors are still reported.
+    #[arg(long, short)]
+    quiet: bool,
+}
+
+fn main() -> std::io::Result<()> {
+    // RUST_LOG=debug shows how long loading the dictionary, finding the playable words and each search pass took
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .with_span_events(FmtSpan::CLOSE)
+        .with_writer(std::io::stderr)
+        .with_ansi(std::io::stderr().is_terminal())
+        .init();
+    // clap would exit 2 for bad arguments, which we use for "no solutions"
+    let exit = |e: clap::Error| -> ! {
+        let _ = e.print();
+        std::process::exit(if e.use_stderr() { 1 } else { 0 });
+    };
+    let matches = Cli::command().try_get_matches().unwrap_or_else(|e| exit(e));
+    let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| exit(e));
+    // The config file only fills in options which weren't given, so it needs to know which those were
+    let config = config::get();
+    match (&mut cli.command, matches.subcommand()) {
+        (Some(Command::Solve(args)), Some((_, sub_matches))) => config.apply_to_solve(args, sub_matches),
+        (Some(Command::Analyze(args)), Some((_, sub_matches))) => config.apply_to_board(&mut args.board, sub_matches),
+        (Some(Command::Play(args)), Some((_, sub_matches))) => config.apply_to_board(args, sub_matches),
+        (None, _) => config.apply_to_solve(&mut cli.solve, &matches),
+        _ => {}
+    }
+    // Conflicts clap can't check itself, since they depend on --format's value, which may come from the config file
+    let solve_args = match &cli.command {
+        Some(Command::Solve(args)) => Some(&**args),
+        None => Some(&cli.solve),
+        _ => None,
+    };
+    if let Some(args) = solve_args.filter(|args| args.format == Some(Format::Csv)) {
+        // --append's sections would break up the rows
+        let flags = [(args.explain_scores, "--explain-scores"), (args.show_letters, "--show-letters"), (args.append, "--append")];
+        for (given, flag) in flags {
+            if given {
+                let message = format!("the argument '{}' cannot be used with '--format csv'", flag);
+                exit(Cli::command().error(clap::error::ErrorKind::ArgumentConflict, message));
+            }
+        }
+    }
+
+    match cli.command {
+        Some(Command::Solve(args)) => run_solve(*args),
+        Some(Command::Analyze(args)) => {
+            let board = load_board(&args.board, false);
+            let dictionary = load_filtered_dictionary(&args.board, Some(&board));
+            print_analysis(&board, &dictionary);
+            if let Some(path) = &args.export_dot {
+                let options = analysis::GraphOptions { solution_words_only: args.solution_words_only };
+                if let Err(e) = std::fs::write(path, analysis::solution_graph(&board, &dictionary, &options)) {
+                    eprintln!("Error writing graph: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Some(Command::Generate(args)) => generate(&args),
+        Some(Command::Reverse(args)) => reverse(&args),
+        Some(Command::Play(args)) => play(&args)?,
+        Some(Command::Bench(args)) => bench(&args),
+        Some(Command::Words(args)) => {
+            let mut query = WordQuery::new();
+            for substring in &args.contains {
+                query = query.contains(substring);
+            }
+            if let Some(prefix) = &args.starts_with {
+                query = query.starts_with(prefix);
+            }
+            if let Some(suffix) = &args.ends_with {
+                query = query.ends_with(suffix);
+            }
+            if let Some((min_length, max_length)) = args.length {
+                query = query.length(min_length, max_length);
+            }
+            print_words(&args.search, &query);
+        }
+        Some(Command::Pattern(args)) => print_words(&args.search, &WordQuery::new().pattern(&args.pattern)),
+        Some(Command::Stats(args)) => print_play_stats(&open_archive(&args.archive)),
+        Some(Command::Tournament(args)) => print_tournament(&args),
+        Some(Command::BuildDict(args)) => {
+            if let Err(e) = build_dict::run(&args) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Some(Command::DictDiff(args)) => print_dictionary_diff(&args),
+        Some(Command::Completions(args)) => {
+            clap_complete::generate(args.shell, &mut Cli::command(), "letter-bounced", &mut std::io::stdout());
+        }
+        None => run_solve(cli.solve),
+    }
+
+    Ok(())
+}
+
+fn run_solve(args: SolveArgs) {
+    if let Some(path) = &args.batch {
+        let dictionary = load_filtered_dictionary(&args.board, None);
+        solve_batch(path, &args, &dictionary);
+        return;
+    }
+
+    if args.my_answer.is_some() && args.save_puzzle.is_none() && args.replay.is_none() {
+        eprintln!("Error: --my-answer needs --save-puzzle or --replay, to know which puzzle it's for");
+        std::process::exit(1);
+    }
+    let answer: Vec<String> = args.my_answer.iter().flat_map(|a| a.split('-')).map(str::to_lowercase).collect();
+
+    let board = match &args.replay {
+        Some(date) => archived_board(&args.archive, date, args.board.board_options()),
+        None => load_board(&args.board, args.quiet),
+    };
+    let dictionary = load_filtered_dictionary(&args.board, Some(&board));
+    if (!args.tags.is_empty() || !args.exclude_tags.is_empty()) && !dictionary.has_tags() {
+        // Binary dictionaries don't keep tags either
+        eprintln!("Warning: this dictionary has no tags, so --tag and --exclude-tag can't match anything");
+    }
+    if !answer.is_empty() {
+        let words: Vec<&str> = answer.iter().map(String::as_str).collect();
+        if let Err(e) = Solver::new(board.clone(), &dictionary, 1).validate_solution(&words) {
+            eprintln!("Error in --my-answer: {}", e);
+            std::process::exit(1);
+        }
+    }
+    // A CSV file should hold nothing but the rows
+    if !args.quiet && args.format != Some(Format::Csv) {
+        println!("{}\n", board.render());
+    }
+    if let Some(path) = &args.export_svg {
+        if let Err(e) = std::fs::write(path, board::render_svg(&board, &SvgOptions::default())) {
+            eprintln!("Error writing SVG: {}", e);
+            std::process::exit(1);
+        }
+    }
+    if let Some(reason) = board.is_solvable(&dictionary).reason() {
+        if !args.quiet {
+            eprintln!("{}", reason);
+        }
+        std::process::exit(EXIT_NO_SOLUTIONS);
+    }
+    let found = if args.pangrams_only {
+        print_pangrams(board, dictionary, &args)
+    } else if args.set_cover {
+        print_covers(board, dictionary, solver_config(&args), &args)
+    } else {
+        let sides = board.sides.clone();
+        let solutions = solve(board, dictionary, solver_config(&args), &args);
+        if let Some(date) = &args.save_puzzle {
+            save_puzzle(&args.archive, date, sides, &solutions, answer);
+        } else if let (Some(date), false) = (&args.replay, answer.is_empty()) {
+            let mut archive = open_archive(&args.archive);
+            if let Err(e) = archive.record_answer(date, answer).and_then(|_| archive.save()) {
+                eprintln!("Error saving answer: {}", e);
+                std::process::exit(1);
+            }
+        }
+        solutions.len()
+    };
+    if found == 0 {
+        std::process::exit(EXIT_NO_SOLUTIONS);
+    }
 }
 
-fn validate_board_spec(board_spec: &str) -> Result<Vec<String>, String> {
-    // Check for invalid characters
-    for ch in board_spec.chars() {
-        if !ch.is_ascii_alphabetic() && ch != ',' {
-            return Err(format!("Invalid character '{}' in game specification. Only A-Z, a-z, and commas are allowed.", ch));
+fn generate(args: &GenerateArgs) {
+    if !args.from_words.is_empty() {
+        let words: Vec<&str> = args.from_words.iter().map(String::as_str).collect();
+        match generator::from_words(&words) {
+            Ok(themed) if args.quiet => println!("{}", themed.board.to_spec_string()),
+            Ok(themed) => {
+                println!("{}\n", themed.board.render());
+                println!("{}", themed.board.to_spec_string());
+                if !themed.padding.is_empty() {
+                    let padding: String = themed.padding.iter().collect();
+                    println!("Filler letters: {}", padding.to_uppercase());
+                }
+            }
+            Err(e) => {
+                eprintln!("Error generating board: {}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+    let dictionary = load_dictionary(args.dictionary.path.as_deref()).unwrap_or_else(|e| {
+        eprintln!("Error loading dictionary: {}", e);
+        std::process::exit(EXIT_DICTIONARY_ERROR);
+    });
+    if let (Some(spec), Some(target)) = (&args.improve, args.target) {
+        let board = Board::from_spec(spec).unwrap_or_else(|e| {
+            eprintln!("Error in board specification: {}", e);
+            std::process::exit(EXIT_INVALID_BOARD);
+        });
+        let mutations = generator::improve(&board, &dictionary, target);
+        if mutations.is_empty() && !args.quiet {
+            eprintln!("No single-letter swap brings the board closer to {} two-word solutions", target);
         }
+        for mutation in &mutations {
+            if args.quiet {
+                println!("{}", mutation.board.to_spec_string());
+            } else {
+                println!("{}: {}", mutation.board.to_spec_string(), mutation);
+            }
+        }
+        return;
     }
 
-    // Split by comma and convert to lowercase
-    let sides: Vec<String> = board_spec.split(',').map(|s| s.to_lowercase()).collect();
+    let mut config = GeneratorConfig::new();
+    if let Some(side_length) = args.side_length {
+        config = config.side_length(side_length);
+    }
+    if let Some(count) = args.min_two_word_solutions {
+        config = config.min_two_word_solutions(count);
+    }
+    if let Some(min_frequency) = args.min_frequency {
+        config = config.min_frequency(min_frequency);
+    }
+    if let Some(letters) = &args.require_letters {
+        config = config.required_letters(letters);
+    }
+    if let Some(max) = args.max_rare_letters {
+        config = config.max_rare_letters(max);
+    }
+    if args.min_vowels.is_some() || args.max_vowels.is_some() {
+        config = config.vowels(args.min_vowels.unwrap_or(0), args.max_vowels.unwrap_or(usize::MAX));
+    }
+    if let Some(attempts) = args.attempts {
+        config = config.max_attempts(attempts);
+    }
+    config = config.unique_two_word(args.unique_two_word);
+    // Any seed will do when none was given, but print it so the board can be made again
+    let seed = args.seed.unwrap_or_else(|| {
+        let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default();
+        now.as_nanos() as u64
+    });
 
-    if sides.is_empty() {
-        return Err("Game specification cannot be empty".to_string());
+    match generator::generate_board(&dictionary, seed, &config) {
+        Ok(board) if args.quiet => println!("{}", board.to_spec_string()),
+        Ok(board) => {
+            println!("{}\n", board.render());
+            println!("{}", board.to_spec_string());
+            println!("Seed: {}", seed);
+        }
+        Err(e) => {
+            eprintln!("Error generating board: {}", e);
+            std::process::exit(1);
+        }
     }
+}
+
+fn reverse(args: &ReverseArgs) {
+    let dictionary = load_dictionary(args.dictionary.path.as_deref()).unwrap_or_else(|e| {
+        eprintln!("Error loading dictionary: {}", e);
+        std::process::exit(EXIT_DICTIONARY_ERROR);
+    });
+    let chain: Vec<&str> = args.chain.split(['-', ',']).filter(|w| !w.is_empty()).collect();
+    let config = ReverseConfig::new()
+        .max_boards(args.boards)
+        .no_shorter(args.no_shorter)
+        .unique(args.unique)
+        .max_attempts(args.attempts);
+    let seed = args.seed.unwrap_or_else(|| {
+        let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default();
+        now.as_nanos() as u64
+    });
 
-    Ok(sides)
+    match generator::reverse(&chain, &dictionary, seed, &config) {
+        Ok(boards) if boards.is_empty() => {
+            eprintln!("No board found after {} attempts (seed {})", args.attempts, seed);
+            std::process::exit(EXIT_NO_SOLUTIONS);
+        }
+        Ok(boards) => {
+            for board in boards {
+                println!("{}", board.to_spec_string());
+            }
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
 }
 
-fn main() -> std::io::Result<()> {
-    env_logger::init();
-    let args = Args::parse();
+// Read words from stdin until the board is covered, checking each against the board and dictionary as it comes
+fn play(args: &BoardArgs) -> std::io::Result<()> {
+    let board = load_board(args, false);
+    let dictionary = load_filtered_dictionary(args, Some(&board));
+    if let Some(reason) = board.is_solvable(&dictionary).reason() {
+        eprintln!("{}", reason);
+        std::process::exit(EXIT_NO_SOLUTIONS);
+    }
+    let solver = Solver::with_config(board.clone(), &dictionary, SolverConfig::new());
 
-    let max_solutions = args.max_solutions;
+    println!("{}\n", board.render());
+    println!("Type your words, one or more at a time. \"undo\" takes back the last word; an empty line gives up.");
+    let mut played: Vec<String> = Vec::new();
+    loop {
+        print!("> ");
+        std::io::stdout().flush()?;
+        let mut line = String::new();
+        if std::io::stdin().read_line(&mut line)? == 0 {
+            break;
+        }
+        let input = line.trim().to_lowercase();
+        if input.is_empty() {
+            break;
+        }
+        if input == "undo" {
+            played.pop();
+            println!("{}", played.join("-"));
+            continue;
+        }
+
+        let mut attempt = played.clone();
+        attempt.extend(input.split(['-', ' ']).filter(|word| !word.is_empty()).map(String::from));
+        let words: Vec<&str> = attempt.iter().map(String::as_str).collect();
+        match solver.missing_letters(&words) {
+            Ok(missing) if missing.is_empty() => {
+                println!("Solved in {} words: {}", attempt.len(), attempt.join("-"));
+                break;
+            }
+            Ok(missing) => {
+                let missing: String = missing.iter().map(|c| c.to_ascii_uppercase()).collect();
+                println!("{}  (still to use: {})", attempt.join("-"), missing);
+                played = attempt;
+            }
+            Err(e) => println!("{}", e),
+        }
+    }
+
+    if let Some(best) = solver.solve().first() {
+        println!("The solver's best: {}", best);
+    }
+    Ok(())
+}
+
+fn solver_config(args: &SolveArgs) -> SolverConfig {
+    let mut config = SolverConfig::new()
+        .max_solutions(args.max_solutions)
+        .max_words(args.max_words)
+        .scoring(args.sort)
+        .filter_redundant(!args.keep_redundant)
+        .collapse_plurals(args.collapse_plurals)
+        .required_words(&args.must_include)
+        .required_tags(&args.tags)
+        .excluded_tags(&args.exclude_tags);
+    if let Some(letter) = args.start_with {
+        config = config.start_letter(letter);
+    }
+    if let Some(spread) = args.max_rarity_spread {
+        config = config.max_rarity_spread(spread);
+    }
+    if let Some(max) = args.diverse {
+        config = config.max_per_first_word(max);
+    }
+    if let Some(max) = args.max_per_length {
+        config = config.max_per_length(max);
+    }
+    if let Some(timeout) = args.timeout {
+        config = config.timeout(timeout);
+    }
+    if let Some(path) = &args.exclude_words {
+        match std::fs::read_to_string(path) {
+            Ok(text) => config = config.excluded_words(text.split_whitespace()),
+            Err(e) => {
+                eprintln!("Error reading excluded words: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+    if let Err(e) = config.validate() {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+    config
+}
 
-    let dictionary_path = Path::new(&args.dictionary);
+// With `quiet`, only errors are reported
+fn load_board(args: &BoardArgs, quiet: bool) -> Board {
+    let board_options = args.board_options();
 
     // Handle game - either from positional argument or --game option
-    let board = match (&args.board_spec, &args.board) {
-        (Some(spec), None) => {
-            // Parse comma-separated game specification
-            match validate_board_spec(spec) {
-                Ok(sides) => {
-                    debug!("Loading game from specification: {}", spec);
-                    match Board::from_sides(sides) {
-                        Ok(game) => game,
-                        Err(e) => {
-                            eprintln!("Error creating board from specification: {}", e);
-                            std::process::exit(1);
-                        }
-                    }
+    let board = match (&args.board_spec, &args.board, &args.from_image) {
+        (Some(spec), None, None) => {
+            let board = Board::normalize_spec(spec).and_then(|spec| {
+                // Say what was tidied up, in case it wasn't what was meant
+                for change in spec.changes.iter().filter(|_| !quiet) {
+                    eprintln!("Note: {} in the board specification", change);
                 }
+                Board::from_sides_with_options(spec.sides, board_options)
+            });
+            match board {
+                Ok(game) => game,
                 Err(e) => {
-                    eprintln!("Error parsing board specification: {}", e);
-                    std::process::exit(1);
+                    eprintln!("Error in board specification: {}", e);
+                    std::process::exit(EXIT_INVALID_BOARD);
                 }
             }
         }
-        (None, Some(path)) => {
+        (None, Some(path), None) => {
             // Load game from file
             let game_path = Path::new(path);
-            debug!("Loading game from: {:?}", game_path);
-            match Board::from_path(game_path) {
+            match Board::from_path_with_options(game_path, board_options) {
                 Ok(game) => game,
                 Err(e) => {
                     eprintln!("Error loading board: {}", e);
-                    std::process::exit(1);
+                    std::process::exit(EXIT_INVALID_BOARD);
                 }
             }
         }
-        (Some(_), Some(_)) => {
-            eprintln!("Error: Cannot specify both board specification and --board option");
-            std::process::exit(1);
+        (None, None, Some(path)) => board_from_image(path, board_options),
+        (None, None, None) if std::io::stdin().is_terminal() => prompt_for_board(board_options),
+        (None, None, None) => {
+            eprintln!("Error: Either board specification, --board or --from-image is required");
+            std::process::exit(EXIT_INVALID_BOARD);
         }
-        (None, None) => {
-            eprintln!("Error: Either board specification or --board option is required");
-            std::process::exit(1);
+        _ => {
+            eprintln!("Error: Give only one of a board specification, --board or --from-image");
+            std::process::exit(EXIT_INVALID_BOARD);
         }
     };
 
+    debug!(board = %board.sides.join(","), digraphs = board.digraphs.len(), "Loaded the board");
+
+    board
+}
 
-    pub fn format_valid_digraphs(digraphs: &HashSet<String>) -> String {
-        let mut sorted_digraphs: Vec<_> = digraphs.iter().collect();
-        sorted_digraphs.sort();
-        sorted_digraphs
+// With a binary dictionary and a board, only the board's playable words are loaded, which is much quicker
+fn load_filtered_dictionary(args: &BoardArgs, board: Option<&Board>) -> Dictionary {
+    let loaded = match (args.dictionary.path.as_deref(), board) {
+        (Some(path), Some(board)) if mapped::is_binary_dictionary(path) => {
+            Dictionary::open_mmap(path).map(|mapped| mapped.playable_on(board))
+        }
+        (path, _) => load_dictionary(path),
+    };
+    let loaded = match args.wordlist {
+        WordList::All => loaded,
+        WordList::Nyt => loaded.and_then(|dictionary| nyt_dictionary(dictionary, &args.nyt_words)),
+    };
+    let loaded = loaded.and_then(|dictionary| spelling_dictionary(dictionary, args.spelling, &args.spelling_variants));
+    // Personal lists go on top, after the NYT and spelling filters, since they're words the player knows are fine
+    let loaded = loaded.and_then(|base| {
+        let mut layers = args
+            .personal
             .iter()
-            .map(|s| s.as_str())
-            .collect::<Vec<_>>()
-            .join(" ")
+            .map(dictionary_from_path)
+            .collect::<letter_bounced::error::Result<Vec<_>>>()?;
+        if layers.is_empty() {
+            return Ok(base);
+        }
+        layers.push(base);
+        Ok(Dictionary::layered(layers))
+    });
+    match loaded {
+        Ok(mut dictionary) => {
+            let added: Vec<(&str, i8)> = args.add_word.iter().map(|(w, f)| (w.as_str(), *f)).collect();
+            dictionary.extend_with_words(&added);
+            match args.min_frequency {
+                Some(min_frequency) => dictionary.filter_by_frequency(min_frequency),
+                None => dictionary,
+            }
+        }
+        Err(e) => {
+            eprintln!("Error loading dictionary: {}", e);
+            std::process::exit(EXIT_DICTIONARY_ERROR);
+        }
+    }
+}
+
+const DEFAULT_DICTIONARY_PATH: &str = "data/dictionary.txt";
+
+fn load_dictionary(path: Option<&str>) -> letter_bounced::error::Result<Dictionary> {
+    let path = path.or(config::get().dictionary.as_deref());
+    #[cfg(feature = "embedded-dictionary")]
+    if path.is_none() {
+        return Dictionary::embedded();
     }
 
-    debug!("Successfully loaded game:");
-    for (i, side) in board.sides.iter().enumerate() {
-        debug!("Side {}: {} ({} letters)", i, side, side.len());
+    let dictionary_path = Path::new(path.unwrap_or(DEFAULT_DICTIONARY_PATH));
+    if mapped::is_binary_dictionary(dictionary_path) {
+        return Dictionary::open_mmap(dictionary_path).map(|mapped| mapped.to_dictionary());
     }
-    debug!(
-        "Number of valid digraphs in this game: {}",
-        board.digraphs.len()
+    dictionary_from_path(dictionary_path)
+}
+
+// The library leaves reporting skipped lines to its callers
+fn dictionary_from_path<P: AsRef<Path>>(path: P) -> letter_bounced::error::Result<Dictionary> {
+    let dictionary = Dictionary::from_path(&path)?;
+    for warning in &dictionary.warnings {
+        eprintln!("{}: {}", path.as_ref().display(), warning);
+    }
+    Ok(dictionary)
+}
+
+fn print_analysis(board: &Board, dictionary: &Dictionary) {
+    let analysis = analysis::analyze(board, dictionary);
+
+    println!("Playable words: {}", analysis.playable_words);
+    if let Some(reason) = board.is_solvable(dictionary).reason() {
+        println!("{}", reason);
+    }
+    println!(
+        "Digraph coverage: {} of {} board digraphs appear in playable words",
+        analysis.digraphs_used, analysis.digraphs_total
     );
-    debug!("Valid digraphs in this game:");
-    debug!("{}", format_valid_digraphs(&board.digraphs));
+    println!("Letters (words starting / containing):");
+    for stats in &analysis.letters {
+        println!("  {}: {} / {}", stats.letter, stats.words_starting, stats.words_containing);
+    }
+    let bottlenecks: Vec<String> = analysis.bottleneck_letters().iter().map(|c| c.to_string()).collect();
+    println!("Bottleneck letters: {}", bottlenecks.join(" "));
+    println!("Hard letters (solutions where only the rarest word covers it):");
+    for coverage in &analysis.letter_coverage {
+        println!("  {}: {}", coverage.letter, coverage.hard_in_solutions);
+    }
+    for (word_count, solutions) in &analysis.solution_counts {
+        let capped = if *solutions >= analysis::SOLUTION_COUNT_LIMIT as usize { "+" } else { "" };
+        println!("{}-word solutions: {}{}", word_count, solutions, capped);
+    }
+    if let Some(cover) = analysis.min_cover_words {
+        let hardness = if analysis.is_chaining_hard() { "chaining-hard" } else { "coverage-hard" };
+        println!("Fewest words covering every letter, ignoring chaining: {} ({})", cover, hardness);
+    }
+    match analysis.par(board, dictionary) {
+        Some(par) => {
+            let sampled = if par.is_sampled() { "+" } else { "" };
+            let ways = if par.solutions == 1 { "way" } else { "ways" };
+            println!("Par: {} words ({}{} {})", par.words, par.solutions, sampled, ways);
+        }
+        None => println!("Par: no solution in up to {} words", analysis::PAR_MAX_WORDS),
+    }
+}
+
+fn print_words(args: &DictionarySearchArgs, query: &WordQuery) {
+    let mut dictionary = load_dictionary(args.dictionary.path.as_deref()).unwrap_or_else(|e| {
+        eprintln!("Error loading dictionary: {}", e);
+        std::process::exit(EXIT_DICTIONARY_ERROR);
+    });
+    if let Some(min_frequency) = args.min_frequency {
+        dictionary = dictionary.filter_by_frequency(min_frequency);
+    }
+    if let Some(spec) = &args.playable_on {
+        let board = Board::from_spec(spec).unwrap_or_else(|e| {
+            eprintln!("Error in --playable-on board: {}", e);
+            std::process::exit(EXIT_INVALID_BOARD);
+        });
+        dictionary = board.playable_dictionary(&dictionary);
+    }
 
-    debug!("Loading dictionary from: {:?}", dictionary_path);
-    match Dictionary::from_path(dictionary_path) {
-        Ok(dictionary) => {
-            solve(board, dictionary, max_solutions);
+    for word in dictionary.query(query).take(args.limit.unwrap_or(usize::MAX)) {
+        println!("{}", word.word);
+    }
+}
+
+fn print_tournament(args: &TournamentArgs) {
+    let tournament = std::fs::read_to_string(&args.entries)
+        .map_err(LetterBouncedError::from)
+        .and_then(|text| Tournament::parse(&text))
+        .unwrap_or_else(|e| {
+            eprintln!("Error reading {}: {}", args.entries, e);
+            std::process::exit(1);
+        });
+    let dictionary = load_dictionary(args.dictionary.path.as_deref()).unwrap_or_else(|e| {
+        eprintln!("Error loading dictionary: {}", e);
+        std::process::exit(EXIT_DICTIONARY_ERROR);
+    });
+    let leaderboard = tournament.score(&dictionary, args.ruleset);
+
+    let name_width = leaderboard.standings.iter().map(|s| s.player.len()).max().unwrap_or(0);
+    if !args.quiet {
+        for (index, board) in tournament.boards.iter().enumerate() {
+            println!("Board {}: {}", index + 1, board.to_spec_string());
+            for scored in leaderboard.entries.iter().filter(|e| e.entry.board == index) {
+                let answer = match &scored.solution {
+                    Ok(solution) => format!("{:#}", solution),
+                    Err(e) => format!("{} (not counted: {})", scored.entry.words.join("-"), e),
+                };
+                println!("  {:<name_width$}  {:>3} pts  {}", scored.entry.player, scored.points, answer);
+            }
+            println!();
         }
-        Err(e) => eprintln!("Error loading dictionary: {}", e),
     }
+    println!("Leaderboard:");
+    for (place, standing) in leaderboard.standings.iter().enumerate() {
+        println!(
+            "  {:>2}. {:<name_width$}  {:>3} pts  {} of {} boards solved",
+            place + 1,
+            standing.player,
+            standing.points,
+            standing.boards_solved,
+            tournament.boards.len()
+        );
+    }
+}
 
-    Ok(())
+fn print_dictionary_diff(args: &DictDiffArgs) {
+    let load = |path: &str| {
+        load_dictionary(Some(path)).unwrap_or_else(|e| {
+            eprintln!("Error loading dictionary {}: {}", path, e);
+            std::process::exit(EXIT_DICTIONARY_ERROR);
+        })
+    };
+    let (old, new) = (load(&args.old), load(&args.new));
+    let mut diff = old.diff(&new);
+    diff.shifted.retain(|(_, before, after)| before.abs_diff(*after) >= args.min_shift);
+
+    println!(
+        "{} words, now {}: {} added, {} removed, {} changed frequency",
+        old.words().len(),
+        new.words().len(),
+        diff.added.len(),
+        diff.removed.len(),
+        diff.shifted.len()
+    );
+    if args.summary {
+        return;
+    }
+    print_diff_sections(&diff, args.limit.unwrap_or(usize::MAX));
 }
 
-fn solve(board: Board, dictionary: Dictionary, max_solutions: u16) {
-    debug!("Successfully loaded dictionary:");
-    debug!("Number of words: {}", dictionary.words.len());
-    {
-        let board_dictionary = board.playable_dictionary(&dictionary);
-        debug!("\nFirst 10 possible words for this game:");
-        for w in board_dictionary.words.iter().take(10) {
-            debug!("  {}", w.word);
+// Each section is most noticeable first, so --limit keeps the changes worth checking
+fn print_diff_sections(diff: &DictionaryDiff, limit: usize) {
+    if !diff.added.is_empty() {
+        println!("\nAdded:");
+        for (word, frequency) in diff.added.iter().take(limit) {
+            println!("  + {} {} ({})", word, frequency, FrequencyLabel::of(*frequency));
+        }
+    }
+    if !diff.removed.is_empty() {
+        println!("\nRemoved:");
+        for (word, frequency) in diff.removed.iter().take(limit) {
+            println!("  - {} {} ({})", word, frequency, FrequencyLabel::of(*frequency));
+        }
+    }
+    if !diff.shifted.is_empty() {
+        println!("\nChanged frequency:");
+        for (word, before, after) in diff.shifted.iter().take(limit) {
+            let (before_label, after_label) = (FrequencyLabel::of(*before), FrequencyLabel::of(*after));
+            let shift = *after as i16 - *before as i16;
+            if before_label == after_label {
+                println!("  ~ {} {} -> {} ({:+})", word, before, after, shift);
+            } else {
+                println!("  ~ {} {} -> {} ({:+}, {} -> {})", word, before, after, shift, before_label, after_label);
+            }
         }
-        debug!("Total possible words: {}", board_dictionary.words.len());
+    }
+}
 
-        // Run the solver
-        debug!("\nSolving the puzzle...");
-        let solver = Solver::new(board, &dictionary, max_solutions);
-        let solutions = solver.solve();
+fn print_play_stats(archive: &PuzzleArchive) {
+    let stats = puzzles::play_stats(archive.puzzles());
+    if stats.puzzles_played == 0 {
+        println!("No answers recorded yet. Add one with --my-answer when saving or replaying a puzzle.");
+        return;
+    }
 
-        if solutions.is_empty() {
-            debug!("No solutions found!");
+    println!("Puzzles with answers: {}", stats.puzzles_played);
+    println!(
+        "Average words per solution: {:.2} (solver's best: {:.2})",
+        stats.average_answer_words, stats.average_solver_words
+    );
+    let words: Vec<String> = stats.most_used_words.iter().map(|(w, n)| format!("{} ({})", w, n)).collect();
+    println!("Most used words: {}", words.join(", "));
+    if !stats.struggle_letters.is_empty() {
+        let letters: Vec<String> = stats.struggle_letters.iter().map(|(c, n)| format!("{} ({})", c, n)).collect();
+        println!("Letters which cost you extra words: {}", letters.join(", "));
+    }
+}
+
+// Returns how many were found
+fn print_pangrams(board: Board, dictionary: Dictionary, args: &SolveArgs) -> usize {
+    let spec = board.to_spec_string();
+    let solver = Solver::new(board, &dictionary, 0);
+    let pangrams = solver.find_pangram_words();
+    let text: String = pangrams.iter().map(|word| format!("{}\n", word.word)).collect();
+    write_results(&text, &spec, args);
+    pangrams.len()
+}
+
+fn print_covers(board: Board, dictionary: Dictionary, config: SolverConfig, args: &SolveArgs) -> usize {
+    let spec = board.to_spec_string();
+    let covers = Solver::with_config(board, &dictionary, config).minimal_covers();
+    let text: String = covers
+        .iter()
+        .map(|cover| {
+            let words: Vec<&str> = cover.iter().map(|w| w.word.as_str()).collect();
+            format!("{}\n", words.join(" "))
+        })
+        .collect();
+    write_results(&text, &spec, args);
+    covers.len()
+}
+
+// To --output if it was given, replacing just this board's section with --append, and otherwise to stdout
+fn write_results(text: &str, spec: &str, args: &SolveArgs) {
+    let Some(path) = &args.output else {
+        print!("{}", text);
+        return;
+    };
+    let written = if args.append { append_results(path, spec, text) } else { write_atomically(path, text) };
+    if let Err(e) = written {
+        eprintln!("Error writing {}: {}", path, e);
+        std::process::exit(1);
+    }
+}
+
+fn solve(board: Board, dictionary: Dictionary, config: SolverConfig, args: &SolveArgs) -> Vec<Solution> {
+    let mut cache = args.cache_dir.as_ref().map(|dir| SolveCache::new(1).dir(dir));
+    let key = cache.as_ref().map(|_| cache::cache_key(&board, &dictionary, &config)).unwrap_or_default();
+    let spec = board.to_spec_string();
+    let solver = Solver::with_config(board, &dictionary, config);
+    if let Some(path) = &args.save_pack {
+        let json = serde_json::to_string(&solver.pack()).expect("packs always serialize");
+        if let Err(e) = std::fs::write(path, json) {
+            eprintln!("Error writing solver pack: {}", e);
+            std::process::exit(1);
+        }
+    }
+    let (solutions, timed_out) = if args.per_word_count {
+        let set = solver.solve_by_word_count(None);
+        let timed_out = set.timed_out();
+        (set.into_solutions(), timed_out)
+    } else if let Some(solutions) = cache.as_mut().and_then(|cache| cache.get(&key, &dictionary)) {
+        debug!("Found the solutions in the cache");
+        (solutions, false)
+    } else {
+        let (outcome, stats) = solver.solve_with_stats(None);
+        if args.stats {
+            print_solve_stats(&stats);
+        }
+        if let Some(cache) = cache.as_mut().filter(|_| !outcome.timed_out) {
+            cache.insert(key, &outcome.solutions);
+        }
+        (outcome.solutions, outcome.timed_out)
+    };
+    if timed_out && !args.quiet {
+        eprintln!("Search timed out; results may be incomplete");
+    }
+
+    if solutions.is_empty() && !timed_out && !args.quiet {
+        eprintln!("No solutions found. {}", solver.explain_no_solutions());
+    }
+    let format = if args.plain { Format::Plain } else { args.format.unwrap_or(Format::Ranked) };
+    // Files never get ranked lines or terminal colors
+    let terminal = args.output.is_none() && std::io::stdout().is_terminal();
+    write_results(&solutions_text(&solutions, format, terminal, args), &spec, args);
+    solutions
+}
+
+// The solutions in `format`, one per line. Ranked lines are only used on a terminal; pipes and scripts get one bare
+// solution per line.
+fn solutions_text(solutions: &[Solution], format: Format, terminal: bool, args: &SolveArgs) -> String {
+    if format == Format::Csv {
+        return csv_rows(solutions);
+    }
+    let ranked = format == Format::Ranked && terminal;
+    let color = !args.no_color && std::env::var_os("NO_COLOR").is_none();
+    let width = solutions.iter().map(|s| s.to_string().len()).max().unwrap_or(0);
+    let mut text = String::new();
+    for (rank, solution) in solutions.iter().enumerate() {
+        let line = if ranked {
+            let line = ranked_line(rank + 1, solution, width, color, args.show_letters);
+            if args.explain_scores {
+                format!("{}   {}", line, solution.score_breakdown())
+            } else {
+                line.trim_end().to_string()
+            }
         } else {
-            debug!("Found {} solutions.", solutions.len());
-            for solution in solutions.iter() {
-                println!("{}", solution);
-                debug!("  {} {}", solution.score, solution.words.iter().map(|w| w.frequency.to_string()).collect::<Vec<_>>().join("-"));
+            let text = if args.show_letters { format!("{:#}", solution) } else { solution.to_string() };
+            if args.explain_scores {
+                format!("{} {}", text, solution.score_breakdown())
+            } else {
+                text
             }
+        };
+        text.push_str(&line);
+        text.push('\n');
+    }
+    text
+}
+
+// Words are joined with hyphens, so nothing needs quoting
+fn csv_rows(solutions: &[Solution]) -> String {
+    let mut text = String::from("rank,words,word_count,letters,min_frequency,score\n");
+    for (rank, solution) in solutions.iter().enumerate() {
+        let min_frequency = solution.words.iter().map(|w| w.frequency).min().unwrap_or(0);
+        text.push_str(&format!(
+            "{},{},{},{},{},{}\n",
+            rank + 1,
+            solution,
+            solution.words.len(),
+            solution.letter_count(),
+            min_frequency,
+            solution.score_breakdown().total
+        ));
+    }
+    text
+}
+
+// Write to a temporary file beside `path`, then rename it into place, so nothing reading `path` ever sees half a file
+fn write_atomically(path: &str, contents: &str) -> std::io::Result<()> {
+    let path = Path::new(path);
+    let name = path.file_name().ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidInput, "not a file"))?;
+    let temp = path.with_file_name(format!(".{}.{}.tmp", name.to_string_lossy(), std::process::id()));
+    let written = std::fs::File::create(&temp)
+        .and_then(|mut file| file.write_all(contents.as_bytes()).and_then(|_| file.sync_all()))
+        .and_then(|_| std::fs::rename(&temp, path));
+    if written.is_err() {
+        let _ = std::fs::remove_file(&temp);
+    }
+    written
+}
+
+/*
+ * With --append, the output file holds results for many boards, each under a `# board <spec>` line:
+ *
+ *     # board yfa-otk-lgw-rni
+ *     forklift-twangy
+ *
+ *     # board vyq-fig-ote-xlu
+ *     foxglove-equity
+ *
+ * Solving a board again replaces its section and leaves the others where they are. New boards go at the end.
+ */
+fn append_results(path: &str, spec: &str, results: &str) -> std::io::Result<()> {
+    let existing = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e),
+    };
+    write_atomically(path, &merge_results(&existing, spec, results))
+}
+
+fn merge_results(existing: &str, spec: &str, results: &str) -> String {
+    // Anything before the first board, then each board's spec and lines
+    let mut preamble = String::new();
+    let mut sections: Vec<(&str, String)> = Vec::new();
+    for line in existing.lines() {
+        if let Some(key) = line.strip_prefix("# board ") {
+            sections.push((key.trim(), String::new()));
+        } else if let Some((_, body)) = sections.last_mut() {
+            body.push_str(line);
+            body.push('\n');
+        } else {
+            preamble.push_str(line);
+            preamble.push('\n');
+        }
+    }
+    match sections.iter_mut().find(|(key, _)| *key == spec) {
+        Some((_, body)) => *body = results.to_string(),
+        None => sections.push((spec, results.to_string())),
+    }
+
+    let mut text = preamble;
+    for (i, (key, body)) in sections.iter().enumerate() {
+        if i > 0 {
+            text.push('\n');
+        }
+        text.push_str(&format!("# board {}\n{}\n", key, body.trim_end()));
+    }
+    text
+}
+
+// To stderr, so the solutions can still be piped somewhere
+fn print_solve_stats(stats: &SolveStats) {
+    eprintln!("Words filtered out: {}", stats.words_filtered);
+    eprintln!("Positions visited: {}", stats.nodes_expanded);
+    eprintln!("Transposition hits: {}", stats.transposition_hits);
+    eprintln!("Redundant chains rejected: {}", stats.redundancy_rejections);
+    eprintln!("{:>4} {:>12} {:>15} {:>10}", "Word", "Candidates", "No new letters", "Redundant");
+    for (depth, counts) in stats.per_depth.iter().enumerate() {
+        eprintln!(
+            "{:>4} {:>12} {:>15} {:>10}",
+            depth + 1,
+            counts.candidates,
+            counts.no_new_letters,
+            counts.redundancy_rejections
+        );
+    }
+    eprintln!();
+}
+
+// "  1. forklift-twangy   score 17   2 words   rarest: rare", with the solutions padded to `width` so the columns
+// line up. Everyday and common words are shown in green, and obscure ones in red.
+fn ranked_line(rank: usize, solution: &Solution, width: usize, color: bool, show_letters: bool) -> String {
+    let words: Vec<String> = solution
+        .words
+        .iter()
+        .map(|word| {
+            let code = match word.frequency_label() {
+                FrequencyLabel::Everyday | FrequencyLabel::Common => "32",
+                FrequencyLabel::Obscure => "31",
+                FrequencyLabel::Uncommon | FrequencyLabel::Rare => "",
+            };
+            if color && !code.is_empty() {
+                format!("\x1b[{}m{}\x1b[0m", code, word.word)
+            } else {
+                word.word.clone()
+            }
+        })
+        .collect();
+    // Padding is worked out on the plain text, since color codes take no room on screen
+    let padding = " ".repeat(width.saturating_sub(solution.to_string().len()));
+    let word_count = solution.words.len();
+    let rarest = solution.words.iter().map(|w| w.frequency_label()).min().map_or("", FrequencyLabel::as_str);
+    let mut line = format!(
+        "{:>4}. {}{}   score {:>3}   {} {:<5}   rarest: {:<8}",
+        rank,
+        words.join("-"),
+        padding,
+        solution.score_breakdown().total,
+        word_count,
+        if word_count == 1 { "word" } else { "words" },
+        rarest
+    );
+    if show_letters {
+        line.push_str(&format!("   {} letters, {} revisited", solution.letter_count(), solution.revisited_letters()));
+    }
+    line
+}
+
+// Ask for the board a side at a time, for when there's a person at the terminal but no board was given. A bad side
+// is pointed out as soon as it's typed, and asked for again.
+fn prompt_for_board(options: BoardOptions) -> Board {
+    let color = std::io::stderr().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+    eprintln!("No board given. Type each side's letters in order; an empty line quits.");
+    let mut sides: Vec<String> = Vec::new();
+    while sides.len() < 4 {
+        let name = board::SIDES_DISPLAY[sides.len()];
+        eprint!("{}{} side: ", name[..1].to_uppercase(), &name[1..]);
+        let mut line = String::new();
+        let read = std::io::stdin().read_line(&mut line).unwrap_or(0);
+        let side: String = line.chars().filter(|ch| !ch.is_whitespace()).collect::<String>().to_lowercase();
+        if read == 0 || side.is_empty() {
+            std::process::exit(EXIT_INVALID_BOARD);
+        }
+
+        let mut attempt = sides.clone();
+        attempt.push(side);
+        match Board::check_partial_sides(&attempt, &options) {
+            Ok(()) => sides = attempt,
+            Err(e) if color => eprintln!("\x1b[31m{}\x1b[0m", e),
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+    Board::from_sides_with_options(sides, options).unwrap_or_else(|e| {
+        eprintln!("Error in board: {}", e);
+        std::process::exit(EXIT_INVALID_BOARD);
+    })
+}
+
+#[cfg(feature = "ocr")]
+fn board_from_image(path: &str, options: BoardOptions) -> Board {
+    let sides = std::fs::read(path).map_err(LetterBouncedError::from).and_then(|bytes| ocr::sides_from_png(&bytes));
+    match sides.and_then(|sides| Board::from_sides_with_options(sides, options)) {
+        Ok(board) => {
+            debug!(path, board = %board.sides.join(","), "Read the board from the screenshot");
+            board
+        }
+        Err(e) => {
+            eprintln!("Error reading board from image: {}", e);
+            std::process::exit(EXIT_INVALID_BOARD);
         }
     }
 }
+
+#[cfg(not(feature = "ocr"))]
+fn board_from_image(_path: &str, _options: BoardOptions) -> Board {
+    eprintln!("Error: --from-image needs letter-bounced built with the ocr feature (cargo build --features ocr)");
+    std::process::exit(EXIT_INVALID_BOARD);
+}
+
+fn open_archive(path: &str) -> PuzzleArchive {
+    PuzzleArchive::open(path).unwrap_or_else(|e| {
+        eprintln!("Error reading puzzle archive: {}", e);
+        std::process::exit(1);
+    })
+}
+
+fn archived_board(archive_path: &str, date: &str, options: BoardOptions) -> Board {
+    let archive = open_archive(archive_path);
+    let Some(puzzle) = archive.get(date) else {
+        eprintln!("Error: No puzzle archived for {}", date);
+        std::process::exit(1);
+    };
+    match Board::from_sides_with_options(puzzle.sides.clone(), options) {
+        Ok(board) => board,
+        Err(e) => {
+            eprintln!("Error in archived board for {}: {}", date, e);
+            std::process::exit(EXIT_INVALID_BOARD);
+        }
+    }
+}
+
+fn save_puzzle(archive_path: &str, date: &str, sides: Vec<String>, solutions: &[Solution], answer: Vec<String>) {
+    let mut archive = open_archive(archive_path);
+    let date = if date.is_empty() { puzzles::today() } else { date.to_string() };
+    let puzzle = ArchivedPuzzle {
+        date,
+        sides,
+        solutions: solutions.iter().take(ARCHIVED_SOLUTIONS).map(|s| s.to_string()).collect(),
+        answer,
+    };
+    if let Err(e) = archive.add(puzzle).and_then(|_| archive.save()) {
+        eprintln!("Error saving puzzle: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn solve_batch(path: &str, args: &SolveArgs, dictionary: &Dictionary) {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) => {
+            eprintln!("Error reading batch file: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let specs: Vec<&str> = text.lines().map(str::trim).filter(|line| !line.is_empty()).collect();
+    let config = solver_config(args);
+    let options = args.board.board_options();
+
+    // Results come back in input order, however the boards were shared out
+    let results: Vec<String> = specs
+        .par_iter()
+        .map(|spec| batch_result(spec, dictionary, &config, options))
+        .collect();
+
+    let lines: String = results.into_iter().map(|line| line + "\n").collect();
+    match &args.output {
+        Some(path) => {
+            if let Err(e) = write_atomically(path, &lines) {
+                eprintln!("Error writing {}: {}", path, e);
+                std::process::exit(1);
+            }
+        }
+        None => print!("{}", lines),
+    }
+}
+
+// One JSON Lines record: the board and its solutions, or the board and why it couldn't be solved
+#[derive(Serialize)]
+struct BatchResult<'a> {
+    board: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    solutions: Option<Vec<BatchSolution>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timed_out: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Serialize)]
+struct BatchSolution {
+    solution: String,
+    score: u8,
+    letters: usize,
+    revisited: usize,
+    // How familiar each word is, in the solution's order
+    labels: Vec<FrequencyLabel>,
+}
+
+fn batch_result(spec: &str, dictionary: &Dictionary, config: &SolverConfig, options: BoardOptions) -> String {
+    let result = match Board::from_spec_with_options(spec, options) {
+        Ok(board) => {
+            let outcome = Solver::with_config(board, dictionary, config.clone()).solve_with_outcome(None);
+            let solutions = outcome
+                .solutions
+                .iter()
+                .map(|solution| BatchSolution {
+                    solution: solution.to_string(),
+                    score: solution.score_breakdown().total,
+                    letters: solution.letter_count(),
+                    revisited: solution.revisited_letters(),
+                    labels: solution.words.iter().map(|w| w.frequency_label()).collect(),
+                })
+                .collect();
+            BatchResult { board: spec, solutions: Some(solutions), timed_out: Some(outcome.timed_out), error: None }
+        }
+        Err(e) => BatchResult { board: spec, solutions: None, timed_out: None, error: Some(e.to_string()) },
+    };
+    serde_json::to_string(&result).expect("batch results always serialize")
+}
+
+#[derive(Serialize)]
+struct BenchBoard<'a> {
+    board: &'a str,
+    median_ms: f64,
+    nodes_expanded: usize,
+    solutions: usize,
+}
+
+#[derive(Serialize)]
+struct BenchReport<'a> {
+    boards: Vec<BenchBoard<'a>>,
+    p50_ms: f64,
+    p90_ms: f64,
+    p99_ms: f64,
+    max_ms: f64,
+}
+
+struct BenchResult {
+    board: String,
+    times: Vec<Duration>,
+    nodes_expanded: usize,
+    solutions: usize,
+}
+
+fn bench(args: &BenchArgs) {
+    let text = match &args.boards {
+        Some(path) => std::fs::read_to_string(path).unwrap_or_else(|e| {
+            eprintln!("Error reading boards: {}", e);
+            std::process::exit(1);
+        }),
+        None => BENCH_BOARDS.to_string(),
+    };
+    let dictionary = load_dictionary(args.dictionary.path.as_deref()).unwrap_or_else(|e| {
+        eprintln!("Error loading dictionary: {}", e);
+        std::process::exit(EXIT_DICTIONARY_ERROR);
+    });
+
+    let mut results = Vec::new();
+    for spec in text.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#')) {
+        let board = Board::from_spec(spec).unwrap_or_else(|e| {
+            eprintln!("Error in bench board '{}': {}", spec, e);
+            std::process::exit(EXIT_INVALID_BOARD);
+        });
+
+        let mut result = BenchResult {
+            board: spec.to_lowercase(),
+            times: Vec::new(),
+            nodes_expanded: 0,
+            solutions: 0,
+        };
+        for _ in 0..args.runs.max(1) {
+            // Includes building the solver, since filtering the dictionary is part of every real solve
+            let start = std::time::Instant::now();
+            let solver = Solver::new(board.clone(), &dictionary, args.max_solutions);
+            let (outcome, stats) = solver.solve_with_stats(None);
+            result.times.push(start.elapsed());
+            result.nodes_expanded = stats.nodes_expanded;
+            result.solutions = outcome.solutions.len();
+        }
+        result.times.sort();
+        results.push(result);
+    }
+
+    let mut all_times: Vec<Duration> = results.iter().flat_map(|r| r.times.iter().copied()).collect();
+    all_times.sort();
+    let summary = [("p50", 50), ("p90", 90), ("p99", 99), ("max", 100)].map(|(name, p)| (name, percentile(&all_times, p)));
+
+    if args.json {
+        let boards = results
+            .iter()
+            .map(|r| BenchBoard {
+                board: &r.board,
+                median_ms: rounded_millis(percentile(&r.times, 50)),
+                nodes_expanded: r.nodes_expanded,
+                solutions: r.solutions,
+            })
+            .collect();
+        let [p50, p90, p99, max] = summary.map(|(_, time)| rounded_millis(time));
+        let report = BenchReport { boards, p50_ms: p50, p90_ms: p90, p99_ms: p99, max_ms: max };
+        println!("{}", serde_json::to_string(&report).expect("bench reports always serialize"));
+    } else {
+        println!("{:<16} {:>12} {:>10} {:>10}", "board", "median ms", "nodes", "solutions");
+        for r in &results {
+            println!(
+                "{:<16} {:>12.3} {:>10} {:>10}",
+                r.board,
+                millis(percentile(&r.times, 50)),
+                r.nodes_expanded,
+                r.solutions
+            );
+        }
+        let summary: Vec<String> = summary
+            .iter()
+            .map(|(name, time)| format!("{} {:.3}ms", name, millis(*time)))
+            .collect();
+        println!("{}", summary.join(", "));
+    }
+}
+
+// Nearest-rank percentile of sorted durations
+fn percentile(sorted: &[Duration], p: usize) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = (p * sorted.len()).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+// Microsecond precision is plenty, and keeps the JSON readable
+fn rounded_millis(duration: Duration) -> f64 {
+    (millis(duration) * 1000.0).round() / 1000.0
+}
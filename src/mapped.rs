@@ -0,0 +1,393 @@
+#[cfg(not(target_arch = "wasm32"))]
+use crate::board::Board;
+use crate::dictionary::{Dictionary, Word};
+use crate::error::{LetterBouncedError, Result};
+use std::path::Path;
+#[cfg(not(target_arch = "wasm32"))]
+use tracing::{debug_span, field};
+
+/*
+ * A binary dictionary format which can be memory-mapped and read in place. Parsing the full text dictionary means
+ * building a Word (and its digraph set) for every one of ~180,000 lines, when a solve only needs the few thousand
+ * playable on one board. With the binary form, `MappedDictionary::playable_on` skims the mapped bytes and only
+ * materializes the words which pass.
+ *
+ * Layout: magic, word count and tier count, then the tier table, then an offset for each word's record, then the
+ * records. Words are stored most frequent first, and each run of words with the same frequency is a tier: the tier
+ * table holds each tier's first word index (u32) and frequency (one signed byte), so frequencies are stored once
+ * per tier rather than once per word. A record is the word's length (one byte) and its lowercase ASCII letters.
+ * Integers are little-endian u32.
+ *
+ * The web app can't map files, but can be sent the same bytes: `Dictionary::from_binary` decodes them from memory.
+ * Either way the layout is checked before anything is read, so damaged or hostile data is an error, not a panic.
+ */
+
+pub const MAGIC: &[u8; 4] = b"LBD2";
+const HEADER_LEN: usize = 12;
+const TIER_LEN: usize = 5;
+
+impl Dictionary {
+    /// Encode the dictionary in the binary format read by `Dictionary::open_mmap`. Words must be lowercase ASCII and
+    /// under 256 letters, and the whole file under 4GB. The format has nowhere to keep tags, or which layer of a
+    /// `Dictionary::layered` stack a word came from, so a word with either is an error too, rather than losing them.
+    pub fn to_binary(&self) -> Result<Vec<u8>> {
+        let too_big = |what: &str| LetterBouncedError::DictionaryParse(format!("Too many {} for the binary format", what));
+        let offset = |n: usize| u32::try_from(n).map_err(|_| too_big("words"));
+        let mut tiers: Vec<(u32, i8)> = Vec::new();
+        for (index, word) in self.words().iter().enumerate() {
+            if tiers.last().is_none_or(|&(_, frequency)| frequency != word.frequency) {
+                tiers.push((offset(index)?, word.frequency));
+            }
+        }
+
+        let offsets_start = HEADER_LEN + TIER_LEN * tiers.len();
+        let records_start = offsets_start + 4 * self.words().len();
+        let mut bytes = Vec::with_capacity(records_start);
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&offset(self.words().len())?.to_le_bytes());
+        bytes.extend_from_slice(&offset(tiers.len())?.to_le_bytes());
+        for (start, frequency) in tiers {
+            bytes.extend_from_slice(&start.to_le_bytes());
+            bytes.push(frequency as u8);
+        }
+
+        let mut records = Vec::new();
+        for word in self.words() {
+            let length = u8::try_from(word.word.len()).map_err(|_| {
+                LetterBouncedError::DictionaryParse(format!("'{}' is too long for the binary format", word.word))
+            })?;
+            if !word.word.bytes().all(|b| b.is_ascii_lowercase()) {
+                return Err(LetterBouncedError::DictionaryParse(format!(
+                    "'{}' isn't lowercase ASCII, which the binary format needs",
+                    word.word
+                )));
+            }
+            if !word.tags.is_empty() {
+                return Err(LetterBouncedError::DictionaryParse(format!(
+                    "'{}' has tags ({}), which the binary format can't store",
+                    word.word,
+                    word.tags.join(",")
+                )));
+            }
+            if word.layer != 0 {
+                return Err(LetterBouncedError::DictionaryParse(format!(
+                    "'{}' is from layer {} of a layered dictionary, which the binary format can't store",
+                    word.word, word.layer
+                )));
+            }
+            bytes.extend_from_slice(&offset(records_start + records.len())?.to_le_bytes());
+            records.push(length);
+            records.extend_from_slice(word.word.as_bytes());
+        }
+        offset(records_start + records.len()).map_err(|_| too_big("letters"))?;
+        bytes.extend_from_slice(&records);
+        Ok(bytes)
+    }
+
+    /// Decode a dictionary written by `to_binary` from memory, checking it first
+    pub fn from_binary(data: &[u8]) -> Result<Dictionary> {
+        let (len, tiers) = check_layout(data)?;
+        let offsets_start = HEADER_LEN + TIER_LEN * tiers.len();
+        let mut words = Vec::with_capacity(len);
+        for (tier_num, tier) in tiers.iter().enumerate() {
+            let end = tiers.get(tier_num + 1).map_or(len, |next| next.start);
+            for index in tier.start..end {
+                words.push(Word::new(record(data, offsets_start, index).to_string(), tier.frequency));
+            }
+        }
+        Ok(Dictionary::from_words(words))
+    }
+
+    /// Memory-map a dictionary written by `to_binary`. The words are checked, but not turned into `Word`s until
+    /// asked for.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn open_mmap<P: AsRef<Path>>(path: P) -> Result<MappedDictionary> {
+        let span = debug_span!("load_dictionary", path = %path.as_ref().display(), words = field::Empty).entered();
+        let file = std::fs::File::open(path)?;
+        // SAFETY: the map is read-only, and we only promise sound behaviour if nobody rewrites the dictionary file
+        // while it's in use, like every other program which maps files.
+        let map = unsafe { memmap2::Mmap::map(&file)? };
+        let mapped = MappedDictionary::new(map)?;
+        span.record("words", mapped.len());
+        Ok(mapped)
+    }
+}
+
+/// True if the file starts like a binary dictionary, so callers can accept either format
+pub fn is_binary_dictionary<P: AsRef<Path>>(path: P) -> bool {
+    use std::io::Read;
+
+    let mut magic = [0; 4];
+    std::fs::File::open(path)
+        .and_then(|mut file| file.read_exact(&mut magic))
+        .is_ok_and(|_| &magic == MAGIC)
+}
+
+// A run of words with the same frequency, starting at word index `start`
+#[derive(Debug, Clone, Copy)]
+struct Tier {
+    start: usize,
+    frequency: i8,
+}
+
+/// A binary dictionary read in place from a memory map. Memory maps need a filesystem, so this isn't in the wasm
+/// build.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct MappedDictionary {
+    map: memmap2::Mmap,
+    len: usize,
+    tiers: Vec<Tier>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl MappedDictionary {
+    fn new(map: memmap2::Mmap) -> Result<Self> {
+        let (len, tiers) = check_layout(&map)?;
+        Ok(MappedDictionary { map, len, tiers })
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The word at `index` and its frequency, without copying
+    pub fn entry(&self, index: usize) -> Option<(&str, i8)> {
+        (index < self.len).then(|| {
+            let tier = self.tiers[self.tiers.partition_point(|tier| tier.start <= index) - 1];
+            (self.word(index), tier.frequency)
+        })
+    }
+
+    /// Materialize the word at `index`
+    pub fn get(&self, index: usize) -> Option<Word> {
+        self.entry(index).map(|(word, frequency)| Word::new(word.to_string(), frequency))
+    }
+
+    /// Every word and its frequency, in dictionary order, without copying
+    pub fn iter(&self) -> impl Iterator<Item = (&str, i8)> + '_ {
+        self.tiers_iter(&self.tiers)
+    }
+
+    /// The words at least as frequent as `min_frequency`, reading only their tiers
+    pub fn words_at_least(&self, min_frequency: i8) -> impl Iterator<Item = (&str, i8)> + '_ {
+        let count = self.tiers.partition_point(|tier| tier.frequency >= min_frequency);
+        self.tiers_iter(&self.tiers[..count])
+    }
+
+    fn tiers_iter<'a>(&'a self, tiers: &'a [Tier]) -> impl Iterator<Item = (&'a str, i8)> + 'a {
+        tiers.iter().enumerate().flat_map(move |(tier_num, tier)| {
+            let end = self.tiers.get(tier_num + 1).map_or(self.len, |next| next.start);
+            (tier.start..end).map(move |index| (self.word(index), tier.frequency))
+        })
+    }
+
+    fn word(&self, index: usize) -> &str {
+        record(&self.map, HEADER_LEN + TIER_LEN * self.tiers.len(), index)
+    }
+
+    /// Materialize the whole dictionary
+    pub fn to_dictionary(&self) -> Dictionary {
+        Dictionary::from_words(self.iter().map(|(word, frequency)| Word::new(word.to_string(), frequency)).collect())
+    }
+
+    /// Materialize only the words which can be played on `board`: every letter is on the board, and no two
+    /// neighbouring letters share a side, unless the board's rules let a letter follow itself. Where a letter is
+    /// on more than one side, words which pass are also traced with `Board::is_word_playable`, since neighbouring
+    /// pairs can need different copies. Solving with the result gives the same answers as with the whole
+    /// dictionary.
+    pub fn playable_on(&self, board: &Board) -> Dictionary {
+        let span = debug_span!("playable_words", board = %board.sides.join(","), playable = field::Empty).entered();
+        // side_of[letter] is a bitmask of the sides the letter is on
+        let mut side_of = [0u8; 26];
+        for (side_num, side) in board.sides.iter().enumerate() {
+            for b in side.bytes() {
+                side_of[(b - b'a') as usize] |= 1 << side_num;
+            }
+        }
+        let sides = |b: u8| side_of[(b - b'a') as usize];
+        let playable = |word: &str| {
+            let bytes = word.as_bytes();
+            bytes.iter().all(|&b| sides(b) != 0)
+                // Neighbours need to be able to sit on different sides
+                && bytes.windows(2).all(|pair| {
+                    let (a, b) = (sides(pair[0]), sides(pair[1]));
+                    a != b || a.count_ones() > 1 || (pair[0] == pair[1] && board.rules.allows_doubled_letters())
+                })
+        };
+        let traced = board.has_letters_on_several_sides();
+
+        let dictionary = Dictionary::from_words(
+            self.iter()
+                .filter(|(word, _)| playable(word) && (!traced || board.is_word_playable(word).is_ok()))
+                .map(|(word, frequency)| Word::new(word.to_string(), frequency))
+                .collect(),
+        );
+        span.record("playable", dictionary.words().len());
+        dictionary
+    }
+}
+
+fn read_u32(data: &[u8], at: usize) -> usize {
+    u32::from_le_bytes(data[at..at + 4].try_into().unwrap()) as usize
+}
+
+// The word at `index`, in data which `check_layout` has accepted
+fn record(data: &[u8], offsets_start: usize, index: usize) -> &str {
+    let offset = read_u32(data, offsets_start + 4 * index);
+    let len = data[offset] as usize;
+    // Checked to be ASCII lowercase
+    std::str::from_utf8(&data[offset + 1..offset + 1 + len]).unwrap()
+}
+
+// Check the tiers run from the first word with falling frequencies, and every offset and record is in bounds with
+// a lowercase ASCII word. Returns the word count and the tiers.
+fn check_layout(data: &[u8]) -> Result<(usize, Vec<Tier>)> {
+    let error = |message: String| LetterBouncedError::DictionaryParse(message);
+    if data.len() < HEADER_LEN || &data[..4] != MAGIC {
+        return Err(error("Not a binary dictionary: bad magic bytes".to_string()));
+    }
+    let len = read_u32(data, 4);
+    let tier_count = read_u32(data, 8);
+    // Checked, since hostile counts could overflow a 32-bit usize, as on wasm
+    let offsets_start = TIER_LEN.checked_mul(tier_count).and_then(|tiers_len| tiers_len.checked_add(HEADER_LEN));
+    let offsets_end = offsets_start.and_then(|start| len.checked_mul(4).and_then(|offsets_len| offsets_len.checked_add(start)));
+    let (Some(offsets_start), Some(offsets_end)) = (offsets_start, offsets_end) else {
+        return Err(error(format!("Binary dictionary is too short for {} words", len)));
+    };
+    if data.len() < offsets_end {
+        return Err(error(format!("Binary dictionary is too short for {} words", len)));
+    }
+
+    let tiers: Vec<Tier> = (0..tier_count)
+        .map(|tier_num| {
+            let at = HEADER_LEN + TIER_LEN * tier_num;
+            Tier { start: read_u32(data, at), frequency: data[at + 4] as i8 }
+        })
+        .collect();
+    let tiers_ok = match tiers.first() {
+        Some(first) => first.start == 0,
+        None => len == 0,
+    } && tiers.last().is_none_or(|last| last.start < len)
+        && tiers.windows(2).all(|pair| pair[0].start < pair[1].start && pair[0].frequency > pair[1].frequency);
+    if !tiers_ok {
+        return Err(error("Binary dictionary has a damaged frequency tier table".to_string()));
+    }
+
+    for index in 0..len {
+        let offset = read_u32(data, offsets_start + 4 * index);
+        let word = data
+            .get(offset..)
+            .and_then(<[u8]>::split_first)
+            .and_then(|(&word_len, rest)| rest.get(..word_len as usize));
+        match word {
+            Some(word) if !word.is_empty() && word.iter().all(u8::is_ascii_lowercase) => {}
+            _ => return Err(error(format!("Binary dictionary record {} is damaged", index))),
+        }
+    }
+    Ok((len, tiers))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{BoardOptions, GameRules};
+
+    #[test]
+    fn test_binary_round_trip() {
+        let dictionary = Dictionary::from_text("and 31\nforklift 14\ntwangy 12\nzax 4\n");
+        let path = std::env::temp_dir().join(format!("letter_bounced_mapped_{}.bin", std::process::id()));
+        std::fs::write(&path, dictionary.to_binary().unwrap()).unwrap();
+
+        assert!(is_binary_dictionary(&path));
+        let mapped = Dictionary::open_mmap(&path).unwrap();
+        assert_eq!(mapped.len(), 4);
+        assert_eq!(mapped.entry(1), Some(("forklift", 14)));
+        assert_eq!(mapped.entry(4), None);
+        assert_eq!(mapped.get(3), Some(Word::new("zax".to_string(), 4)));
+        assert_eq!(mapped.to_dictionary().words(), dictionary.words());
+        assert_eq!(mapped.words_at_least(12).collect::<Vec<_>>(), vec![("and", 31), ("forklift", 14), ("twangy", 12)]);
+        assert_eq!(mapped.words_at_least(32).count(), 0);
+
+        let board = Board::from_spec("yfa,otk,lgw,rni").unwrap();
+        let playable: Vec<String> = mapped.playable_on(&board).into_words().into_iter().map(|w| w.word).collect();
+        assert_eq!(playable, vec!["forklift", "twangy"]);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_playable_on_repeated_letters() {
+        let dictionary = Dictionary::from_text("xay 20\nbay 20\n");
+        let path = std::env::temp_dir().join(format!("letter_bounced_repeated_{}.bin", std::process::id()));
+        std::fs::write(&path, dictionary.to_binary().unwrap()).unwrap();
+        let mapped = Dictionary::open_mmap(&path).unwrap();
+
+        // XA and AY each need a different copy of A
+        let options = BoardOptions::new(GameRules::DuplicateLetters);
+        let board = Board::from_spec_with_options("ayb,axc,def,ghi", options).unwrap();
+        let playable: Vec<String> = mapped.playable_on(&board).into_words().into_iter().map(|w| w.word).collect();
+        assert_eq!(playable, vec!["bay"]);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_binary_refuses_what_it_cant_store() {
+        let tagged = Dictionary::from_text("colour 22 british\ncolor 22\n");
+        let error = tagged.to_binary().unwrap_err().to_string();
+        assert!(error.contains("'colour' has tags (british)"), "{}", error);
+
+        let layered = Dictionary::layered(vec![Dictionary::from_text("color 22\n"), Dictionary::from_text("qajaq 8\n")]);
+        let error = layered.to_binary().unwrap_err().to_string();
+        assert!(error.contains("'qajaq' is from layer 1"), "{}", error);
+    }
+
+    #[test]
+    fn test_damaged_binary() {
+        let mut bytes = Dictionary::from_text("forklift 14\n").to_binary().unwrap();
+        assert_eq!(check_layout(&bytes).unwrap().0, 1);
+
+        bytes.truncate(bytes.len() - 1);
+        assert!(matches!(check_layout(&bytes), Err(LetterBouncedError::DictionaryParse(_))));
+        assert!(check_layout(b"forklift 14\n").is_err());
+
+        // Tiers must fall in frequency
+        let mut bytes = Dictionary::from_text("and 31\nforklift 14\n").to_binary().unwrap();
+        assert_eq!(check_layout(&bytes).unwrap().1.len(), 2);
+        bytes[HEADER_LEN + TIER_LEN + 4] = 31;
+        assert!(check_layout(&bytes).is_err());
+
+        // Counts too big to address, and empty words
+        let mut bytes = Dictionary::from_text("forklift 14\n").to_binary().unwrap();
+        bytes[8..12].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(check_layout(&bytes).is_err());
+        let mut bytes = Dictionary::from_text("forklift 14\n").to_binary().unwrap();
+        bytes[4..8].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(check_layout(&bytes).is_err());
+        let mut bytes = Dictionary::from_text("forklift 14\n").to_binary().unwrap();
+        let record_start = HEADER_LEN + TIER_LEN + 4;
+        bytes[record_start] = 0;
+        assert!(Dictionary::from_binary(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_from_binary() {
+        let dictionary = Dictionary::from_text("and 31\nforklift 14\ntwangy 14\nzax 4\n");
+        let bytes = dictionary.to_binary().unwrap();
+
+        assert_eq!(Dictionary::from_binary(&bytes).unwrap().words(), dictionary.words());
+        // from_bytes takes either format, as the web app may be sent either
+        assert_eq!(Dictionary::from_bytes(&bytes).unwrap().words(), dictionary.words());
+        assert!(Dictionary::from_binary(&bytes[..bytes.len() - 2]).is_err());
+
+        // Words the records can't hold are refused rather than cut short
+        let long = Dictionary::from_text(&format!("{} 14\n", "a".repeat(256)));
+        assert!(matches!(long.to_binary(), Err(LetterBouncedError::DictionaryParse(_))));
+        assert!(Dictionary::from_text(&format!("{} 14\n", "a".repeat(255))).to_binary().is_ok());
+        assert!(Dictionary::from_text("café 14\n").to_binary().is_err());
+    }
+}
@@ -0,0 +1,427 @@
+use crate::error::{LetterBouncedError, Result};
+
+/*
+ * Reading a board's letters from a screenshot of the puzzle, since typing them in by hand is the easiest way to end
+ * up solving the wrong board. It only knows the NYT's layout: a square box with three letters outside each side,
+ * dark on a light background.
+ *
+ * The box is found from its left and right edges, the columns with the most dark pixels, and how far down those
+ * edges run. Each side's letters sit in a band just outside it, and are told apart by the gaps between them. A
+ * letter is cropped to its bounding box, shrunk to an 8x10 grid, and compared with a template of every letter, the
+ * best correlated winning, so strokes heavier or lighter than the template's don't matter much. The templates are
+ * DejaVu Sans, rendered large and shrunk the same way, which is near enough to the puzzle's typeface at that size.
+ */
+
+const GRID_WIDTH: usize = 8;
+const GRID_HEIGHT: usize = 10;
+
+// Pixels darker than this are ink
+const DARK_LUMINANCE: u32 = 128;
+
+// Where a side's letters are looked for, as fractions of the box's width measured out from the side
+const BAND_START: f64 = 0.07;
+const BAND_END: f64 = 0.35;
+
+// How much of each cell of each letter's 8x10 grid is ink, from 0 to 9, row by row from the top. I is left out,
+// as its grid is solid; it's told by its shape instead.
+const TEMPLATES: [(char, &str); 25] = [
+    (
+        'A',
+        "00067000001882000046640000743700019118200460065007866770184444824700006574000047",
+    ),
+    (
+        'B',
+        "79988730862226928600019486000392887789408622259286000077860000788611249589999850",
+    ),
+    (
+        'C',
+        "01479873068422484810000175000000940000009400000075000000482000010684335800478862",
+    ),
+    (
+        'D',
+        "79887510852247718300018583000058830000398300003983000058830000858412378189998510",
+    ),
+    (
+        'E',
+        "79999996882222228700000087000000897777748822222187000000870000008722222189999998",
+    ),
+    (
+        'F',
+        "79999998793222227910000079100000798777737932222179100000791000007910000079100000",
+    ),
+    (
+        'G',
+        "01589862177323565710000184000000930013329300277884000038581000381774236801478751",
+    ),
+    (
+        'H',
+        "75000048850000498500004985000049887777898622225985000049850000498500004985000049",
+    ),
+    (
+        'J',
+        "00003998000039980000399800003998000039980000399800003998000039980113798378876520",
+    ),
+    (
+        'K',
+        "74000582840068208406810085671000898100008795000084384000840394008400394084000394",
+    ),
+    (
+        'L',
+        "77000000870000008700000087000000870000008700000087000000870000008722222289999998",
+    ),
+    (
+        'M',
+        "88100078883002898660056984810849826327298236542982177129820670298201102982000029",
+    ),
+    (
+        'N',
+        "78300048897000488683004884571048841840488404814884007548840038588400069884000398",
+    ),
+    (
+        'O',
+        "01688610186226715700007584000047930000399300003984000047570000751763367101588510",
+    ),
+    (
+        'P',
+        "79988730872236948700008787000087871125958999873087110000870000008700000087000000",
+    ),
+    (
+        'Q',
+        "02688620284114826500006683000038930000398400004857000075177447610035681000000660",
+    ),
+    (
+        'R',
+        "79987400862259308500075085000750863369208877940085004920850006608500029285000077",
+    ),
+    (
+        'S',
+        "04789861494223517600000068200000179865200023589300000087000000776532359336788630",
+    ),
+    (
+        'T',
+        "89999998222662220006600000066000000660000006600000066000000660000006600000066000",
+    ),
+    (
+        'U',
+        "85000057850000588500005885000058850000588500005885000058670000762853358202688620",
+    ),
+    (
+        'V',
+        "64000037470000651810018207400370047006500292192000743700004665000018820000077000",
+    ),
+    (
+        'W',
+        "71044017620550265305503544055044251551521734437107422470066116600580085004700740",
+    ),
+    (
+        'X',
+        "28200074066004810183183000486600000781000028930000754710048107401830038266000066",
+    ),
+    (
+        'Y',
+        "56000065183003810481184000755700002882000006600000066000000660000006600000066000",
+    ),
+    (
+        'Z',
+        "79999997222224940000176000006710000482000039300001850000076000006832222299999999",
+    ),
+];
+
+// Which pixels of the image are dark
+struct Ink {
+    width: usize,
+    height: usize,
+    dark: Vec<bool>,
+}
+
+impl Ink {
+    fn is_dark(&self, x: usize, y: usize) -> bool {
+        self.dark[y * self.width + x]
+    }
+}
+
+// A rectangle of pixels, right and bottom exclusive
+#[derive(Debug, Clone, Copy)]
+struct Region {
+    left: usize,
+    top: usize,
+    right: usize,
+    bottom: usize,
+}
+
+/**
+ * The sides of the board in a PNG screenshot, in spec order: top and bottom left to right, left and right top to
+ * bottom. The letters are lowercase, ready for `Board::from_sides`.
+ */
+pub fn sides_from_png(bytes: &[u8]) -> Result<Vec<String>> {
+    let ink = decode(bytes)?;
+    let (box_region, edge) = find_box(&ink)?;
+    let size = box_region.right - box_region.left;
+    let near = (size as f64 * BAND_START) as usize;
+    let far = (size as f64 * BAND_END) as usize;
+    let bands = [
+        Region {
+            left: box_region.left,
+            top: box_region.top.saturating_sub(far),
+            right: box_region.right,
+            bottom: box_region.top.saturating_sub(near),
+        },
+        Region {
+            left: (box_region.right + edge + near).min(ink.width),
+            top: box_region.top,
+            right: (box_region.right + edge + far).min(ink.width),
+            bottom: box_region.bottom,
+        },
+        Region {
+            left: box_region.left.saturating_sub(far),
+            top: box_region.top,
+            right: box_region.left.saturating_sub(near),
+            bottom: box_region.bottom,
+        },
+        Region {
+            left: box_region.left,
+            top: (box_region.bottom + near).min(ink.height),
+            right: box_region.right,
+            bottom: (box_region.bottom + far).min(ink.height),
+        },
+    ];
+
+    ["top", "right", "left", "bottom"]
+        .iter()
+        .zip(bands)
+        .enumerate()
+        .map(|(i, (name, band))| {
+            let letters = find_letters(&ink, band, i == 0 || i == 3);
+            if letters.len() != 3 {
+                return Err(LetterBouncedError::Image(format!(
+                    "Found {} letters on the {} side, not 3",
+                    letters.len(),
+                    name
+                )));
+            }
+            Ok(letters
+                .iter()
+                .map(|&letter| recognize(&ink, letter).to_ascii_lowercase())
+                .collect())
+        })
+        .collect()
+}
+
+fn decode(bytes: &[u8]) -> Result<Ink> {
+    let image_error = |e: png::DecodingError| LetterBouncedError::Image(format!("Can't read the PNG: {}", e));
+    let mut decoder = png::Decoder::new(bytes);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(image_error)?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let frame = reader.next_frame(&mut buffer).map_err(image_error)?;
+    let channels = frame.color_type.samples();
+
+    // Transparent pixels count as the white they'd be shown on
+    let dark = buffer[..frame.buffer_size()]
+        .chunks_exact(channels)
+        .map(|pixel| {
+            let (luminance, alpha) = match *pixel {
+                [gray] => (gray as u32, 255),
+                [gray, alpha] => (gray as u32, alpha as u32),
+                [r, g, b] => ((r as u32 * 299 + g as u32 * 587 + b as u32 * 114) / 1000, 255),
+                [r, g, b, alpha] => ((r as u32 * 299 + g as u32 * 587 + b as u32 * 114) / 1000, alpha as u32),
+                _ => (255, 255),
+            };
+            (luminance * alpha + 255 * (255 - alpha)) / 255 < DARK_LUMINANCE
+        })
+        .collect();
+    Ok(Ink {
+        width: frame.width as usize,
+        height: frame.height as usize,
+        dark,
+    })
+}
+
+// The box's outline, from the outside of its left and top edges to the inside of its right and bottom ones, and
+// how thick its edges are
+fn find_box(ink: &Ink) -> Result<(Region, usize)> {
+    let not_found = || LetterBouncedError::Image("Can't find the puzzle's box".to_string());
+    let column_ink: Vec<usize> = (0..ink.width)
+        .map(|x| (0..ink.height).filter(|&y| ink.is_dark(x, y)).count())
+        .collect();
+    let most = column_ink.iter().copied().max().unwrap_or(0);
+    let is_edge = |x: usize| most > 0 && column_ink[x] * 2 >= most;
+    let left = (0..ink.width).find(|&x| is_edge(x)).ok_or_else(not_found)?;
+    let right = (0..ink.width).rev().find(|&x| is_edge(x)).ok_or_else(not_found)?;
+    let edge = (left..=right).take_while(|&x| is_edge(x)).count();
+    let size = right + 1 - left;
+    if size < ink.width / 4 {
+        return Err(not_found());
+    }
+
+    // The left edge runs from the top of the box to the bottom, broken only by the circles on it
+    let on_edge = |y: usize| (left..left + edge).any(|x| ink.is_dark(x, y));
+    let max_gap = size / 8;
+    let (mut best, mut start, mut last) = ((0, 0), None, 0);
+    for y in (0..ink.height).filter(|&y| on_edge(y)) {
+        match start {
+            Some(_) if y - last <= max_gap => {}
+            _ => start = Some(y),
+        }
+        last = y;
+        let run = (start.unwrap_or(y), y + 1);
+        if run.1 - run.0 > best.1 - best.0 {
+            best = run;
+        }
+    }
+    let (top, bottom) = best;
+    // It's a square, give or take
+    if (bottom - top).abs_diff(size) > size / 5 {
+        return Err(not_found());
+    }
+    // A box whose edges are as thick as it is wide, like a solid block of ink, has no inside
+    let inside = |outer: usize, start: usize| outer.checked_sub(edge).filter(|&inner| inner > start + edge);
+    let region = Region {
+        left,
+        top,
+        right: inside(right + 1, left).ok_or_else(not_found)?,
+        bottom: inside(bottom, top).ok_or_else(not_found)?,
+    };
+    Ok((region, edge))
+}
+
+// The bounding boxes of the letters in a band, in order along it. The letters on the top and bottom sides are
+// side by side, and those on the left and right are stacked.
+fn find_letters(ink: &Ink, band: Region, across: bool) -> Vec<Region> {
+    let (along, thickness) = if across {
+        (band.left..band.right, band.top..band.bottom)
+    } else {
+        (band.top..band.bottom, band.left..band.right)
+    };
+    let dark = |a: usize, t: usize| if across { ink.is_dark(a, t) } else { ink.is_dark(t, a) };
+
+    let mut letters = Vec::new();
+    let mut start = None;
+    for a in along.clone().chain(std::iter::once(along.end)) {
+        let inked = a < along.end && thickness.clone().any(|t| dark(a, t));
+        match (inked, start) {
+            (true, None) => start = Some(a),
+            (false, Some(from)) => {
+                let (low, high) = thickness
+                    .clone()
+                    .filter(|&t| (from..a).any(|a| dark(a, t)))
+                    .fold((usize::MAX, 0), |(low, high), t| (low.min(t), high.max(t + 1)));
+                letters.push(if across {
+                    Region {
+                        left: from,
+                        top: low,
+                        right: a,
+                        bottom: high,
+                    }
+                } else {
+                    Region {
+                        left: low,
+                        top: from,
+                        right: high,
+                        bottom: a,
+                    }
+                });
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    letters
+}
+
+// The letter whose template best correlates with the ink in `region`
+fn recognize(ink: &Ink, region: Region) -> char {
+    // An I's bounding box is nearly all ink, which makes it the one letter with no shape to correlate
+    if (region.right - region.left) * 3 < region.bottom - region.top {
+        return 'I';
+    }
+    let grid = shrink(ink, region);
+    TEMPLATES
+        .iter()
+        .map(|(letter, template)| {
+            let template: Vec<f64> = template.bytes().map(|cell| (cell - b'0') as f64 / 9.0).collect();
+            (correlation(&template, &grid), *letter)
+        })
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+        .map_or('?', |(_, letter)| letter)
+}
+
+// Pearson correlation of two equally long lists of values, 0 if either doesn't vary
+fn correlation(a: &[f64], b: &[f64]) -> f64 {
+    let mean = |values: &[f64]| values.iter().sum::<f64>() / values.len() as f64;
+    let (mean_a, mean_b) = (mean(a), mean(b));
+    let (mut covariance, mut variance_a, mut variance_b) = (0.0, 0.0, 0.0);
+    for (x, y) in a.iter().zip(b) {
+        covariance += (x - mean_a) * (y - mean_b);
+        variance_a += (x - mean_a) * (x - mean_a);
+        variance_b += (y - mean_b) * (y - mean_b);
+    }
+    if variance_a == 0.0 || variance_b == 0.0 {
+        0.0
+    } else {
+        covariance / (variance_a * variance_b).sqrt()
+    }
+}
+
+// How much of each cell of an 8x10 grid over `region` is ink, row by row
+fn shrink(ink: &Ink, region: Region) -> Vec<f64> {
+    let (width, height) = (region.right - region.left, region.bottom - region.top);
+    let mut grid = Vec::with_capacity(GRID_WIDTH * GRID_HEIGHT);
+    for row in 0..GRID_HEIGHT {
+        let (y0, y1) = (row * height / GRID_HEIGHT, ((row + 1) * height).div_ceil(GRID_HEIGHT));
+        for column in 0..GRID_WIDTH {
+            let (x0, x1) = (column * width / GRID_WIDTH, ((column + 1) * width).div_ceil(GRID_WIDTH));
+            let cells = (y0..y1).flat_map(|y| (x0..x1).map(move |x| (x, y)));
+            let (dark, total) = cells.fold((0, 0), |(dark, total), (x, y)| {
+                (dark + ink.is_dark(region.left + x, region.top + y) as usize, total + 1)
+            });
+            grid.push(dark as f64 / total.max(1) as f64);
+        }
+    }
+    grid
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sides_from_png() {
+        let bytes = std::fs::read("NY_Times_Letter_Boxed.png").unwrap();
+        assert_eq!(sides_from_png(&bytes).unwrap(), vec!["rvh", "eai", "omg", "wnp"]);
+    }
+
+    #[test]
+    fn test_not_a_board() {
+        assert!(matches!(
+            sides_from_png(b"not a png"),
+            Err(LetterBouncedError::Image(_))
+        ));
+        let bytes = std::fs::read("web-speed-1.png").unwrap();
+        assert!(sides_from_png(&bytes).is_err());
+    }
+
+    // A grayscale PNG of one flat shade
+    fn flat_png(width: u32, height: u32, gray: u8) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        let mut encoder = png::Encoder::new(&mut bytes, width, height);
+        encoder.set_color(png::ColorType::Grayscale);
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(&vec![gray; (width * height) as usize]).unwrap();
+        writer.finish().unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_no_box() {
+        for (width, height, gray) in [(100, 85, 0), (100, 100, 0), (1, 1, 0), (3, 2, 0), (100, 85, 255)] {
+            assert!(
+                matches!(sides_from_png(&flat_png(width, height, gray)), Err(LetterBouncedError::Image(_))),
+                "{}x{} of {}",
+                width,
+                height,
+                gray
+            );
+        }
+    }
+}
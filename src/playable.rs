@@ -0,0 +1,187 @@
+use crate::board::{Board, BoardOptions};
+use crate::dictionary::Dictionary;
+use crate::error::{LetterBouncedError, Result};
+use std::collections::BTreeSet;
+
+/*
+ * Which dictionary words are playable on a board, kept up to date as the board's letters change one at a time.
+ *
+ * `Board::playable_dictionary` checks every word in the dictionary, which is fine once per board but slow when the
+ * generator tries thousands of variations on one. Swapping a letter can only change the playability of words which
+ * contain the old letter or the new one, so that's all `replace_letter` rechecks. Each word's set of letters is kept
+ * as a bitmask, so most words with the new letter are ruled out without looking at their digraphs.
+ */
+
+pub struct PlayableIndex<'a> {
+    dictionary: &'a Dictionary,
+    board: Board,
+    // Bit n is set for each word with the nth letter of the alphabet, by dictionary index
+    letter_masks: Vec<u32>,
+    // Dictionary indices of the words with each letter of the alphabet
+    words_with_letter: Vec<Vec<usize>>,
+    // Dictionary indices of the words playable on `board`, so iterating keeps dictionary order
+    playable: BTreeSet<usize>,
+}
+
+fn letter_bit(letter: char) -> u32 {
+    if letter.is_ascii_lowercase() {
+        1 << (letter as u8 - b'a')
+    } else {
+        // Letters outside a-z never appear on a board, so this bit is never in a board's mask
+        1 << 26
+    }
+}
+
+fn letters_mask(letters: impl Iterator<Item = char>) -> u32 {
+    letters.fold(0, |mask, letter| mask | letter_bit(letter))
+}
+
+impl<'a> PlayableIndex<'a> {
+    pub fn new(board: Board, dictionary: &'a Dictionary) -> Self {
+        let letter_masks: Vec<u32> = dictionary.words().iter().map(|w| letters_mask(w.word.chars())).collect();
+        let mut words_with_letter = vec![Vec::new(); 26];
+        for (index, mask) in letter_masks.iter().enumerate() {
+            for (letter, words) in words_with_letter.iter_mut().enumerate() {
+                if mask & (1 << letter) != 0 {
+                    words.push(index);
+                }
+            }
+        }
+
+        let mut index = PlayableIndex {
+            dictionary,
+            board,
+            letter_masks,
+            words_with_letter,
+            playable: BTreeSet::new(),
+        };
+        let board_mask = index.board_mask();
+        index.playable = (0..dictionary.words().len()).filter(|&i| index.is_playable(i, board_mask)).collect();
+        index
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// How many dictionary words are playable on the board
+    pub fn len(&self) -> usize {
+        self.playable.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.playable.is_empty()
+    }
+
+    /// The playable words, as `Board::playable_dictionary` would give them
+    pub fn playable_dictionary(&self) -> Dictionary {
+        let words = self.playable.iter().map(|&i| self.dictionary.words()[i].clone()).collect();
+        Dictionary::from_words(words)
+    }
+
+    /**
+     * Put `letter` at `position` on side `side`, rechecking only the words the change could affect. Returns the
+     * letter it replaced. Fails, leaving the board as it was, if the position doesn't exist or the new board would
+     * break its rules.
+     */
+    pub fn replace_letter(&mut self, side: usize, position: usize, letter: char) -> Result<char> {
+        let old = self
+            .board
+            .sides
+            .get(side)
+            .and_then(|s| s.chars().nth(position))
+            .ok_or_else(|| LetterBouncedError::Board(format!("No letter {} on side {}", position + 1, side + 1)))?;
+
+        let mut sides = self.board.sides.clone();
+        sides[side] = sides[side].chars().enumerate().map(|(i, c)| if i == position { letter } else { c }).collect();
+        self.board = Board::from_sides_with_options(sides, BoardOptions::new(self.board.rules))?;
+        let board_mask = self.board_mask();
+
+        // Words with the old letter may have lost a digraph. Only the playable ones can change.
+        let old_bit = letter_bit(old);
+        let lost: Vec<usize> = self
+            .playable
+            .iter()
+            .copied()
+            .filter(|&i| self.letter_masks[i] & old_bit != 0 && !self.is_playable(i, board_mask))
+            .collect();
+        for i in lost {
+            self.playable.remove(&i);
+        }
+
+        // Words with the new letter may have gained one
+        if let Some(index) = letter.is_ascii_lowercase().then(|| (letter as u8 - b'a') as usize) {
+            for &i in &self.words_with_letter[index] {
+                if self.is_playable(i, board_mask) {
+                    self.playable.insert(i);
+                }
+            }
+        }
+        Ok(old)
+    }
+
+    fn board_mask(&self) -> u32 {
+        letters_mask(self.board.sides.iter().flat_map(|side| side.chars()))
+    }
+
+    fn is_playable(&self, index: usize, board_mask: u32) -> bool {
+        let word = &self.dictionary.words()[index];
+        self.letter_masks[index] & !board_mask == 0
+            && word.digraphs.is_subset(&self.board.digraphs)
+            && (!self.board.has_letters_on_several_sides() || self.board.is_word_playable(&word.word).is_ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::GameRules;
+
+    fn words(dictionary: &Dictionary) -> Vec<&str> {
+        dictionary.words().iter().map(|w| w.word.as_str()).collect()
+    }
+
+    #[test]
+    fn test_matches_playable_dictionary() {
+        let dictionary = Dictionary::from_text("forklift 14\ntwangy 12\nfiltration 15\nnag 20\ngawkily 10\nabode 20\nfly 18\nfry 9\n");
+        let board = Board::from_spec("yfa,otk,lgw,rni").unwrap();
+        let mut index = PlayableIndex::new(board.clone(), &dictionary);
+        assert_eq!(words(&index.playable_dictionary()), words(&board.playable_dictionary(&dictionary)));
+        assert_eq!(index.len(), 7);
+
+        // Swapping L for E loses every word with an L
+        assert_eq!(index.replace_letter(2, 0, 'e').unwrap(), 'l');
+        assert_eq!(index.board().sides, vec!["yfa", "otk", "egw", "rni"]);
+        let expected = index.board().playable_dictionary(&dictionary);
+        assert_eq!(words(&index.playable_dictionary()), words(&expected));
+        assert_eq!(words(&expected), vec!["nag", "twangy", "fry"]);
+
+        // and swapping it back brings them back
+        index.replace_letter(2, 0, 'l').unwrap();
+        assert_eq!(words(&index.playable_dictionary()), words(&board.playable_dictionary(&dictionary)));
+    }
+
+    #[test]
+    fn test_repeated_letters() {
+        let dictionary = Dictionary::from_text("xay 20\nbay 20\nday 20\n");
+        let options = BoardOptions::new(GameRules::DuplicateLetters);
+        let board = Board::from_spec_with_options("ayb,axc,def,ghi", options).unwrap();
+        let index = PlayableIndex::new(board.clone(), &dictionary);
+        assert_eq!(words(&index.playable_dictionary()), words(&board.playable_dictionary(&dictionary)));
+        assert_eq!(words(&index.playable_dictionary()), vec!["bay", "day"]);
+    }
+
+    #[test]
+    fn test_replace_letter_errors() {
+        let dictionary = Dictionary::from_text("forklift 14\ntwangy 12\n");
+        let board = Board::from_spec("yfa,otk,lgw,rni").unwrap();
+        let mut index = PlayableIndex::new(board, &dictionary);
+
+        assert!(index.replace_letter(4, 0, 'e').is_err());
+        assert!(index.replace_letter(0, 3, 'e').is_err());
+        // F is already on the board
+        assert!(index.replace_letter(2, 0, 'f').is_err());
+        assert_eq!(index.board().sides, vec!["yfa", "otk", "lgw", "rni"]);
+        assert_eq!(index.len(), 2);
+    }
+}
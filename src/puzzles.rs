@@ -0,0 +1,271 @@
+use crate::error::{LetterBouncedError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/*
+ * A personal archive of boards, one per date, kept as a JSON file. Each entry remembers the solver's best
+ * solutions at the time it was saved, so they can be compared with later solver versions or with your own
+ * answers.
+ */
+
+// How many entries play_stats reports in its top-N lists
+const TOP_COUNT: usize = 10;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchivedPuzzle {
+    /// YYYY-MM-DD
+    pub date: String,
+    pub sides: Vec<String>,
+    /// The solver's best solutions when the puzzle was saved, best first, e.g. "forklift-twangy"
+    pub solutions: Vec<String>,
+    /// The words you played, if you recorded them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub answer: Vec<String>,
+}
+
+impl ArchivedPuzzle {
+    /// The fewest words any of the solver's solutions used
+    pub fn solver_best_word_count(&self) -> Option<usize> {
+        self.solutions.iter().map(|s| s.split('-').count()).min()
+    }
+}
+
+/// How your recorded answers compare with the solver
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayStats {
+    /// Puzzles with a recorded answer
+    pub puzzles_played: usize,
+    pub average_answer_words: f64,
+    /// Average of the solver's shortest solution, over the same puzzles
+    pub average_solver_words: f64,
+    /// Your most played words and how often, most used first
+    pub most_used_words: Vec<(String, usize)>,
+    /// Letters which, on puzzles where you used more words than the solver, you only reached in your last word.
+    /// Those are the letters which cost you the extra words. Most often first.
+    pub struggle_letters: Vec<(char, usize)>,
+}
+
+#[derive(Debug)]
+pub struct PuzzleArchive {
+    path: PathBuf,
+    puzzles: Vec<ArchivedPuzzle>,
+}
+
+impl PuzzleArchive {
+    /// Load the archive at `path`, or start an empty one if the file doesn't exist yet
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let puzzles = match fs::read_to_string(&path) {
+            Ok(text) => serde_json::from_str(&text).map_err(|e| {
+                LetterBouncedError::Archive(format!("Invalid puzzle archive {}: {}", path.display(), e))
+            })?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(PuzzleArchive { path, puzzles })
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let json = serde_json::to_string_pretty(&self.puzzles)?;
+        fs::write(&self.path, json + "\n")?;
+        Ok(())
+    }
+
+    /// Add a puzzle, replacing any already archived for the same date. Keeps the archive in date order.
+    pub fn add(&mut self, puzzle: ArchivedPuzzle) -> Result<()> {
+        if !is_valid_date(&puzzle.date) {
+            return Err(LetterBouncedError::Archive(format!(
+                "Invalid date '{}'. Dates must look like 2025-01-31",
+                puzzle.date
+            )));
+        }
+        self.puzzles.retain(|p| p.date != puzzle.date);
+        let position = self.puzzles.partition_point(|p| p.date < puzzle.date);
+        self.puzzles.insert(position, puzzle);
+        Ok(())
+    }
+
+    /// Record the words you played for an archived puzzle
+    pub fn record_answer(&mut self, date: &str, words: Vec<String>) -> Result<()> {
+        match self.puzzles.iter_mut().find(|p| p.date == date) {
+            Some(puzzle) => {
+                puzzle.answer = words.iter().map(|w| w.to_lowercase()).collect();
+                Ok(())
+            }
+            None => Err(LetterBouncedError::Archive(format!("No puzzle archived for {}", date))),
+        }
+    }
+
+    pub fn get(&self, date: &str) -> Option<&ArchivedPuzzle> {
+        self.puzzles.iter().find(|p| p.date == date)
+    }
+
+    pub fn puzzles(&self) -> &[ArchivedPuzzle] {
+        &self.puzzles
+    }
+}
+
+pub fn play_stats(puzzles: &[ArchivedPuzzle]) -> PlayStats {
+    let mut answer_words = 0;
+    let mut solver_words = 0;
+    let mut played = 0;
+    let mut word_counts: HashMap<&str, usize> = HashMap::new();
+    let mut letter_counts: HashMap<char, usize> = HashMap::new();
+
+    for puzzle in puzzles {
+        let Some(solver_best) = puzzle.solver_best_word_count() else {
+            continue;
+        };
+        if puzzle.answer.is_empty() {
+            continue;
+        }
+        played += 1;
+        answer_words += puzzle.answer.len();
+        solver_words += solver_best;
+        for word in &puzzle.answer {
+            *word_counts.entry(word.as_str()).or_default() += 1;
+        }
+
+        if puzzle.answer.len() > solver_best {
+            let (last, earlier) = puzzle.answer.split_last().unwrap();
+            let reached_earlier: HashSet<char> = earlier.iter().flat_map(|w| w.chars()).collect();
+            let late_letters: HashSet<char> = last.chars().filter(|c| !reached_earlier.contains(c)).collect();
+            for letter in late_letters {
+                *letter_counts.entry(letter).or_default() += 1;
+            }
+        }
+    }
+
+    let average = |total: usize| if played == 0 { 0.0 } else { total as f64 / played as f64 };
+    PlayStats {
+        puzzles_played: played,
+        average_answer_words: average(answer_words),
+        average_solver_words: average(solver_words),
+        most_used_words: top_counts(word_counts.into_iter().map(|(w, n)| (w.to_string(), n))),
+        struggle_letters: top_counts(letter_counts.into_iter()),
+    }
+}
+
+// Highest counts first, ties in natural order
+fn top_counts<T: Ord>(counts: impl Iterator<Item = (T, usize)>) -> Vec<(T, usize)> {
+    let mut counts: Vec<(T, usize)> = counts.collect();
+    counts.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+    counts.truncate(TOP_COUNT);
+    counts
+}
+
+fn is_valid_date(date: &str) -> bool {
+    let parts: Vec<&str> = date.split('-').collect();
+    let digits = |part: &str, len: usize| part.len() == len && part.chars().all(|c| c.is_ascii_digit());
+    match parts.as_slice() {
+        [year, month, day] if digits(year, 4) && digits(month, 2) && digits(day, 2) => {
+            matches!(month.parse::<u32>(), Ok(1..=12)) && matches!(day.parse::<u32>(), Ok(1..=31))
+        }
+        _ => false,
+    }
+}
+
+/// Today's date in UTC as YYYY-MM-DD
+pub fn today() -> String {
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() / 86_400) as i64;
+    let (year, month, day) = civil_from_days(days);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+// Howard Hinnant's days-since-1970 to proleptic Gregorian date conversion
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn puzzle(date: &str) -> ArchivedPuzzle {
+        ArchivedPuzzle {
+            date: date.to_string(),
+            sides: vec!["yfa".to_string(), "otk".to_string(), "lgw".to_string(), "rni".to_string()],
+            solutions: vec!["forklift-twangy".to_string()],
+            answer: vec![],
+        }
+    }
+
+    fn answered(date: &str, answer: &[&str]) -> ArchivedPuzzle {
+        ArchivedPuzzle {
+            answer: answer.iter().map(|w| w.to_string()).collect(),
+            ..puzzle(date)
+        }
+    }
+
+    #[test]
+    fn test_civil_from_days() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+        assert_eq!(civil_from_days(20_377), (2025, 10, 16));
+    }
+
+    #[test]
+    fn test_archive_round_trip() {
+        let path = std::env::temp_dir().join(format!("letter-bounced-archive-{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let mut archive = PuzzleArchive::open(&path).unwrap();
+        assert!(archive.puzzles().is_empty());
+        archive.add(puzzle("2025-02-01")).unwrap();
+        archive.add(puzzle("2025-01-31")).unwrap();
+        archive.add(puzzle("2025-02-01")).unwrap();
+        assert!(archive.add(puzzle("yesterday")).is_err());
+        archive.save().unwrap();
+
+        let reopened = PuzzleArchive::open(&path).unwrap();
+        let dates: Vec<&str> = reopened.puzzles().iter().map(|p| p.date.as_str()).collect();
+        assert_eq!(dates, vec!["2025-01-31", "2025-02-01"]);
+        assert_eq!(reopened.get("2025-01-31"), Some(&puzzle("2025-01-31")));
+        assert_eq!(reopened.get("2025-03-01"), None);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_open_malformed_archive() {
+        let path = std::env::temp_dir().join(format!("letter-bounced-bad-archive-{}.json", std::process::id()));
+        fs::write(&path, "{ not json").unwrap();
+
+        let message = PuzzleArchive::open(&path).unwrap_err().to_string();
+        assert!(message.starts_with("Invalid puzzle archive "), "{}", message);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_play_stats() {
+        let puzzles = vec![
+            answered("2025-01-01", &["forklift", "twangy"]),
+            // One word more than the solver needed; GAWKILY is the first to reach I, K, W and Y
+            answered("2025-01-02", &["frontal", "nag", "gawkily"]),
+            puzzle("2025-01-03"),
+        ];
+        let stats = play_stats(&puzzles);
+
+        assert_eq!(stats.puzzles_played, 2);
+        assert_eq!(stats.average_answer_words, 2.5);
+        assert_eq!(stats.average_solver_words, 2.0);
+        assert_eq!(stats.most_used_words[0], ("forklift".to_string(), 1));
+        assert_eq!(stats.struggle_letters, vec![('i', 1), ('k', 1), ('w', 1), ('y', 1)]);
+    }
+}
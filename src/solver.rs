@@ -1,10 +1,17 @@
-use crate::board::Board;
-use crate::dictionary::{Dictionary, Word};
-use std::collections::HashMap;
+use crate::board::{Board, BoardOptions, GameRules};
+use crate::dictionary::{BkTree, Dictionary, Word, WordArena};
+use crate::error::{LetterBouncedError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fmt;
+use std::ops::Range;
 use std::cmp::min;
-use std::sync::Arc;
+use std::future::Future;
+use std::sync::{Arc, OnceLock};
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use tracing::{debug_span, field};
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Solution {
@@ -13,76 +20,128 @@ pub struct Solution {
 }
 
 impl Solution {
+    /// A solution of `words`, in order. An empty one scores 0.
     pub fn new(words: Vec<Word>) -> Self {
         let min_frequency: usize = words.iter().fold(256usize, |acc, w| min(acc, w.frequency as usize));
-        let score: usize = (min_frequency * 10) / words.len();
+        let score: usize = (min_frequency * 10).checked_div(words.len()).unwrap_or(0);
         Solution { words, score }
     }
 
-    /// Returns all redactable subsequences of this solution as vectors of indices.
-    /// A subsequence is redactable if:
-    /// 1. It includes the head of the solution (first word can be removed), OR
-    /// 2. It doesn't include the head, but forms a valid word chain (first and last letters match across the gap)
+    /// The redactions worth checking when deciding whether this solution is redundant, as bitmasks of the word
+    /// indices kept (bit i set means word i stays). A redaction is a shorter subsequence which is still playable:
+    /// 1. It drops the head: any words from index k onward, for k >= 1. Only the whole suffix from each k is
+    ///    listed, since it covers at least as much as any subset of it.
+    /// 2. It keeps the head and cuts out one run of words, where the words either side of the gap still chain
+    ///    (or the run reaches the end). Any chain which keeps the head lies inside one of these, because cutting
+    ///    out just one of its gaps is also a valid chain.
     ///
-    /// All redactions must be shorter than the original solution.
-    pub fn redactable_subsequences(&self) -> Vec<Vec<usize>> {
+    /// Either way there are O(n^2) redactions, rather than the 2^n subsequences they stand for.
+    pub fn redactable_subsequences(&self) -> Vec<u32> {
         let n = self.words.len();
         if n <= 1 {
             return vec![];
         }
+        let all: u32 = (1 << n) - 1;
+
+        // Suffixes which drop the head
+        let mut redactions: Vec<u32> = (1..n).map(|k| all & !((1 << k) - 1)).collect();
+
+        // Cut out words i..=j, keeping the head
+        for i in 1..n {
+            let before = self.words[i - 1].last_letter;
+            for j in i..n {
+                let joins = j == n - 1 || self.words[j + 1].first_letter == before;
+                if joins {
+                    let run = ((1 << (j + 1)) - 1) & !((1 << i) - 1);
+                    redactions.push(all & !run);
+                }
+            }
+        }
 
-        let mut redactions = Vec::new();
+        redactions
+    }
 
-        // Generate all non-empty proper subsequences (shorter than original)
-        // Use bitmask to represent which words to include
-        let num_subsets = 1 << n;
-        for mask in 1..num_subsets {
-            // Skip if this includes all words (not a proper subsequence)
-            if mask == num_subsets - 1 {
-                continue;
-            }
+    /// Whether the two solutions are the same but for S plurals, like DOG-GROWL and DOG-GROWLS
+    pub fn is_plural_variant_of(&self, other: &Solution) -> bool {
+        self.words != other.words && self.singular_words() == other.singular_words()
+    }
 
-            let mut indices = Vec::new();
-            for i in 0..n {
-                if (mask & (1 << i)) != 0 {
-                    indices.push(i);
-                }
-            }
+    // The words with any final S taken off, which all of a solution's plural variants share
+    fn singular_words(&self) -> Vec<&str> {
+        self.words.iter().map(|w| w.word.strip_suffix('s').unwrap_or(&w.word)).collect()
+    }
 
-            // Check if this subsequence is redactable
-            if indices.is_empty() {
-                continue;
+    /// Total letters typed to enter the solution: the sum of its word lengths
+    pub fn letter_count(&self) -> usize {
+        self.words.iter().map(|w| w.length).sum()
+    }
+
+    /// How many times the solution lands on a letter it has already visited. The letter joining two words is
+    /// one visit, not two, so a perfect 12-letter board solution revisits nothing.
+    pub fn revisited_letters(&self) -> usize {
+        let mut seen = HashSet::new();
+        let mut revisits = 0;
+        for (word_num, word) in self.words.iter().enumerate() {
+            // Chained words start on the letter the previous word finished on
+            for ch in word.word.chars().skip(if word_num == 0 { 0 } else { 1 }) {
+                if !seen.insert(ch) {
+                    revisits += 1;
+                }
             }
+        }
+        revisits
+    }
 
-            // Rule 1: Includes the head (index 0 not set in mask means head is removed)
-            let includes_head = (mask & 1) == 0;
+    /// The solution's score on a 0-100 scale, and how it's made up; see `ScoreBreakdown`
+    pub fn score_breakdown(&self) -> ScoreBreakdown {
+        let rarest = self.words.iter().map(|w| w.frequency).min().unwrap_or(0);
+        let frequency = (rarest.clamp(0, TOP_FREQUENCY) as u8) * (FREQUENCY_POINTS / TOP_FREQUENCY as u8);
+
+        // Each word after the first starts on the letter the one before ended on, which isn't a new visit
+        let visits = self.letter_count().saturating_sub(self.words.len().saturating_sub(1));
+        let fresh = visits - self.revisited_letters().min(visits);
+        let letter_efficiency = (fresh * EFFICIENCY_POINTS as usize).checked_div(visits).unwrap_or(0) as u8;
+
+        let length_penalty = (self.words.len().saturating_sub(1) * PENALTY_PER_WORD as usize).min(100) as u8;
+        ScoreBreakdown {
+            total: (frequency + letter_efficiency).saturating_sub(length_penalty),
+            frequency,
+            letter_efficiency,
+            length_penalty,
+        }
+    }
 
-            // Rule 2: Check if it forms a valid chain (if head is included)
-            let forms_valid_chain = if !includes_head {
-                // Check that consecutive words in the subsequence form valid chains
-                let mut valid = true;
-                for i in 0..indices.len() - 1 {
-                    let last_char = self.words[indices[i]].word.chars().last();
-                    let first_char = self.words[indices[i + 1]].word.chars().next();
-                    if last_char != first_char {
-                        valid = false;
-                        break;
-                    }
-                }
-                valid
-            } else {
-                true
-            };
+    /// How many frequency points separate the solution's most common word from its rarest
+    pub fn rarity_spread(&self) -> u8 {
+        let frequencies = self.words.iter().map(|w| w.frequency);
+        match (frequencies.clone().max(), frequencies.min()) {
+            (Some(max), Some(min)) => max.abs_diff(min),
+            _ => 0,
+        }
+    }
 
-            if includes_head || forms_valid_chain {
-                redactions.push(indices);
+    /// Where each letter of the solution sits on the board, as (side, position along that side), in the order
+    /// they're played. The letter shared by consecutive words appears once, so the result traces one
+    /// continuous path. If a letter is on the board more than once, the first copy which lets the rest of the
+    /// solution be played is used. A doubled letter played twice in place, as the `DoubledLetters` rules allow,
+    /// appears twice. Returns None if the solution can't be played on this board.
+    pub fn letter_path(&self, board: &Board) -> Option<Vec<(usize, usize)>> {
+        let mut letters = String::new();
+        for (word_num, word) in self.words.iter().enumerate() {
+            if word_num == 0 {
+                letters.push_str(&word.word);
+                continue;
             }
+            // Chained words start where the previous one ended
+            let rest = word.word.strip_prefix(self.words[word_num - 1].last_letter)?;
+            letters.push_str(rest);
         }
-
-        redactions
+        board.letter_path(&letters)
     }
 }
 
+/// Words joined with hyphens, e.g. "forklift-twangy". The alternate form, `{:#}`, adds the letter count and
+/// revisited letters: "forklift-twangy (14 letters, 1 revisited)".
 impl fmt::Display for Solution {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let s = self.words
@@ -90,92 +149,1079 @@ impl fmt::Display for Solution {
             .map(|w| w.word.as_str())
             .collect::<Vec<&str>>()
             .join("-");
-        write!(f, "{}", s)
+        if f.alternate() {
+            write!(f, "{} ({} letters, {} revisited)", s, self.letter_count(), self.revisited_letters())
+        } else {
+            write!(f, "{}", s)
+        }
     }
 }
 
-struct WordBitmap {
-    word: Word,
+/**
+ * A solution's score on a scale from 0 to 100, for showing people. `Solution::score` still does the ranking, but
+ * its numbers only mean anything next to each other; this one is made of parts which can be explained:
+ *
+ * - up to 60 points for how common the rarest word is: two per frequency point, up to a frequency of 30, which
+ *   only the commonest words in the dictionary reach,
+ * - up to 40 points for letter efficiency: the share of the letters typed which land on a letter for the first
+ *   time, so a solution which revisits nothing gets all 40,
+ * - less 10 points for each word after the first.
+ *
+ * A one-word pangram of everyday letters, with no letter typed twice, scores 100. FORKLIFT-TWANGY scores 54.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScoreBreakdown {
+    pub total: u8,
+    pub frequency: u8,
+    pub letter_efficiency: u8,
+    pub length_penalty: u8,
+}
+
+impl fmt::Display for ScoreBreakdown {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/100 = frequency {} + letter efficiency {} - length {}",
+            self.total, self.frequency, self.letter_efficiency, self.length_penalty
+        )
+    }
+}
+
+// The frequency at which a solution's rarest word earns all of its FREQUENCY_POINTS
+const TOP_FREQUENCY: i8 = 30;
+const FREQUENCY_POINTS: u8 = 60;
+const EFFICIENCY_POINTS: u8 = 40;
+const PENALTY_PER_WORD: u8 = 10;
+
+// Most "did you mean" words offered for an unknown word in an answer
+pub const MAX_SUGGESTIONS: usize = 5;
+
+// The longest solutions we search for unless told otherwise
+const MAX_SOLUTION_WORDS: usize = 4;
+
+// How many first words `solve_async`, and the wasm streaming solves, search from between yields, and how many
+// positions they visit at most before yielding early
+pub const FIRST_WORDS_PER_YIELD: usize = 200;
+pub const NODES_PER_YIELD: usize = 20_000;
+
+// How long a chain `explain_no_solutions` looks for before giving up on the board
+const MAX_EXPLAINED_WORDS: usize = 6;
+
+// Give each board letter a bit, in the order they first appear. On variant boards a letter may appear more than
+// once, but visiting any copy of it counts as covering it, so repeated letters share a single bit.
+fn letter_bits(board: &Board) -> HashMap<char, u32> {
+    let mut letter_to_bit = HashMap::new();
+    let mut bit_index = 0;
+    for side in &board.sides {
+        for ch in side.chars() {
+            if let Entry::Vacant(e) = letter_to_bit.entry(ch) {
+                e.insert(1 << bit_index);
+                bit_index += 1;
+            }
+        }
+    }
+    letter_to_bit
+}
+
+fn letter_index(ch: char) -> Option<usize> {
+    ch.is_ascii_lowercase().then(|| (ch as u8 - b'a') as usize)
+}
+
+// A span around the search for solutions of exactly `target_words` words. With a subscriber which reports span
+// timings, this shows which word count the time went on.
+fn search_pass_span(target_words: usize) -> tracing::span::EnteredSpan {
+    debug_span!("search_pass", target_words, solutions = field::Empty, nodes = field::Empty, stopped = field::Empty)
+        .entered()
+}
+
+/// How to order the solutions a search finds
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ScoringStrategy {
+    /// Highest `Solution::score` first: common words, and few of them
+    #[default]
+    Score,
+    /// Fewest words first, then by score
+    FewestWords,
+    /// Fewest letters typed first, then by score, for players counting keystrokes
+    FewestLetters,
+    /// Fewest revisited letters first, then by score: solutions which waste the fewest moves
+    Elegance,
+}
+
+impl std::str::FromStr for ScoringStrategy {
+    type Err = LetterBouncedError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "score" => Ok(ScoringStrategy::Score),
+            "fewest-words" => Ok(ScoringStrategy::FewestWords),
+            "fewest-letters" => Ok(ScoringStrategy::FewestLetters),
+            "elegance" => Ok(ScoringStrategy::Elegance),
+            _ => Err(LetterBouncedError::SolverConfig(format!(
+                "Unknown scoring strategy '{}'. Choose score, fewest-words, fewest-letters or elegance",
+                s
+            ))),
+        }
+    }
+}
+
+impl ScoringStrategy {
+    /// Put solutions in this strategy's order, best first
+    pub fn sort(&self, solutions: &mut [Solution]) {
+        solutions.sort_by(|a, b| self.compare(a, b));
+    }
+
+    /// How two solutions rank under this strategy: `Less` if `a` is better
+    pub fn compare(&self, a: &Solution, b: &Solution) -> std::cmp::Ordering {
+        let by_score = b.score.cmp(&a.score);
+        match self {
+            ScoringStrategy::Score => by_score,
+            ScoringStrategy::FewestWords => a.words.len().cmp(&b.words.len()).then(by_score),
+            ScoringStrategy::FewestLetters => a.letter_count().cmp(&b.letter_count()).then(by_score),
+            ScoringStrategy::Elegance => a.revisited_letters().cmp(&b.revisited_letters()).then(by_score),
+        }
+    }
+}
+
+/// Knobs for a solve. Build with `SolverConfig::new()` and the chained setters.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SolverConfig {
+    pub max_solutions: u16,
+    /// Stop searching after this long and return what we have. Not supported on wasm, which has no clock.
+    pub timeout: Option<Duration>,
+    /// Words which must never appear in a solution, e.g. ones the NYT rejects
+    pub excluded_words: HashSet<String>,
+    /// Only report solutions which use all of these words
+    pub required_words: HashSet<String>,
+    /// Only use words which have all of these tags, like `common`
+    pub required_tags: HashSet<String>,
+    /// Never use words with any of these tags, like `scrabble-only`
+    pub excluded_tags: HashSet<String>,
+    /// The first word must start with this letter
+    pub start_letter: Option<char>,
+    /// The longest solutions to look for
+    pub max_words: usize,
+    pub scoring: ScoringStrategy,
+    /// Drop solutions which still cover the board with some words taken out, e.g. FOXGLOVE-EYE-EQUITY
+    pub filter_redundant: bool,
+    /// Drop solutions whose rarest word is more than this many frequency points below their most common one,
+    /// like an everyday word chained to an obscure Scrabble word
+    pub max_rarity_spread: Option<u8>,
+    /// Keep at most this many solutions starting with any one word, so the list shows a variety of openings
+    pub max_per_first_word: Option<u16>,
+    /// Of solutions which are the same but for S plurals, keep only the one the scoring strategy ranks best
+    pub collapse_plurals: bool,
+    /// Search every word count up to `max_words`, keeping at most this many solutions of each, before ranking
+    /// them together and keeping the best `max_solutions`. Without it, the search stops at the word count where
+    /// `max_solutions` is reached, so plentiful short solutions can leave no room for longer ones.
+    pub max_per_length: Option<u16>,
+}
+
+impl Default for SolverConfig {
+    fn default() -> Self {
+        SolverConfig {
+            max_solutions: 500,
+            timeout: None,
+            excluded_words: HashSet::new(),
+            required_words: HashSet::new(),
+            required_tags: HashSet::new(),
+            excluded_tags: HashSet::new(),
+            start_letter: None,
+            max_words: MAX_SOLUTION_WORDS,
+            scoring: ScoringStrategy::default(),
+            filter_redundant: true,
+            max_rarity_spread: None,
+            max_per_first_word: None,
+            collapse_plurals: false,
+            max_per_length: None,
+        }
+    }
+}
+
+impl SolverConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn max_solutions(mut self, max_solutions: u16) -> Self {
+        self.max_solutions = max_solutions;
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn excluded_words<I, S>(mut self, words: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.excluded_words
+            .extend(words.into_iter().map(|w| w.as_ref().to_lowercase()));
+        self
+    }
+
+    pub fn required_words<I, S>(mut self, words: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.required_words
+            .extend(words.into_iter().map(|w| w.as_ref().to_lowercase()));
+        self
+    }
+
+    pub fn required_tags<I, S>(mut self, tags: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.required_tags
+            .extend(tags.into_iter().map(|t| t.as_ref().to_lowercase()));
+        self
+    }
+
+    pub fn excluded_tags<I, S>(mut self, tags: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.excluded_tags
+            .extend(tags.into_iter().map(|t| t.as_ref().to_lowercase()));
+        self
+    }
+
+    pub fn start_letter(mut self, letter: char) -> Self {
+        self.start_letter = Some(letter.to_ascii_lowercase());
+        self
+    }
+
+    pub fn max_words(mut self, max_words: usize) -> Self {
+        self.max_words = max_words;
+        self
+    }
+
+    pub fn scoring(mut self, scoring: ScoringStrategy) -> Self {
+        self.scoring = scoring;
+        self
+    }
+
+    pub fn filter_redundant(mut self, filter_redundant: bool) -> Self {
+        self.filter_redundant = filter_redundant;
+        self
+    }
+
+    pub fn max_rarity_spread(mut self, spread: u8) -> Self {
+        self.max_rarity_spread = Some(spread);
+        self
+    }
+
+    pub fn max_per_first_word(mut self, max: u16) -> Self {
+        self.max_per_first_word = Some(max);
+        self
+    }
+
+    pub fn collapse_plurals(mut self, collapse: bool) -> Self {
+        self.collapse_plurals = collapse;
+        self
+    }
+
+    pub fn max_per_length(mut self, max: u16) -> Self {
+        self.max_per_length = Some(max);
+        self
+    }
+
+    /// Check for settings which rule out every solution before starting a search
+    pub fn validate(&self) -> Result<()> {
+        if let Some(letter) = self.start_letter {
+            if letter_index(letter).is_none() {
+                return Err(LetterBouncedError::SolverConfig(format!(
+                    "Start letter '{}' must be a letter A-Z",
+                    letter
+                )));
+            }
+        }
+        if let Some(word) = self.required_words.iter().find(|w| self.excluded_words.contains(*w)) {
+            return Err(LetterBouncedError::SolverConfig(format!(
+                "'{}' is both required and excluded",
+                word
+            )));
+        }
+        if let Some(tag) = self.required_tags.iter().find(|t| self.excluded_tags.contains(*t)) {
+            return Err(LetterBouncedError::SolverConfig(format!(
+                "Tag '{}' is both required and excluded",
+                tag
+            )));
+        }
+        if self.max_words == 0 {
+            return Err(LetterBouncedError::SolverConfig("Solutions need at least one word".to_string()));
+        }
+        if self.max_per_first_word == Some(0) {
+            return Err(LetterBouncedError::SolverConfig(
+                "At least one solution per first word must be allowed".to_string(),
+            ));
+        }
+        if self.max_per_length == Some(0) {
+            return Err(LetterBouncedError::SolverConfig(
+                "At least one solution per word count must be allowed".to_string(),
+            ));
+        }
+        if self.required_words.len() > self.max_words {
+            return Err(LetterBouncedError::SolverConfig(format!(
+                "At most {} words can be required, since no solution is longer",
+                self.max_words
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// The `SolverPack` format this build writes, and the only one it reads
+pub const SOLVER_PACK_VERSION: u32 = 1;
+
+/**
+ * A solver's prepared data for one board, from `Solver::pack`, so the work of finding the board's playable words
+ * in a whole dictionary can be done once, ahead of time. A pack serializes (e.g. as JSON) to tens of kilobytes, and
+ * `Solver::from_pack` turns it back into a solver without a dictionary, which suits shipping the daily puzzle to
+ * clients which would rather not download and filter the dictionary themselves.
+ *
+ * The word filters of the config a pack was made with (excluded words and tags) are baked into it. The search
+ * settings, like `max_solutions`, come from the config it's loaded with.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SolverPack {
+    pub version: u32,
+    pub sides: Vec<String>,
+    pub rules: GameRules,
+    /// The board's distinct letters, in the order of their bits in `bitmaps`
+    pub letters: Vec<char>,
+    /// The playable words, most frequent first
+    pub words: Vec<PackedWord>,
+    /// For each word, the board letters it covers
+    pub bitmaps: Vec<u32>,
+    /// For each letter from a to z, the indices in `words` of the words starting with it
+    pub first_letter_index: Vec<Vec<u32>>,
+    /// How many dictionary words weren't playable, or were filtered out
+    pub words_filtered: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PackedWord {
+    pub word: String,
+    pub frequency: i8,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub layer: u8,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+fn is_zero(layer: &u8) -> bool {
+    *layer == 0
+}
+
+/// Solutions plus whether the search stopped early
+#[derive(Debug, Clone, PartialEq)]
+pub struct SolveOutcome {
+    pub solutions: Vec<Solution>,
+    /// True if the timeout expired before the search finished, so better solutions may exist
+    pub timed_out: bool,
+}
+
+/// Why a finished solve found nothing; see `Solver::explain_no_solutions`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NoSolutionReason {
+    /// max_solutions was 0
+    NoSolutionsRequested,
+    /// A required word isn't playable on the board, or is excluded
+    RequiredWordUnplayable(String),
+    /// These letters are in none of the words the solver may use
+    UncoverableLetters(Vec<char>),
+    /// No playable word starts with the start letter
+    NoWordStartsWith(char),
+    /// Chains covering the board exist, but the shortest has `needed` words
+    MaxWordsTooLow { max_words: usize, needed: usize },
+    /// No chain of up to `max_words` words covers the board, and we didn't look further
+    NoCoveringChain { max_words: usize },
+    /// Chains covering the board fit in max_words, but the required words or rarity spread rule them all out
+    FilteredOut,
+}
+
+impl fmt::Display for NoSolutionReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NoSolutionReason::NoSolutionsRequested => write!(f, "Asked for at most 0 solutions"),
+            NoSolutionReason::RequiredWordUnplayable(word) => {
+                write!(f, "The required word '{}' can't be played on this board", word)
+            }
+            NoSolutionReason::UncoverableLetters(letters) => {
+                let letters: String = letters.iter().map(|c| c.to_ascii_uppercase()).collect();
+                write!(f, "No playable word uses {}", letters)
+            }
+            NoSolutionReason::NoWordStartsWith(letter) => {
+                write!(f, "No playable word starts with {}", letter.to_ascii_uppercase())
+            }
+            NoSolutionReason::MaxWordsTooLow { max_words, needed } => write!(
+                f,
+                "Solutions need at least {} words, but at most {} were allowed",
+                needed, max_words
+            ),
+            NoSolutionReason::NoCoveringChain { max_words } => {
+                write!(f, "No chain of up to {} words covers the board", max_words)
+            }
+            NoSolutionReason::FilteredOut => write!(
+                f,
+                "Chains covering the board exist, but the required words or rarity spread rule them all out"
+            ),
+        }
+    }
+}
+
+/// Counters describing how much work a solve did. They don't depend on the machine, so they're the way to
+/// tell whether a pruning change actually helps.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SolveStats {
+    /// Dictionary words dropped before searching, as unplayable on this board or excluded
+    pub words_filtered: usize,
+    /// Search positions visited
+    pub nodes_expanded: usize,
+    /// Positions skipped because the transposition memo already knew they lead nowhere
+    pub transposition_hits: usize,
+    /// Chains which covered the board but were thrown away as redundant
+    pub redundancy_rejections: usize,
+    /// The same work broken down by how far into the chain it happened: `per_depth[0]` is the first word,
+    /// `per_depth[1]` the second, and so on
+    pub per_depth: Vec<DepthStats>,
+}
+
+/// Counters for one position in the chain, summed over every word count searched
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DepthStats {
+    /// Words tried in this position
+    pub candidates: usize,
+    /// Candidates skipped because they cover no letter the chain hadn't already
+    pub no_new_letters: usize,
+    /// Chains ending with a word in this position which covered the board but were thrown away as redundant
+    pub redundancy_rejections: usize,
+}
+
+impl SolveStats {
+    // The counters for the word at `depth` (0 for the first), growing the list to reach it
+    fn depth(&mut self, depth: usize) -> &mut DepthStats {
+        if self.per_depth.len() <= depth {
+            self.per_depth.resize(depth + 1, DepthStats::default());
+        }
+        &mut self.per_depth[depth]
+    }
+}
+
+/// A solve which runs a slice at a time, for callers like the web worker which must hand control back
+/// regularly. Each `next_batch` call searches onward from a few more first words, or as many as fit in a budget of
+/// search positions, and returns the solutions that turned up, in search order rather than sorted. Stop calling it
+/// to cancel.
+pub struct IncrementalSolve<'a> {
+    solver: &'a Solver,
+    state: SearchState<'static>,
+    // Everything found so far, so max_solutions and the search's early exits still apply
+    solutions: Vec<Solution>,
+    target_words: usize,
+    // Index into the first-word candidates where the next batch starts
+    next_first_word: usize,
+    finished: bool,
+    timed_out: bool,
+}
+
+impl IncrementalSolve<'_> {
+    /// Search from the next `first_words` first words, stopping sooner once the batch has visited `max_nodes`
+    /// search positions. The budget is checked after each first word, so a batch always finishes the search under
+    /// at least one. Returns None once the search is over; a batch may be empty if its first words led nowhere.
+    pub fn next_batch(&mut self, first_words: usize, max_nodes: usize) -> Option<Vec<Solution>> {
+        if self.finished {
+            return None;
+        }
+        let solver = self.solver;
+        let found_before = self.solutions.len();
+        let nodes_before = self.state.stats.nodes_expanded;
+        let root = self.state.root;
+        let candidates = solver.candidates_starting_with(root.first_letter);
+        let end = min(self.next_first_word.saturating_add(first_words.max(1)), candidates.len());
+
+        while self.next_first_word < end {
+            let candidate = &candidates[self.next_first_word];
+            self.next_first_word += 1;
+            let mut current_path = vec![candidate.word_index];
+            let completed = solver.search_recursive(
+                &mut current_path,
+                root.covered_bitmap | candidate.bitmap,
+                candidate.last_char,
+                &mut self.solutions,
+                self.target_words,
+                &mut self.state,
+            );
+            if !completed {
+                self.finished = true;
+                self.timed_out = true;
+                break;
+            }
+            if self.state.stats.nodes_expanded - nodes_before >= max_nodes {
+                break;
+            }
+        }
+
+        // With a quota per word count, filling it ends this word count's search early but not the whole solve
+        let quota_filled = solver.max_per_length.is_some() && self.solutions.len() >= self.state.quota;
+        if self.next_first_word >= candidates.len() || quota_filled {
+            self.target_words += 1;
+            self.next_first_word = 0;
+            if let Some(quota) = solver.max_per_length {
+                self.state.quota = self.solutions.len() + quota;
+            }
+        }
+        let enough = solver.max_per_length.is_none() && self.solutions.len() >= solver.max_solutions;
+        if self.target_words > solver.max_words || enough {
+            self.finished = true;
+        }
+
+        Some(self.solutions[found_before..].to_vec())
+    }
+
+    /// True if the configured timeout ended the search
+    pub fn timed_out(&self) -> bool {
+        self.timed_out
+    }
+}
+
+/// Solutions grouped by how many words they use, each group holding up to `max_solutions` of its best
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SolutionSet {
+    // buckets[n - 1] holds the n-word solutions, best first
+    buckets: Vec<Vec<Solution>>,
+    timed_out: bool,
+}
+
+impl SolutionSet {
+    /// The best solutions using exactly `word_count` words
+    pub fn with_word_count(&self, word_count: usize) -> &[Solution] {
+        word_count
+            .checked_sub(1)
+            .and_then(|i| self.buckets.get(i))
+            .map_or(&[], |bucket| bucket.as_slice())
+    }
+
+    /// The largest word count searched; shorter buckets may still be empty
+    pub fn max_word_count(&self) -> usize {
+        self.buckets.len()
+    }
+
+    /// All solutions, fewest words first, then by score
+    pub fn iter(&self) -> impl Iterator<Item = &Solution> {
+        self.buckets.iter().flatten()
+    }
+
+    pub fn len(&self) -> usize {
+        self.buckets.iter().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// True if the timeout expired before every bucket was searched
+    pub fn timed_out(&self) -> bool {
+        self.timed_out
+    }
+
+    pub fn into_solutions(self) -> Vec<Solution> {
+        self.buckets.into_iter().flatten().collect()
+    }
+}
+
+// Everything the search loop needs about a possible next word, without touching the Word itself
+#[derive(Debug, Clone, Copy)]
+struct Candidate {
+    word_index: usize,
     bitmap: u32,
+    last_char: Option<char>,
 }
 
 pub struct Solver {
-    word_bitmaps: Vec<WordBitmap>,
-    words_by_first_letter: HashMap<char, Vec<usize>>,
+    board: Board,
+    words_filtered: usize,
+    letter_to_bit: HashMap<char, u32>,
+    // The playable words, most frequent first. Searches refer to words by their index here.
+    words: WordArena,
+    // bitmaps[i] has a bit set for each board letter in words[i]
+    bitmaps: Vec<u32>,
+    // Candidates for the first word: every playable word
+    all_candidates: Vec<Candidate>,
+    // Candidates for a following word, indexed by the letter it must start with (a = 0). Like all_candidates,
+    // these are most frequent first. The search reorders them by the letters still needed; see `next_candidates`.
+    candidates_by_first_letter: Vec<Vec<Candidate>>,
     all_letters_mask: u32,
     max_solutions: usize, // this is usize for convenience in comparisons to length(), but set from u16
+    timeout: Option<Duration>,
+    required_words: Vec<String>,
+    // Where each of required_words is in `words`, if it's playable at all
+    required_indices: Vec<Option<usize>>,
+    start_letter: Option<char>,
+    max_words: usize,
+    scoring: ScoringStrategy,
+    filter_redundant: bool,
+    max_rarity_spread: Option<u8>,
+    max_per_first_word: Option<usize>,
+    collapse_plurals: bool,
+    max_per_length: Option<usize>,
+    // Whether some letter is on more than one side, so that consecutive words, each playable, may need different
+    // copies of the letter they share
+    letters_on_several_sides: bool,
+    // The playable words indexed for suggestions, built the first time a word isn't found
+    fuzzy_index: OnceLock<BkTree>,
 }
 
-impl Solver {
-    pub fn new(board: Board, dictionary: &Dictionary, max_solutions: u16) -> Self {
-        // Create letter-to-bit mapping
-        let mut letter_to_bit = HashMap::new();
-        let mut bit_index = 0;
-        for side in &board.sides {
-            for ch in side.chars() {
-                letter_to_bit.insert(ch, 1 << bit_index);
-                bit_index += 1;
+// Where the search should stop, other than running out of words
+struct SearchLimits<'a> {
+    cancel_flag: Option<&'a Arc<AtomicBool>>,
+    deadline: Option<Instant>,
+}
+
+// A search position which is equivalent no matter which words got us there: letters covered, the letter the
+// next word must start with, how many words we may still add, and which required words we've used.
+type SearchPosition = (u32, Option<char>, usize, Vec<u64>);
+
+// Where a search begins: nothing covered for a fresh solve, or the words already played when resuming a game
+#[derive(Debug, Clone, Copy)]
+struct SearchRoot {
+    covered_bitmap: u32,
+    // The letter the first word we add must start with, if any
+    first_letter: Option<char>,
+}
+
+// The set-cover search behind `minimal_covers`, which ignores chaining altogether
+struct CoverSearch<'a> {
+    solver: &'a Solver,
+    // The words containing each board letter, by bit
+    words_with_letter: Vec<Vec<usize>>,
+    // The most board letters any one word covers
+    widest: u32,
+    // How many words the covers we're looking for have
+    size: usize,
+    chosen: Vec<usize>,
+    // Word indices of each cover found, in ascending order so each set is only kept once
+    covers: BTreeSet<Vec<usize>>,
+}
+
+impl CoverSearch<'_> {
+    fn find(&mut self, covered: u32) {
+        if self.covers.len() >= self.solver.max_solutions {
+            return;
+        }
+        if covered == self.solver.all_letters_mask {
+            let mut cover = self.chosen.clone();
+            cover.sort_unstable();
+            self.covers.insert(cover);
+            return;
+        }
+        let uncovered = self.solver.all_letters_mask & !covered;
+        let remaining = (self.size - self.chosen.len()) as u32;
+        if uncovered.count_ones() > remaining * self.widest {
+            return;
+        }
+
+        // Every cover has a word containing the letter fewest words contain, so only those need trying
+        let Some(bit) = (0..self.words_with_letter.len())
+            .filter(|&bit| uncovered & (1 << bit) != 0)
+            .min_by_key(|&bit| self.words_with_letter[bit].len())
+        else {
+            return;
+        };
+        for i in 0..self.words_with_letter[bit].len() {
+            let index = self.words_with_letter[bit][i];
+            self.chosen.push(index);
+            self.find(covered | self.solver.bitmaps[index]);
+            self.chosen.pop();
+        }
+    }
+}
+
+// Mutable bookkeeping shared across the whole recursive search
+struct SearchState<'a> {
+    limits: SearchLimits<'a>,
+    root: SearchRoot,
+    // How many solutions to collect before this search stops: max_solutions, or a per word count quota
+    quota: usize,
+    // Positions from which no chain of the remaining length covers the board
+    fruitless: HashSet<SearchPosition>,
+    // Every full-coverage chain reached, redundant or not
+    covers_found: usize,
+    // For collapse_plurals: each kept solution's singular words, and where it is in the solutions being collected.
+    // A search may start collecting into a new list, so an entry only counts if it still points at a match.
+    plural_variants: HashMap<Vec<String>, usize>,
+    // Candidates for a following word by the letter it must start with and the board letters still needed, most
+    // new letters first. Built the first time the search reaches each pair; see `Solver::next_candidates`. The
+    // lists are kept end to end in `candidate_lists`, and the map has where each one is.
+    next_candidates: HashMap<(char, u32), Range<usize>>,
+    candidate_lists: Vec<Candidate>,
+    stats: SolveStats,
+}
+
+impl<'a> SearchState<'a> {
+    fn new(limits: SearchLimits<'a>, root: SearchRoot, quota: usize) -> Self {
+        SearchState {
+            limits,
+            root,
+            quota,
+            fruitless: HashSet::new(),
+            covers_found: 0,
+            plural_variants: HashMap::new(),
+            next_candidates: HashMap::new(),
+            candidate_lists: Vec::new(),
+            stats: SolveStats::default(),
+        }
+    }
+}
+
+impl SearchLimits<'_> {
+    fn should_stop(&self) -> bool {
+        if let Some(flag) = self.cancel_flag {
+            if flag.load(Ordering::Relaxed) {
+                return true;
             }
         }
+        if let Some(deadline) = self.deadline {
+            if Instant::now() >= deadline {
+                return true;
+            }
+        }
+        false
+    }
+}
 
-        // Calculate mask for all letters, e.g. for 8 letters, this is 0b11111111
-        let all_letters_mask = 2u32.pow(bit_index) - 1;
+impl Solver {
+    pub fn new(board: Board, dictionary: &Dictionary, max_solutions: u16) -> Self {
+        Self::with_config(board, dictionary, SolverConfig::new().max_solutions(max_solutions))
+    }
 
-        // Create word bitmaps for all words playable
+    pub fn with_config(board: Board, dictionary: &Dictionary, config: SolverConfig) -> Self {
+        let letter_to_bit = letter_bits(&board);
+
+        // Best-first: try common words before rare ones, so that the first max_solutions found (and whatever we
+        // have when a search is cut short) are the most human-friendly. The stable sort keeps dictionary order
+        // among equally common words.
         let board_dictionary = board.playable_dictionary(dictionary);
-        let word_bitmaps: Vec<WordBitmap> = board_dictionary
-            .words
+        let mut playable: Vec<&Word> = board_dictionary
+            .words()
             .iter()
-            .map(|word| {
-                let bitmap = word.word.chars().fold(0, |acc, ch| {
-                    acc | letter_to_bit.get(&ch).copied().unwrap_or(0)
-                });
-                WordBitmap {
-                    word: word.clone(),
-                    bitmap,
-                }
+            .filter(|word| !config.excluded_words.contains(&word.word))
+            .filter(|word| config.required_tags.iter().all(|tag| word.has_tag(tag)))
+            .filter(|word| !config.excluded_tags.iter().any(|tag| word.has_tag(tag)))
+            .collect();
+        playable.sort_by_key(|word| std::cmp::Reverse(word.frequency));
+
+        let mut words = WordArena::default();
+        let mut bitmaps = Vec::with_capacity(playable.len());
+        for word in playable {
+            words.push_word(word);
+            bitmaps.push(word.word.chars().fold(0, |acc, ch| {
+                acc | letter_to_bit.get(&ch).copied().unwrap_or(0)
+            }));
+        }
+
+        // Index the words by first letter: for each letter, the words which can follow a word ending in it
+        let mut first_letter_index = vec![Vec::new(); 26];
+        for word_index in 0..words.len() {
+            if let Some(index) = words.word(word_index).chars().next().and_then(letter_index) {
+                first_letter_index[index].push(word_index);
+            }
+        }
+
+        let words_filtered = dictionary.words().len() - words.len();
+        Self::from_prepared(board, letter_to_bit, words, bitmaps, first_letter_index, words_filtered, config)
+    }
+
+    /// The solver's prepared data, to save and load with `from_pack` instead of building the solver again
+    pub fn pack(&self) -> SolverPack {
+        let mut letters: Vec<(char, u32)> = self.letter_to_bit.iter().map(|(&letter, &bit)| (letter, bit)).collect();
+        letters.sort_unstable_by_key(|&(_, bit)| bit);
+        let words = (0..self.words.len())
+            .map(|index| PackedWord {
+                word: self.words.word(index).to_string(),
+                frequency: self.words.frequency(index),
+                layer: self.words.layer(index),
+                tags: self.words.tags(index).to_vec(),
             })
             .collect();
+        let first_letter_index = self
+            .candidates_by_first_letter
+            .iter()
+            .map(|candidates| candidates.iter().map(|c| c.word_index as u32).collect())
+            .collect();
+
+        SolverPack {
+            version: SOLVER_PACK_VERSION,
+            sides: self.board.sides.clone(),
+            rules: self.board.rules,
+            letters: letters.into_iter().map(|(letter, _)| letter).collect(),
+            words,
+            bitmaps: self.bitmaps.clone(),
+            first_letter_index,
+            words_filtered: self.words_filtered,
+        }
+    }
+
+    /**
+     * Rebuild a solver from a pack, with `config`'s search settings. The pack is checked enough that a damaged or
+     * hand-edited one fails here rather than making the search panic, but not so much that loading one costs what
+     * building the solver would. Fails too if `config` filters words, since those filters were fixed when the pack
+     * was made.
+     */
+    pub fn from_pack(pack: &SolverPack, config: SolverConfig) -> Result<Self> {
+        let invalid = |message: String| Err(LetterBouncedError::InvalidPack(message));
+        if pack.version != SOLVER_PACK_VERSION {
+            return invalid(format!("version {} isn't supported, only {}", pack.version, SOLVER_PACK_VERSION));
+        }
+        if !config.excluded_words.is_empty() || !config.required_tags.is_empty() || !config.excluded_tags.is_empty() {
+            return Err(LetterBouncedError::SolverConfig(
+                "A solver pack's words were filtered when it was made, so excluded words and tags can't be changed"
+                    .to_string(),
+            ));
+        }
 
-        // Index words by first letter
-        let mut words_by_first_letter: HashMap<char, Vec<usize>> = HashMap::new();
-        for (i, word_bitmap) in word_bitmaps.iter().enumerate() {
-            if let Some(first_char) = word_bitmap.word.word.chars().next() {
-                words_by_first_letter.entry(first_char).or_default().push(i);
+        let board = Board::from_sides_with_options(pack.sides.clone(), BoardOptions::new(pack.rules))?;
+        let letter_to_bit = letter_bits(&board);
+        let in_bit_order = pack.letters.iter().enumerate().all(|(i, c)| letter_to_bit.get(c) == Some(&(1 << i)));
+        if pack.letters.len() != letter_to_bit.len() || !in_bit_order {
+            return invalid("its letters don't match its board".to_string());
+        }
+        if pack.bitmaps.len() != pack.words.len() || pack.first_letter_index.len() != 26 {
+            return invalid("its word bitmaps or first letter index are the wrong size".to_string());
+        }
+        let all_letters_mask = 2u32.pow(letter_to_bit.len() as u32) - 1;
+        if pack.bitmaps.iter().any(|bitmap| bitmap & !all_letters_mask != 0) {
+            return invalid("a word bitmap has letters which aren't on the board".to_string());
+        }
+
+        let mut words = WordArena::default();
+        for packed in &pack.words {
+            if packed.word.is_empty() {
+                return invalid("it has an empty word".to_string());
+            }
+            let index = words.push(&packed.word, packed.frequency);
+            if packed.layer != 0 {
+                words.set_layer(index, packed.layer);
+            }
+            words.set_tags(index, packed.tags.clone());
+        }
+        let mut first_letter_index = Vec::with_capacity(26);
+        for (letter, indices) in ('a'..='z').zip(&pack.first_letter_index) {
+            let indices: Vec<usize> = indices.iter().map(|&i| i as usize).collect();
+            if indices.iter().any(|&i| i >= words.len() || !words.word(i).starts_with(letter)) {
+                return invalid(format!("its index of words starting with {} is wrong", letter));
             }
+            first_letter_index.push(indices);
         }
 
+        let bitmaps = pack.bitmaps.clone();
+        Ok(Self::from_prepared(board, letter_to_bit, words, bitmaps, first_letter_index, pack.words_filtered, config))
+    }
+
+    // The rest of building a solver, once the playable words are known, shared by `with_config` and `from_pack`
+    fn from_prepared(
+        board: Board,
+        letter_to_bit: HashMap<char, u32>,
+        words: WordArena,
+        bitmaps: Vec<u32>,
+        first_letter_index: Vec<Vec<usize>>,
+        words_filtered: usize,
+        config: SolverConfig,
+    ) -> Self {
+        let candidate = |word_index: usize| Candidate {
+            word_index,
+            bitmap: bitmaps[word_index],
+            last_char: words.word(word_index).chars().last(),
+        };
+        let all_candidates: Vec<Candidate> = (0..words.len()).map(candidate).collect();
+        let candidates_by_first_letter = first_letter_index
+            .iter()
+            .map(|indices| indices.iter().map(|&word_index| candidate(word_index)).collect())
+            .collect();
+        // Calculate mask for all letters, e.g. for 8 letters, this is 0b11111111
+        let all_letters_mask = 2u32.pow(letter_to_bit.len() as u32) - 1;
+
+        let required_words: Vec<String> = config.required_words.into_iter().collect();
+        let required_indices = required_words.iter().map(|word| words.position(word)).collect();
+
         Solver {
-            word_bitmaps,
-            words_by_first_letter,
+            letters_on_several_sides: board.has_letters_on_several_sides(),
+            board,
+            words_filtered,
+            letter_to_bit,
+            words,
+            bitmaps,
+            all_candidates,
+            candidates_by_first_letter,
             all_letters_mask,
-            max_solutions: max_solutions.into(),
+            max_solutions: config.max_solutions.into(),
+            timeout: config.timeout,
+            required_words,
+            required_indices,
+            start_letter: config.start_letter,
+            max_words: config.max_words,
+            scoring: config.scoring,
+            filter_redundant: config.filter_redundant,
+            max_rarity_spread: config.max_rarity_spread,
+            max_per_first_word: config.max_per_first_word.map(usize::from),
+            collapse_plurals: config.collapse_plurals,
+            max_per_length: config.max_per_length.map(usize::from),
+            fuzzy_index: OnceLock::new(),
+        }
+    }
+
+    fn fresh_root(&self) -> SearchRoot {
+        SearchRoot {
+            covered_bitmap: 0,
+            first_letter: self.start_letter,
         }
     }
 
     /// Check if a solution is redundant by examining its redactable subsequences.
     /// A solution is redundant if any of its redactions also covers all letters.
-    fn is_solution_redundant(&self, solution: &Solution) -> bool {
-        let redaction_indices = solution.redactable_subsequences();
-
-        for indices in redaction_indices {
-            // Compute the combined bitmap for this redaction by indexing into solution
-            let mut combined_bitmap = 0u32;
-            for &idx in &indices {
-                let word = &solution.words[idx];
-                // Find the bitmap for this word
-                if let Some(wb) = self.word_bitmaps.iter().find(|wb| wb.word == *word) {
-                    combined_bitmap |= wb.bitmap;
+    fn is_solution_redundant(&self, solution: &Solution, root: &SearchRoot) -> bool {
+        let bitmaps: Vec<u32> = solution
+            .words
+            .iter()
+            .map(|word| {
+                word.word.chars().fold(0, |acc, ch| {
+                    acc | self.letter_to_bit.get(&ch).copied().unwrap_or(0)
+                })
+            })
+            .collect();
+
+        solution.redactable_subsequences().into_iter().any(|kept| {
+            let first = kept.trailing_zeros() as usize;
+            // Dropping the head word doesn't give a shorter solution if the new head can't start the chain
+            if let Some(first_letter) = root.first_letter {
+                if solution.words[first].first_letter != first_letter {
+                    return false;
                 }
             }
 
-            // If this redaction covers all letters, the original solution is redundant
-            if combined_bitmap == self.all_letters_mask {
-                return true;
+            let combined_bitmap = bitmaps
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| kept & (1 << i) != 0)
+                .fold(root.covered_bitmap, |acc, (_, bitmap)| acc | bitmap);
+            combined_bitmap == self.all_letters_mask
+        })
+    }
+
+    /// All single words which cover every letter on the board, most frequent first.
+    pub fn find_pangram_words(&self) -> Vec<Word> {
+        let mut pangrams: Vec<Word> = (0..self.words.len())
+            .filter(|&index| self.bitmaps[index] == self.all_letters_mask)
+            .map(|index| self.words.to_word(index))
+            .collect();
+        pangrams.sort_by_key(|w| std::cmp::Reverse(w.frequency));
+        pangrams
+    }
+
+    /**
+     * The smallest sets of playable words which between them cover every letter on the board, whether or not they
+     * chain. When these are smaller than the shortest solution, the board is hard because its words don't link up
+     * ("chaining-hard") rather than because its letters are hard to cover ("coverage-hard"). Returns at most
+     * max_solutions sets, most frequent first, each with its most frequent word first. Empty if some letter is in no
+     * playable word.
+     */
+    pub fn minimal_covers(&self) -> Vec<Vec<Word>> {
+        let letter_count = self.all_letters_mask.count_ones() as usize;
+        let coverable = self.bitmaps.iter().fold(0, |acc, bitmap| acc | bitmap);
+        if coverable != self.all_letters_mask || self.max_solutions == 0 {
+            return Vec::new();
+        }
+
+        let mut search = CoverSearch {
+            solver: self,
+            words_with_letter: (0..letter_count)
+                .map(|bit| (0..self.words.len()).filter(|&i| self.bitmaps[i] & (1 << bit) != 0).collect())
+                .collect(),
+            widest: self.bitmaps.iter().map(|bitmap| bitmap.count_ones()).max().unwrap_or(0),
+            size: 0,
+            chosen: Vec::new(),
+            covers: BTreeSet::new(),
+        };
+        // Try each size in turn, so the first size with any covers is the smallest
+        for size in 1..=letter_count {
+            search.size = size;
+            search.find(0);
+            if !search.covers.is_empty() {
+                break;
             }
         }
 
-        false
+        let mut covers: Vec<Vec<Word>> = search
+            .covers
+            .into_iter()
+            .map(|indices| indices.into_iter().map(|index| self.words.to_word(index)).collect())
+            .collect();
+        covers.sort_by_key(|words| std::cmp::Reverse(words.iter().map(|w| i32::from(w.frequency)).sum::<i32>()));
+        covers
+    }
+
+    /// Bitset of which of `required_words` appear in the path, 64 to a block. There can be any number of required
+    /// words, and with none this is empty, so it costs nothing.
+    fn required_words_used(&self, path: &[usize]) -> Vec<u64> {
+        let mut used = vec![0u64; self.required_indices.len().div_ceil(64)];
+        for (i, required) in self.required_indices.iter().enumerate() {
+            if required.is_some_and(|index| path.contains(&index)) {
+                used[i / 64] |= 1 << (i % 64);
+            }
+        }
+        used
+    }
+
+    // Add `solution` unless it's a plural variant of one already found, in which case whichever ranks better
+    // under the scoring strategy takes the earlier one's place
+    fn keep_best_plural_variant(&self, solution: Solution, solutions: &mut Vec<Solution>, state: &mut SearchState) {
+        let key: Vec<String> = solution.singular_words().into_iter().map(String::from).collect();
+        let earlier = state.plural_variants.get(&key).copied().filter(|&index| {
+            solutions.get(index).is_some_and(|found| found.singular_words() == solution.singular_words())
+        });
+        match earlier {
+            Some(index) => {
+                if self.scoring.compare(&solution, &solutions[index]) == std::cmp::Ordering::Less {
+                    solutions[index] = solution;
+                }
+            }
+            None => {
+                state.plural_variants.insert(key, solutions.len());
+                solutions.push(solution);
+            }
+        }
+    }
+
+    // Rank solutions by the scoring strategy, keep only the best of any plural variants, and cut the list to
+    // max_solutions
+    fn rank(&self, solutions: &mut Vec<Solution>) {
+        self.scoring.sort(solutions);
+        if self.collapse_plurals {
+            let mut seen = HashSet::new();
+            solutions.retain(|solution| {
+                seen.insert(solution.singular_words().into_iter().map(String::from).collect::<Vec<_>>())
+            });
+        }
+        solutions.truncate(self.max_solutions);
+    }
+
+    // Whether `solutions` already has as many chains starting with word `first` as max_per_first_word allows
+    fn first_word_full(&self, first: usize, solutions: &[Solution]) -> bool {
+        self.max_per_first_word.is_some_and(|max| {
+            let first = self.words.word(first);
+            solutions.iter().filter(|solution| solution.words[0].word == first).count() >= max
+        })
+    }
+
+    fn solution_from_path(&self, path: &[usize]) -> Solution {
+        Solution::new(path.iter().map(|&index| self.words.to_word(index)).collect())
     }
 
     pub fn solve(&self) -> Vec<Solution> {
@@ -187,62 +1233,450 @@ impl Solver {
     /// The `cancel_flag` parameter allows external cancellation of the solve operation.
     /// When the flag is set to true, the solver will stop as soon as possible.
     pub fn solve_cancellable(&self, cancel_flag: Option<Arc<AtomicBool>>) -> Vec<Solution> {
+        self.solve_with_outcome(cancel_flag).solutions
+    }
+
+    /// Like `solve_cancellable`, but also reports whether the configured timeout cut the search short.
+    pub fn solve_with_outcome(&self, cancel_flag: Option<Arc<AtomicBool>>) -> SolveOutcome {
+        self.solve_with_stats(cancel_flag).0
+    }
+
+    /// Like `solve_with_outcome`, but also reports counters from inside the search.
+    pub fn solve_with_stats(&self, cancel_flag: Option<Arc<AtomicBool>>) -> (SolveOutcome, SolveStats) {
+        self.solve_from_root(self.fresh_root(), cancel_flag)
+    }
+
+    /// Finish a partially played game: given the words already played, find chains which complete it.
+    /// The returned solutions contain only the new words. Fails if the played words use letters which aren't
+    /// on the board, or don't chain together.
+    pub fn solve_from_prefix(&self, played: &[&str]) -> Result<Vec<Solution>> {
+        let mut root = self.fresh_root();
+        let (covered_bitmap, last_char) = self.cover_played(played)?;
+        root.covered_bitmap = covered_bitmap;
+        if last_char.is_some() {
+            root.first_letter = last_char;
+        }
+
+        if root.covered_bitmap == self.all_letters_mask {
+            return Ok(vec![]);
+        }
+        Ok(self.solve_from_root(root, None).0.solutions)
+    }
+
+    /// Check a player's answer: every word must be a playable dictionary word, the words must chain, and
+    /// together they must cover the board. A word the solver doesn't know comes back as
+    /// `LetterBouncedError::UnknownWord`, with up to `MAX_SUGGESTIONS` playable words within two edits of it.
+    pub fn validate_solution(&self, words: &[&str]) -> Result<Solution> {
+        if words.is_empty() {
+            return Err(LetterBouncedError::InvalidPlay("An answer needs at least one word".to_string()));
+        }
+        let missing = self.missing_letters(words)?;
+        if !missing.is_empty() {
+            return Err(LetterBouncedError::InvalidPlay(format!(
+                "The answer doesn't use {}",
+                missing.iter().collect::<String>()
+            )));
+        }
+        let played = words
+            .iter()
+            .filter_map(|word| self.words.position(&word.to_lowercase()))
+            .map(|index| self.words.to_word(index))
+            .collect();
+        Ok(Solution::new(played))
+    }
+
+    /// Check a game in progress, with the same rules as `validate_solution` except that the words needn't cover
+    /// the board yet. Returns the board letters they haven't used, in alphabetical order.
+    pub fn missing_letters(&self, words: &[&str]) -> Result<Vec<char>> {
+        // A typo is often a letter which isn't on the board, so suggestions come before the letter and chaining
+        // checks. An unknown word nothing is close to is only reported if the words are otherwise fine.
+        let mut unknown = None;
+        for word in words {
+            let word = word.to_lowercase();
+            if self.words.position(&word).is_none() {
+                let index = self.fuzzy_index.get_or_init(|| BkTree::new(self.words.iter().map(|(word, _)| word)));
+                let suggestions: Vec<String> = index
+                    .within(&word, 2)
+                    .into_iter()
+                    .take(MAX_SUGGESTIONS)
+                    .map(|(suggestion, _)| suggestion.to_string())
+                    .collect();
+                if !suggestions.is_empty() {
+                    return Err(LetterBouncedError::UnknownWord { word, suggestions });
+                }
+                unknown.get_or_insert(word);
+            }
+        }
+        let (covered_bitmap, _) = self.cover_played(words)?;
+        if let Some(word) = unknown {
+            return Err(LetterBouncedError::UnknownWord { word, suggestions: Vec::new() });
+        }
+
+        let mut missing: Vec<char> = self
+            .letter_to_bit
+            .iter()
+            .filter(|(_, bit)| covered_bitmap & **bit == 0)
+            .map(|(letter, _)| *letter)
+            .collect();
+        missing.sort_unstable();
+        Ok(missing)
+    }
+
+    // The letters covered by the played words, and the letter the last one finished on. Fails if the words don't
+    // chain or use letters which aren't on the board.
+    fn cover_played(&self, played: &[&str]) -> Result<(u32, Option<char>)> {
+        let mut covered_bitmap = 0;
+        let mut last_char = None;
+        for word in played {
+            let word = word.to_lowercase();
+            let first_char = word.chars().next();
+            if last_char.is_some() && first_char != last_char {
+                return Err(LetterBouncedError::InvalidPlay(format!(
+                    "'{}' doesn't start with the last letter of the previous word",
+                    word
+                )));
+            }
+            for ch in word.chars() {
+                match self.letter_to_bit.get(&ch) {
+                    Some(bit) => covered_bitmap |= bit,
+                    None => {
+                        return Err(LetterBouncedError::InvalidPlay(format!(
+                            "Letter '{}' in '{}' is not on the board",
+                            ch, word
+                        )))
+                    }
+                }
+            }
+            last_char = word.chars().last();
+        }
+        Ok((covered_bitmap, last_char))
+    }
+
+    fn solve_from_root(&self, root: SearchRoot, cancel_flag: Option<Arc<AtomicBool>>) -> (SolveOutcome, SolveStats) {
+        let mut state = SearchState::new(
+            SearchLimits {
+                cancel_flag: cancel_flag.as_ref(),
+                deadline: self.timeout.map(|timeout| Instant::now() + timeout),
+            },
+            root,
+            self.max_solutions,
+        );
         let mut solutions = Vec::new();
+        let mut stopped = false;
+
+        // Try solutions of each exact length. With a quota per length, each gets a search of its own, and all of
+        // them run before the ranking below picks the best.
+        for target_words in 1..=self.max_words {
+            let span = search_pass_span(target_words);
+            let (solutions_before, nodes_before) = (solutions.len(), state.stats.nodes_expanded);
+            let mut found = Vec::new();
+            let into = match self.max_per_length {
+                Some(quota) => {
+                    state.quota = quota;
+                    &mut found
+                }
+                None => &mut solutions,
+            };
+            let mut current_path = Vec::new();
+            stopped = !self.search_recursive(&mut current_path, root.covered_bitmap, None, into, target_words, &mut state);
+            solutions.append(&mut found);
+            span.record("solutions", solutions.len() - solutions_before);
+            span.record("nodes", state.stats.nodes_expanded - nodes_before);
+            span.record("stopped", stopped);
+
+            if stopped || (self.max_per_length.is_none() && solutions.len() >= self.max_solutions) {
+                break;
+            }
+        }
+
+        self.rank(&mut solutions);
 
-        // Try solutions of each exact length
-        for target_words in 1..=4 {
+        let cancelled = cancel_flag.as_ref().is_some_and(|flag| flag.load(Ordering::Relaxed));
+        let outcome = SolveOutcome {
+            solutions,
+            timed_out: stopped && !cancelled,
+        };
+        let stats = SolveStats {
+            words_filtered: self.words_filtered,
+            ..state.stats
+        };
+        (outcome, stats)
+    }
+
+    /// Like `solve_with_outcome`, but `max_solutions` applies to each word count separately, so a glut of
+    /// two-word solutions can't crowd out the three-word ones. With `max_per_length`, each word count's search
+    /// stops after that many solutions, and the best `max_solutions` of them are kept.
+    pub fn solve_by_word_count(&self, cancel_flag: Option<Arc<AtomicBool>>) -> SolutionSet {
+        let mut state = SearchState::new(
+            SearchLimits {
+                cancel_flag: cancel_flag.as_ref(),
+                deadline: self.timeout.map(|timeout| Instant::now() + timeout),
+            },
+            self.fresh_root(),
+            self.max_per_length.unwrap_or(self.max_solutions),
+        );
+        let mut set = SolutionSet::default();
+
+        for target_words in 1..=self.max_words {
+            let span = search_pass_span(target_words);
+            let nodes_before = state.stats.nodes_expanded;
+            let mut solutions = Vec::new();
             let mut current_path = Vec::new();
-            let cancelled = !self.search_recursive(
-                &mut current_path,
-                0,
-                None,
-                &mut solutions,
-                target_words,
-                cancel_flag.as_ref(),
-            );
+            let stopped = !self.search_recursive(&mut current_path, 0, None, &mut solutions, target_words, &mut state);
+            span.record("solutions", solutions.len());
+            span.record("nodes", state.stats.nodes_expanded - nodes_before);
+            span.record("stopped", stopped);
 
-            if cancelled || solutions.len() >= self.max_solutions {
+            self.rank(&mut solutions);
+            set.buckets.push(solutions);
+
+            if stopped {
+                let cancelled = cancel_flag.is_some_and(|flag| flag.load(Ordering::Relaxed));
+                set.timed_out = !cancelled;
                 break;
             }
         }
 
-        // Sort by score descending
-        solutions.sort_by(|a, b| b.score.cmp(&a.score));
+        set
+    }
 
-        // Ensure we don't exceed max_solutions after sorting
-        solutions.truncate(self.max_solutions);
+    /// Why a solve found nothing. Only meaningful if the search ran to completion, rather than being stopped by
+    /// the timeout or cancelled. Looks for longer chains than `max_words` allows, so it can take a moment.
+    pub fn explain_no_solutions(&self) -> NoSolutionReason {
+        if self.max_solutions == 0 {
+            return NoSolutionReason::NoSolutionsRequested;
+        }
+        let unplayable = self.required_words.iter().zip(&self.required_indices).find(|(_, index)| index.is_none());
+        if let Some((word, _)) = unplayable {
+            return NoSolutionReason::RequiredWordUnplayable(word.clone());
+        }
+
+        let coverable = self.bitmaps.iter().fold(0, |acc, bitmap| acc | bitmap);
+        if coverable != self.all_letters_mask {
+            let mut letters: Vec<char> = self
+                .letter_to_bit
+                .iter()
+                .filter(|(_, &bit)| coverable & bit == 0)
+                .map(|(&letter, _)| letter)
+                .collect();
+            letters.sort_unstable();
+            return NoSolutionReason::UncoverableLetters(letters);
+        }
+        if let Some(letter) = self.start_letter {
+            if self.candidates_starting_with(Some(letter)).is_empty() {
+                return NoSolutionReason::NoWordStartsWith(letter);
+            }
+        }
 
+        let limit = self.max_words.max(MAX_EXPLAINED_WORDS);
+        let mut fruitless = HashSet::new();
+        match (1..=limit).find(|&words| self.any_cover(0, self.start_letter, words, &mut fruitless)) {
+            None => NoSolutionReason::NoCoveringChain { max_words: limit },
+            Some(needed) if needed > self.max_words => NoSolutionReason::MaxWordsTooLow {
+                max_words: self.max_words,
+                needed,
+            },
+            Some(_) => NoSolutionReason::FilteredOut,
+        }
+    }
+
+    // Whether some chain of `words_left` more words, starting with `first_letter` if given, covers the board,
+    // ignoring required words, rarity and redundancy
+    fn any_cover(
+        &self,
+        covered_bitmap: u32,
+        first_letter: Option<char>,
+        words_left: usize,
+        fruitless: &mut HashSet<(u32, Option<char>, usize)>,
+    ) -> bool {
+        if covered_bitmap == self.all_letters_mask {
+            return true;
+        }
+        if words_left == 0 || fruitless.contains(&(covered_bitmap, first_letter, words_left)) {
+            return false;
+        }
+        let found = self.candidates_starting_with(first_letter).iter().any(|candidate| {
+            let new_bitmap = covered_bitmap | candidate.bitmap;
+            new_bitmap != covered_bitmap && self.any_cover(new_bitmap, candidate.last_char, words_left - 1, fruitless)
+        });
+        if !found {
+            fruitless.insert((covered_bitmap, first_letter, words_left));
+        }
+        found
+    }
+
+    /// All non-redundant solutions of exactly `target_words` words, up to `max_solutions`, in search order.
+    pub fn solve_exact_length(&self, target_words: usize) -> Vec<Solution> {
+        let mut state = SearchState::new(
+            SearchLimits {
+                cancel_flag: None,
+                deadline: None,
+            },
+            self.fresh_root(),
+            self.max_solutions,
+        );
+        let mut solutions = Vec::new();
+        let mut current_path = Vec::new();
+        self.search_recursive(&mut current_path, 0, None, &mut solutions, target_words, &mut state);
         solutions
     }
 
+    fn candidates_starting_with(&self, letter: Option<char>) -> &[Candidate] {
+        match letter {
+            Some(ch) => letter_index(ch)
+                .map(|index| self.candidates_by_first_letter[index].as_slice())
+                .unwrap_or_default(),
+            None => &self.all_candidates,
+        }
+    }
+
+    // The words which can follow one ending in `letter` and cover some of `needed`, those covering the most
+    // first, and words covering as many in frequency order. There are too many combinations of needed letters to
+    // build every list up front, but a search only reaches a few thousand, each many times, so each is built the
+    // first time and kept in `state`.
+    fn next_candidates(&self, letter: char, needed: u32, state: &mut SearchState) -> Range<usize> {
+        if let Some(range) = state.next_candidates.get(&(letter, needed)) {
+            return range.clone();
+        }
+        // A counting sort: gains only go up to the number of letters still needed, and a search builds thousands
+        // of these lists, so it's much quicker than comparing. A gain of nothing ranks last and is left out.
+        let candidates = self.candidates_starting_with(Some(letter));
+        let most = needed.count_ones() as usize;
+        let rank = |candidate: &Candidate| most - (candidate.bitmap & needed).count_ones() as usize;
+        let mut next_slot = vec![0; most + 1];
+        for candidate in candidates {
+            next_slot[rank(candidate)] += 1;
+        }
+        let start = state.candidate_lists.len();
+        let range = start..start + candidates.len() - next_slot[most];
+        // From how many have each rank to where each rank starts
+        let mut slot_start = start;
+        for slot in &mut next_slot {
+            (*slot, slot_start) = (slot_start, slot_start + *slot);
+        }
+        state.candidate_lists.extend_from_slice(&candidates[..range.len()]);
+        for &candidate in candidates.iter().filter(|candidate| rank(candidate) < most) {
+            let slot = &mut next_slot[rank(&candidate)];
+            state.candidate_lists[*slot] = candidate;
+            *slot += 1;
+        }
+        state.next_candidates.insert((letter, needed), range.clone());
+        range
+    }
+
+    /// Start a solve which runs a slice at a time; see `IncrementalSolve`. With `max_per_length`, batches can
+    /// hold more than `max_solutions` between them, for the caller to rank and cut down as `solve_async` does.
+    pub fn solve_incrementally(&self) -> IncrementalSolve<'_> {
+        IncrementalSolve {
+            solver: self,
+            state: SearchState::new(
+                SearchLimits {
+                    cancel_flag: None,
+                    deadline: self.timeout.map(|timeout| Instant::now() + timeout),
+                },
+                self.fresh_root(),
+                self.max_per_length.unwrap_or(self.max_solutions),
+            ),
+            solutions: Vec::new(),
+            target_words: 1,
+            next_first_word: 0,
+            finished: false,
+            timed_out: false,
+        }
+    }
+
+    /// Like `solve_with_outcome`, but hands control back to the caller's executor between slices of the search by
+    /// awaiting `yield_now()`, so a long solve doesn't hog a single-threaded runtime or a browser's worker. Pass the
+    /// executor's own yield, like `tokio::task::yield_now`; this crate doesn't depend on any runtime. Setting
+    /// `cancel_flag` stops the search at the next yield, with whatever it has found. After each slice which finds
+    /// something, `on_progress` is called with the new solutions, in search order, and how many there are so far.
+    pub async fn solve_async<F, Fut, P>(
+        &self,
+        cancel_flag: Option<Arc<AtomicBool>>,
+        mut yield_now: F,
+        mut on_progress: P,
+    ) -> SolveOutcome
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = ()>,
+        P: FnMut(&[Solution], usize),
+    {
+        let mut solve = self.solve_incrementally();
+        while let Some(batch) = solve.next_batch(FIRST_WORDS_PER_YIELD, NODES_PER_YIELD) {
+            if !batch.is_empty() {
+                on_progress(&batch, solve.solutions.len());
+            }
+            yield_now().await;
+            if cancel_flag.as_ref().is_some_and(|flag| flag.load(Ordering::Relaxed)) {
+                break;
+            }
+        }
+
+        // Taken from the search rather than the batches, since a better plural variant replaces the one it found
+        // first in place
+        let timed_out = solve.timed_out();
+        let mut solutions = solve.solutions;
+        self.rank(&mut solutions);
+        SolveOutcome { solutions, timed_out }
+    }
+
     fn search_recursive(
         &self,
-        current_path: &mut Vec<Word>,
+        current_path: &mut Vec<usize>,
         covered_bitmap: u32,
         last_char: Option<char>,
         solutions: &mut Vec<Solution>,
         target_words: usize,
-        cancel_flag: Option<&Arc<AtomicBool>>,
-    ) -> bool // Returns true if not cancelled
+        state: &mut SearchState,
+    ) -> bool // Returns true if not cancelled or timed out
     {
-        // Check for cancellation
-        if let Some(flag) = cancel_flag {
-            if flag.load(Ordering::Relaxed) {
-                return false; // Cancelled
-            }
+        if state.limits.should_stop() {
+            return false;
         }
 
         // Early termination if we have enough solutions
-        if solutions.len() >= self.max_solutions {
+        if solutions.len() >= state.quota {
+            return true;
+        }
+        state.stats.nodes_expanded += 1;
+
+        // Transposition pruning: if we've been here before via different words and nothing covered the board,
+        // nothing will this time either.
+        let required_used = self.required_words_used(current_path);
+        let missing_required =
+            self.required_words.len() - required_used.iter().map(|block| block.count_ones() as usize).sum::<usize>();
+        let position = (covered_bitmap, last_char, target_words - current_path.len(), required_used);
+        if state.fruitless.contains(&position) {
+            state.stats.transposition_hits += 1;
+            return true;
+        }
+
+        // Not enough words left to fit in the ones we're required to use
+        if current_path.len() + missing_required > target_words {
             return true;
         }
 
         // Check if we've found a complete solution of the target length
         if covered_bitmap == self.all_letters_mask && current_path.len() == target_words {
-            let solution = Solution::new(current_path.clone());
-            if !self.is_solution_redundant(&solution) {
-                solutions.push(solution);
-                return true;
+            state.covers_found += 1;
+            let solution = self.solution_from_path(current_path);
+            let too_spread = self.max_rarity_spread.is_some_and(|max| solution.rarity_spread() > max);
+            let unplayable = self.letters_on_several_sides && solution.letter_path(&self.board).is_none();
+            if missing_required == 0
+                && !too_spread
+                && !unplayable
+                && !self.first_word_full(current_path[0], solutions)
+            {
+                if !self.filter_redundant || !self.is_solution_redundant(&solution, &state.root) {
+                    if self.collapse_plurals {
+                        self.keep_best_plural_variant(solution, solutions, state);
+                    } else {
+                        solutions.push(solution);
+                    }
+                    return true;
+                }
+                state.stats.redundancy_rejections += 1;
+                state.stats.depth(current_path.len() - 1).redundancy_rejections += 1;
             }
         }
 
@@ -251,44 +1685,78 @@ impl Solver {
             return true;
         }
 
+        let covers_before = state.covers_found;
+
         // Determine which words we can try next
-        let word_indices: Vec<usize> = if let Some(ch) = last_char {
-            // Must start with the last character of the previous word
-            self.words_by_first_letter
-                .get(&ch)
-                .cloned()
-                .unwrap_or_default()
+        let required_first_letter = if current_path.is_empty() {
+            // First word - can be any word, unless we were told where to start or are continuing a game
+            state.root.first_letter
         } else {
-            // First word - can be any word
-            (0..self.word_bitmaps.len()).collect()
+            // Must start with the last character of the previous word
+            last_char
         };
-
-        for word_idx in word_indices {
-            let word_bitmap = &self.word_bitmaps[word_idx];
-            let new_bitmap = covered_bitmap | word_bitmap.bitmap;
+        // Counted here and added up after the loop, to keep the bookkeeping out of the way of the recursion
+        let depth = current_path.len();
+        let (mut candidates, mut no_new_letters) = (0, 0);
+        let choices = match required_first_letter {
+            Some(letter) => {
+                let range = self.next_candidates(letter, self.all_letters_mask & !covered_bitmap, state);
+                // The words which would add no letters were left out, but still count as candidates
+                let skipped = self.candidates_starting_with(Some(letter)).len() - range.len();
+                candidates += skipped;
+                no_new_letters += skipped;
+                range
+            }
+            None => 0..self.all_candidates.len(),
+        };
+        for choice in choices {
+            let candidate = match required_first_letter {
+                Some(_) => state.candidate_lists[choice],
+                None => self.all_candidates[choice],
+            };
+            candidates += 1;
+            // No point exploring openings which have had their share of solutions
+            if current_path.is_empty() && self.first_word_full(candidate.word_index, solutions) {
+                continue;
+            }
+            let new_bitmap = covered_bitmap | candidate.bitmap;
 
             // Only continue if this word adds new letters
             if new_bitmap != covered_bitmap {
-                current_path.push(word_bitmap.word.clone());
-                let new_last_char = word_bitmap.word.word.chars().last();
+                current_path.push(candidate.word_index);
 
                 if !self.search_recursive(
                     current_path,
                     new_bitmap,
-                    new_last_char,
+                    candidate.last_char,
                     solutions,
                     target_words,
-                    cancel_flag,
+                    state,
                 ) {
                     current_path.pop();
-                    return false; // Cancelled
+                    let counts = state.stats.depth(depth);
+                    counts.candidates += candidates;
+                    counts.no_new_letters += no_new_letters;
+                    return false; // Cancelled or timed out
                 }
 
                 current_path.pop();
+            } else {
+                no_new_letters += 1;
             }
         }
+        let counts = state.stats.depth(depth);
+        counts.candidates += candidates;
+        counts.no_new_letters += no_new_letters;
+
+        // Whether a chain is redundant depends on the words before it, but whether it covers the board does not,
+        // so only remember positions which reached no cover at all. Searches cut short by max_solutions weren't
+        // exhaustive, so they prove nothing.
+        if state.covers_found == covers_before && solutions.len() < state.quota {
+            state.fruitless.insert(position);
+        }
 
-        true // Not cancelled
+        true // Not cancelled or timed out
     }
 }
 
@@ -296,21 +1764,83 @@ impl Solver {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_many_required_words() {
+        // More required words than fit in one block of the bitset, all playable
+        let board = Board::from_spec("yfa,otk,lgw,rni").unwrap();
+        let mut words = Vec::new();
+        for [first, second, third] in [["yfa", "otk", "lgw"], ["rni", "yfa", "otk"], ["otk", "lgw", "rni"]] {
+            for a in first.chars() {
+                for b in second.chars() {
+                    for c in third.chars() {
+                        words.push(format!("{}{}{}", a, b, c));
+                    }
+                }
+            }
+        }
+        let dictionary = Dictionary::from_strings(words.clone());
+        let solver = Solver::with_config(
+            board,
+            &dictionary,
+            SolverConfig::new().max_words(words.len()).required_words(&words),
+        );
+
+        let path: Vec<usize> = solver.required_indices.iter().flatten().copied().collect();
+        assert_eq!(path.len(), 81);
+        let used = solver.required_words_used(&path);
+        assert_eq!(used.len(), 2);
+        assert_eq!(used.iter().map(|block| block.count_ones()).sum::<u32>(), 81);
+        let first = solver.required_words_used(&path[..1]);
+        assert_eq!(first.iter().map(|block| block.count_ones()).sum::<u32>(), 1);
+    }
+
     #[test]
     fn test_solution_display() {
         let words = ["word", "ocean", "dojo"];
         let word_strings = words.iter().map(|&s| s.to_string()).collect();
         let dictionary = Dictionary::from_strings(word_strings);
         let solution = Solution::new(vec![
-            dictionary.words[0].clone(),
-            dictionary.words[2].clone(),
-            dictionary.words[1].clone(),
+            dictionary.words()[0].clone(),
+            dictionary.words()[2].clone(),
+            dictionary.words()[1].clone(),
         ]);
         assert_eq!(solution.to_string(), "word-dojo-ocean");
-        let single_word = Solution::new(vec![dictionary.words[0].clone()]);
+        assert_eq!(solution.letter_count(), 13);
+        // D and O join the words; the other O visits are revisits
+        assert_eq!(solution.revisited_letters(), 2);
+        assert_eq!(format!("{:#}", solution), "word-dojo-ocean (13 letters, 2 revisited)");
+        let single_word = Solution::new(vec![dictionary.words()[0].clone()]);
         assert_eq!(single_word.to_string(), "word");
     }
 
+    #[test]
+    fn test_score_breakdown() {
+        let dictionary = Dictionary::from_text("forklift 14\ntwangy 18\nforklifts 40\n");
+        let solution = Solution::new(vec![dictionary.words()[2].clone(), dictionary.words()[1].clone()]);
+        assert_eq!(solution.to_string(), "forklift-twangy");
+        // The rarest word is 14; 12 of the 13 letter visits are new; one word after the first
+        let breakdown = solution.score_breakdown();
+        assert_eq!(
+            breakdown,
+            ScoreBreakdown {
+                total: 54,
+                frequency: 28,
+                letter_efficiency: 36,
+                length_penalty: 10,
+            }
+        );
+        assert_eq!(breakdown.to_string(), "54/100 = frequency 28 + letter efficiency 36 - length 10");
+
+        // Frequencies above the top of the scale count as the top
+        let single_word = Solution::new(vec![dictionary.words()[0].clone()]);
+        assert_eq!(single_word.score_breakdown().frequency, 60);
+        assert!(single_word.score_breakdown().total <= 100);
+
+        // Nothing to score, but no division by zero either
+        let empty = Solution::new(Vec::new());
+        assert_eq!((empty.score, empty.score_breakdown().total), (0, 0));
+    }
+
     #[test]
     fn test_redactable_subsequences() {
         let words = ["foxglove", "eye", "equity"];
@@ -319,30 +1849,34 @@ mod tests {
 
         // Test FOXGLOVE-EYE-EQUITY
         let solution = Solution::new(vec![
-            dictionary.words[0].clone(), // foxglove (index 0)
-            dictionary.words[1].clone(), // eye (index 1)
-            dictionary.words[2].clone(), // equity (index 2)
+            dictionary.words()[0].clone(), // foxglove (index 0)
+            dictionary.words()[1].clone(), // eye (index 1)
+            dictionary.words()[2].clone(), // equity (index 2)
         ]);
 
-        let redaction_indices = solution.redactable_subsequences();
-
-        // Should include redactions like:
-        // - [1, 2] = [EYE, EQUITY] (removes head)
-        // - [2] = [EQUITY] (removes head)
-        // - [0, 2] = [FOXGLOVE, EQUITY] (valid chain, skips EYE)
-        // etc.
-
-        // Helper to check if specific indices are in redactions
-        let has_redaction = |expected_indices: Vec<usize>| {
-            redaction_indices.iter().any(|r| *r == expected_indices)
-        };
+        let redactions = solution.redactable_subsequences();
 
-        assert!(has_redaction(vec![1, 2]), "Should have [1, 2] = EYE-EQUITY (removes head)");
-        assert!(has_redaction(vec![2]), "Should have [2] = EQUITY (removes head)");
-        assert!(has_redaction(vec![0, 2]), "Should have [0, 2] = FOXGLOVE-EQUITY (valid chain)");
+        assert!(redactions.contains(&0b110), "Should have EYE-EQUITY (removes head)");
+        assert!(redactions.contains(&0b100), "Should have EQUITY (removes head)");
+        assert!(redactions.contains(&0b101), "Should have FOXGLOVE-EQUITY (valid chain)");
+        assert!(redactions.contains(&0b011), "Should have FOXGLOVE-EYE (removes tail)");
 
         // Should NOT include the full solution
-        assert!(!has_redaction(vec![0, 1, 2]), "Should not include full solution [0, 1, 2]");
+        assert!(!redactions.contains(&0b111), "Should not include full solution");
+    }
+
+    #[test]
+    fn test_redactable_subsequences_skip_broken_chains() {
+        let dictionary = Dictionary::from_strings(
+            ["work", "kif", "flagrantly", "yak"].iter().map(|s| s.to_string()).collect(),
+        );
+        let solution = Solution::new(dictionary.words().to_vec());
+
+        let mut redactions = solution.redactable_subsequences();
+        redactions.sort_unstable();
+        // Cutting KIF leaves WORK-FLAGRANTLY, which doesn't chain, and cutting FLAGRANTLY leaves WORK-KIF-YAK,
+        // which doesn't either. Subsets like KIF-YAK are covered by the suffix KIF-FLAGRANTLY-YAK.
+        assert_eq!(redactions, vec![0b0001, 0b0011, 0b0111, 0b1000, 0b1100, 0b1110]);
     }
 
     #[test]
@@ -351,7 +1885,7 @@ mod tests {
         let word_strings = words.iter().map(|&s| s.to_string()).collect();
         let dictionary = Dictionary::from_strings(word_strings);
 
-        let solution = Solution::new(vec![dictionary.words[0].clone()]);
+        let solution = Solution::new(vec![dictionary.words()[0].clone()]);
         let redactions = solution.redactable_subsequences();
 
         assert_eq!(redactions.len(), 0, "Single word solution should have no redactions");
@@ -371,21 +1905,21 @@ mod tests {
         let word_strings = word_strs.iter().map(|&s| s.to_string()).collect();
         let dictionary = Dictionary::from_strings(word_strings);
 
-        let foxglove = &dictionary.words[0];
-        let equity = &dictionary.words[1];
-        let eye = &dictionary.words[2];
-        let golf = &dictionary.words[3];
-        let flog = &dictionary.words[4];
-        let glove = &dictionary.words[5];
-        let exile = &dictionary.words[6];
-        let exit = &dictionary.words[7];
-        let tie = &dictionary.words[8];
-        let yog = &dictionary.words[9];
+        let foxglove = &dictionary.words()[0];
+        let equity = &dictionary.words()[1];
+        let eye = &dictionary.words()[2];
+        let golf = &dictionary.words()[3];
+        let flog = &dictionary.words()[4];
+        let glove = &dictionary.words()[5];
+        let exile = &dictionary.words()[6];
+        let exit = &dictionary.words()[7];
+        let tie = &dictionary.words()[8];
+        let yog = &dictionary.words()[9];
 
         let solver = Solver::new(board, &dictionary, 1000);
         let solutions = solver.solve();
 
-        fn has(solutions: &Vec<Solution>, ws: Vec<&Word>) -> bool {
+        fn has(solutions: &[Solution], ws: Vec<&Word>) -> bool {
             let vec_word_clones: Vec<Word> = ws.iter().map(|&w| w.clone()).collect();
             let solution = Solution::new(vec_word_clones);
             solutions.contains(&solution)
@@ -408,6 +1942,166 @@ mod tests {
 
     }
 
+    #[test]
+    fn test_letter_path() {
+        let sides = vec![
+            "vyq".to_string(),
+            "fig".to_string(),
+            "ote".to_string(),
+            "xlu".to_string(),
+        ];
+        let board = Board::from_sides(sides).unwrap();
+        let dictionary = Dictionary::from_strings(vec!["golf".to_string(), "fox".to_string(), "fig".to_string()]);
+
+        let solution = Solution::new(vec![dictionary.words()[0].clone(), dictionary.words()[1].clone()]);
+        assert_eq!(
+            solution.letter_path(&board),
+            Some(vec![(1, 2), (2, 0), (3, 1), (1, 0), (2, 0), (3, 0)])
+        );
+
+        // FIG can't be played: F, I and G are all on one side
+        let unplayable = Solution::new(vec![dictionary.words()[2].clone()]);
+        assert_eq!(unplayable.letter_path(&board), None);
+
+        // D can only reach the A on the right, which is the only copy Y can follow
+        let options = BoardOptions::new(GameRules::DuplicateLetters);
+        let board = Board::from_spec_with_options("ayb,axc,def,ghi", options).unwrap();
+        let day = Solution::new(vec![Word::new("day".to_string(), 20)]);
+        assert_eq!(day.letter_path(&board), Some(vec![(2, 0), (1, 0), (0, 1)]));
+    }
+
+    #[test]
+    fn test_find_pangram_words() {
+        let sides = vec![
+            "vyq".to_string(),
+            "fig".to_string(),
+            "ote".to_string(),
+            "xlu".to_string(),
+        ];
+        let board = Board::from_sides(sides).unwrap();
+        let dictionary = Dictionary::from_text("foxglove 16\nequity 18\nfoxglovequity 1\n");
+
+        let solver = Solver::new(board, &dictionary, 10);
+        let pangrams = solver.find_pangram_words();
+
+        assert_eq!(pangrams.len(), 1);
+        assert_eq!(pangrams[0].word, "foxglovequity");
+    }
+
+    #[test]
+    fn test_common_words_found_first() {
+        let sides = vec![
+            "ab".to_string(),
+            "cd".to_string(),
+            "ef".to_string(),
+            "gh".to_string(),
+        ];
+        let board = Board::from_sides(sides).unwrap();
+        // The rare chain comes first in the dictionary, but the common one should be found first
+        let dictionary = Dictionary::from_text("aceg 1\ngbdfh 1\nacfg 20\ngbdeh 20\n");
+
+        let solver = Solver::new(board, &dictionary, 1);
+        let solutions = solver.solve();
+
+        assert_eq!(solutions.len(), 1);
+        assert_eq!(solutions[0].to_string(), "acfg-gbdeh");
+    }
+
+    #[test]
+    fn test_equivalent_prefixes_both_searched() {
+        let sides = vec![
+            "ab".to_string(),
+            "cd".to_string(),
+            "ef".to_string(),
+            "gh".to_string(),
+        ];
+        let board = Board::from_sides(sides).unwrap();
+        // ACG and CAG reach the same position: same letters covered, both end in G
+        let dictionary = Dictionary::from_text("acg 20\ncag 10\ngbdfhe 15\nachg 5\n");
+
+        let solver = Solver::new(board, &dictionary, 10);
+        let solutions: Vec<String> = solver.solve().iter().map(|s| s.to_string()).collect();
+
+        assert_eq!(solutions, vec!["acg-gbdfhe", "cag-gbdfhe"]);
+    }
+
+    #[test]
+    fn test_start_letter() {
+        let sides = vec![
+            "vyq".to_string(),
+            "fig".to_string(),
+            "ote".to_string(),
+            "xlu".to_string(),
+        ];
+        let board = Board::from_sides(sides).unwrap();
+        let dictionary = Dictionary::from_strings(
+            ["foxglove", "equity", "golf", "yog"].iter().map(|s| s.to_string()).collect(),
+        );
+
+        let config = SolverConfig::new().start_letter('G');
+        let solver = Solver::with_config(board, &dictionary, config);
+        let solutions: Vec<String> = solver.solve().iter().map(|s| s.to_string()).collect();
+
+        // Normally redundant, since FOXGLOVE-EQUITY covers the board, but that doesn't start with G
+        assert_eq!(solutions, vec!["golf-foxglove-equity"]);
+    }
+
+    #[test]
+    fn test_next_candidates() {
+        let board = Board::from_spec("yfa,otk,lgw,rni").unwrap();
+        let dictionary = Dictionary::from_text("tan 30\ntail 25\ntwangy 12\ntaxi 10\n");
+        let solver = Solver::new(board, &dictionary, 10);
+        let limits = SearchLimits { cancel_flag: None, deadline: None };
+        let mut state = SearchState::new(limits, solver.fresh_root(), 10);
+        let words = |state: &SearchState, range: Range<usize>| -> Vec<String> {
+            state.candidate_lists[range].iter().map(|c| solver.words.word(c.word_index).to_string()).collect()
+        };
+        let bits = |letters: &str| letters.chars().fold(0, |mask, ch| mask | solver.letter_to_bit[&ch]);
+
+        // TWANGY covers the most, though the others are commoner
+        let range = solver.next_candidates('t', solver.all_letters_mask, &mut state);
+        assert_eq!(words(&state, range), vec!["twangy", "tail", "tan"]);
+        // With only A and N needed, TAN and TWANGY cover as many, so they're in frequency order
+        let range = solver.next_candidates('t', bits("an"), &mut state);
+        assert_eq!(words(&state, range.clone()), vec!["tan", "twangy", "tail"]);
+        assert_eq!(solver.next_candidates('t', bits("an"), &mut state), range);
+        // and words which cover nothing needed are left out
+        let range = solver.next_candidates('t', bits("wy"), &mut state);
+        assert_eq!(words(&state, range), vec!["twangy"]);
+        assert_eq!(state.next_candidates.len(), 3);
+    }
+
+    #[test]
+    fn test_solve_stats() {
+        let sides = vec![
+            "vyq".to_string(),
+            "fig".to_string(),
+            "ote".to_string(),
+            "xlu".to_string(),
+        ];
+        let board = Board::from_sides(sides).unwrap();
+        // ZEBRA isn't playable on this board
+        let dictionary = Dictionary::from_strings(
+            ["foxglove", "equity", "eye", "zebra"].iter().map(|s| s.to_string()).collect(),
+        );
+
+        let solver = Solver::new(board, &dictionary, 10);
+        let (outcome, stats) = solver.solve_with_stats(None);
+
+        assert_eq!(outcome.solutions.len(), 1);
+        assert_eq!(stats.words_filtered, 1);
+        assert!(stats.nodes_expanded > 0);
+        // FOXGLOVE-EYE-EQUITY covers the board but FOXGLOVE-EQUITY already does
+        assert_eq!(stats.redundancy_rejections, 1);
+        assert_eq!(stats.per_depth[2].redundancy_rejections, 1);
+        assert_eq!(stats.per_depth.iter().map(|d| d.redundancy_rejections).sum::<usize>(), 1);
+        // Each search tries all three playable words first
+        assert_eq!(stats.per_depth[0].candidates, 3 * solver.max_words);
+        assert_eq!(stats.per_depth[0].no_new_letters, 0);
+        // EYE after EYE adds nothing
+        assert!(stats.per_depth[1].no_new_letters > 0);
+    }
+
     #[test]
     fn test_bitmap_coverage() {
         let sides = vec![
@@ -424,7 +2118,7 @@ mod tests {
 
         // Add all valid digraphs from the game (cross-side pairs)
         for digraph in &game.digraphs {
-            valid_digraphs.insert(digraph.clone());
+            valid_digraphs.insert(*digraph);
         }
 
         // For our test words, we need to pick ones that use valid cross-side digraphs
@@ -438,22 +2132,74 @@ mod tests {
         assert_eq!(solver.all_letters_mask, 0b11111111); // 8 bits for 8 letters
 
         // Test that word bitmaps are correctly calculated
-        if let Some(word_ac) = solver.word_bitmaps.iter().find(|wb| wb.word.word == "AC") {
+        if let Some(word_ac) = solver.words.position("AC") {
             // A=bit0, C=bit2, so AC should be 0b00000101
-            assert_eq!(word_ac.bitmap, 0b00000101);
+            assert_eq!(solver.bitmaps[word_ac], 0b00000101);
         }
 
-        if let Some(word_ce) = solver.word_bitmaps.iter().find(|wb| wb.word.word == "CE") {
+        if let Some(word_ce) = solver.words.position("CE") {
             // C=bit2, E=bit4, so CE should be 0b00010100
-            assert_eq!(word_ce.bitmap, 0b00010100);
+            assert_eq!(solver.bitmaps[word_ce], 0b00010100);
         }
 
-        if let Some(word_eg) = solver.word_bitmaps.iter().find(|wb| wb.word.word == "EG") {
+        if let Some(word_eg) = solver.words.position("EG") {
             // E=bit4, G=bit6, so EG should be 0b01010000
-            assert_eq!(word_eg.bitmap, 0b01010000);
+            assert_eq!(solver.bitmaps[word_eg], 0b01010000);
         }
 
         // Test that basic bitmap operations work
-        assert!(solver.word_bitmaps.len() > 0);
+        assert!(!solver.words.is_empty());
+    }
+
+    #[test]
+    fn test_pack_round_trip() {
+        let board = Board::from_spec("yfa,otk,lgw,rni").unwrap();
+        let dictionary = Dictionary::from_text("forklift 20\ntwangy 18 british\nfork 20\nkilt 20\nquiz 20\n");
+        let solver = Solver::new(board, &dictionary, 10);
+        let pack = solver.pack();
+        assert_eq!(pack.words.len(), 4);
+        assert_eq!(pack.words_filtered, 1);
+
+        let json = serde_json::to_string(&pack).unwrap();
+        let loaded: SolverPack = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, pack);
+        let reloaded = Solver::from_pack(&loaded, SolverConfig::new().max_solutions(10)).unwrap();
+        assert_eq!(reloaded.solve(), solver.solve());
+        assert_eq!(reloaded.pack(), pack);
+        // Solutions' words keep their tags, through the pack too
+        assert_eq!(reloaded.solve()[0].words[1].tags, vec!["british"]);
+
+        // A different max_solutions is fine, but word filters were settled when the pack was made
+        assert_eq!(Solver::from_pack(&pack, SolverConfig::new().max_solutions(1)).unwrap().solve().len(), 1);
+        let config = SolverConfig::new().excluded_words(["fork"]);
+        assert!(matches!(Solver::from_pack(&pack, config), Err(LetterBouncedError::SolverConfig(_))));
+    }
+
+    #[test]
+    fn test_invalid_packs() {
+        let board = Board::from_spec("yfa,otk,lgw,rni").unwrap();
+        let dictionary = Dictionary::from_text("forklift 20\ntwangy 18\n");
+        let pack = Solver::new(board, &dictionary, 10).pack();
+
+        let mut damaged = Vec::new();
+        let mut newer = pack.clone();
+        newer.version += 1;
+        damaged.push(newer);
+        let mut letters = pack.clone();
+        letters.letters.swap(0, 1);
+        damaged.push(letters);
+        let mut bitmaps = pack.clone();
+        bitmaps.bitmaps[0] |= 1 << 20;
+        damaged.push(bitmaps);
+        let mut index = pack.clone();
+        index.first_letter_index[0].push(7);
+        damaged.push(index);
+        let mut misfiled = pack.clone();
+        misfiled.first_letter_index.swap(5, 19);
+        damaged.push(misfiled);
+
+        for pack in damaged {
+            assert!(matches!(Solver::from_pack(&pack, SolverConfig::new()), Err(LetterBouncedError::InvalidPack(_))));
+        }
     }
 }
@@ -0,0 +1,126 @@
+use crate::board::Board;
+use crate::dictionary::{Dictionary, Word};
+use proptest::prelude::*;
+
+/*
+ * proptest strategies for random boards and dictionaries, so properties of the solver can be checked on many more
+ * boards than anyone would write by hand. Built for the crate's own tests, and available to other crates with the
+ * `proptest` feature.
+ *
+ * A dictionary of random letter strings would almost never solve a board, so most of the generated words are walks
+ * around the board, which are always playable on it. A few random words which usually aren't are mixed in, to make
+ * sure they get filtered out.
+ */
+
+/// Standard boards: twelve different letters, three to a side
+pub fn board() -> impl Strategy<Value = Board> {
+    let alphabet: Vec<char> = ('a'..='z').collect();
+    proptest::sample::subsequence(alphabet, 12)
+        .prop_shuffle()
+        .prop_map(|letters| {
+            let sides = letters.chunks(3).map(|side| side.iter().collect()).collect();
+            Board::from_sides(sides).expect("twelve different letters make a valid board")
+        })
+}
+
+/// A word which can be played on `board`: each letter is on a different side from the one before
+pub fn word_on(board: &Board) -> impl Strategy<Value = String> {
+    let sides: Vec<Vec<char>> = board.sides.iter().map(|side| side.chars().collect()).collect();
+    let side_length = sides[0].len();
+    let start = (0..sides.len(), 0..side_length);
+    // Each step moves 1-3 sides round from the current one, so it never stays on the same side
+    let steps = proptest::collection::vec((1..sides.len(), 0..side_length), 1..8);
+    (start, steps).prop_map(move |((mut side, position), steps)| {
+        let mut word = String::from(sides[side][position]);
+        for (turn, position) in steps {
+            side = (side + turn) % sides.len();
+            word.push(sides[side][position]);
+        }
+        word
+    })
+}
+
+/// A dictionary of words mostly playable on `board`, with frequencies across the whole 0-31 scale
+pub fn dictionary_for(board: &Board) -> impl Strategy<Value = Dictionary> {
+    let playable = proptest::collection::vec((word_on(board), 0..=31i8), 5..60);
+    let unplayable = proptest::collection::vec(("[a-z]{3,8}", 0..=31i8), 0..10);
+    (playable, unplayable).prop_map(|(playable, unplayable)| {
+        let mut seen = std::collections::HashSet::new();
+        let words = playable
+            .into_iter()
+            .chain(unplayable)
+            .filter(|(word, _)| seen.insert(word.clone()))
+            .map(|(word, frequency)| Word::new(word, frequency))
+            .collect();
+        Dictionary::from_words(words)
+    })
+}
+
+/// A board and a dictionary for it
+pub fn board_and_dictionary() -> impl Strategy<Value = (Board, Dictionary)> {
+    board().prop_flat_map(|board| {
+        let dictionary = dictionary_for(&board);
+        (Just(board), dictionary)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::{Solution, Solver, SolverConfig};
+    use std::collections::HashSet;
+
+    fn board_letters(board: &Board) -> HashSet<char> {
+        board.sides.iter().flat_map(|side| side.chars()).collect()
+    }
+
+    // Whether the words in `kept` (a bitmask of indices) chain together and cover every letter on the board
+    fn covers_board(solution: &Solution, kept: u32, letters: &HashSet<char>) -> bool {
+        let words: Vec<&Word> = solution
+            .words
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| kept & (1 << i) != 0)
+            .map(|(_, w)| w)
+            .collect();
+        let chains = words.windows(2).all(|pair| pair[0].last_letter == pair[1].first_letter);
+        let covered: HashSet<char> = words.iter().flat_map(|w| w.word.chars()).collect();
+        chains && letters.is_subset(&covered)
+    }
+
+    proptest! {
+        #[test]
+        fn generated_words_are_playable((board, word) in board().prop_flat_map(|b| (Just(b.clone()), word_on(&b)))) {
+            prop_assert_eq!(board_letters(&board).len(), 12);
+            let word = Word::new(word, 0);
+            prop_assert!(word.digraphs.is_subset(&board.digraphs), "{} isn't playable on {:?}", word.word, board.sides);
+        }
+
+        #[test]
+        fn solutions_are_valid_and_not_redundant((board, dictionary) in board_and_dictionary()) {
+            let letters = board_letters(&board);
+            let words: HashSet<&str> = dictionary.words().iter().map(|w| w.word.as_str()).collect();
+            let config = SolverConfig::new().max_solutions(200).max_words(3);
+            let solutions = Solver::with_config(board.clone(), &dictionary, config).solve();
+
+            for solution in &solutions {
+                let all = (1u32 << solution.words.len()) - 1;
+                // Chains correctly and covers every letter
+                prop_assert!(covers_board(solution, all, &letters), "{} doesn't solve the board", solution);
+                for word in &solution.words {
+                    prop_assert!(words.contains(word.word.as_str()), "{} isn't in the dictionary", word.word);
+                    prop_assert!(
+                        word.digraphs.is_subset(&board.digraphs),
+                        "{} in {} isn't playable on the board",
+                        word.word,
+                        solution
+                    );
+                }
+                // No shorter chain of its words would do, checked the slow way against every subsequence
+                for kept in 1..all {
+                    prop_assert!(!covers_board(solution, kept, &letters), "{} is redundant", solution);
+                }
+            }
+        }
+    }
+}
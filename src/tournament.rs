@@ -0,0 +1,263 @@
+use crate::board::Board;
+use crate::dictionary::Dictionary;
+use crate::error::{LetterBouncedError, Result};
+use crate::solver::{Solution, Solver};
+use std::collections::HashMap;
+
+/*
+ * Scoring a league: several boards, and the answers several players sent in for them. Every answer is checked
+ * against its board and the dictionary, then ranked against the other answers to the same board under a
+ * `Ruleset`. Each board awards points by placing, and the points add up to a leaderboard.
+ *
+ * Entries are written one per line, after the boards they refer to:
+ *
+ *     # Week 12
+ *     board yfa,otk,lgw,rni
+ *     board vyq,fig,ote,xlu
+ *     alice 1 forklift-twangy
+ *     bob 1 now-wakf-fragility
+ *     alice 2 foxglove-equity
+ *
+ * Boards are numbered from 1 in the order they're given. A player's later answer to a board replaces their earlier
+ * one, so corrections can simply be appended.
+ */
+
+/// How answers to the same board are ranked against each other
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Ruleset {
+    /// Fewest words wins, then fewest letters typed
+    #[default]
+    FewestWords,
+    /// Fewest letters typed wins, then fewest words
+    FewestLetters,
+    /// The answer whose rarest word is rarest wins, then fewest words: a league for word nerds
+    RarestWords,
+}
+
+impl std::str::FromStr for Ruleset {
+    type Err = LetterBouncedError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "fewest-words" => Ok(Ruleset::FewestWords),
+            "fewest-letters" => Ok(Ruleset::FewestLetters),
+            "rarest-words" => Ok(Ruleset::RarestWords),
+            _ => Err(LetterBouncedError::Tournament(format!(
+                "Unknown ruleset '{}'. Choose fewest-words, fewest-letters or rarest-words",
+                s
+            ))),
+        }
+    }
+}
+
+impl Ruleset {
+    // Lower is better
+    fn rank_key(&self, solution: &Solution) -> (i32, usize, usize) {
+        let words = solution.words.len();
+        let letters = solution.letter_count();
+        match self {
+            Ruleset::FewestWords => (words as i32, letters, 0),
+            Ruleset::FewestLetters => (letters as i32, words, 0),
+            Ruleset::RarestWords => {
+                let rarest = solution.words.iter().map(|w| w.frequency).min().unwrap_or(0);
+                (rarest as i32, words, letters)
+            }
+        }
+    }
+}
+
+/// One player's answer to one board
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub player: String,
+    /// Index into `Tournament::boards`
+    pub board: usize,
+    pub words: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Tournament {
+    pub boards: Vec<Board>,
+    pub entries: Vec<Entry>,
+}
+
+/// An entry after checking and ranking
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScoredEntry {
+    pub entry: Entry,
+    /// The checked answer, or why it isn't a solution
+    pub solution: std::result::Result<Solution, String>,
+    pub points: usize,
+}
+
+/// A player's place on the leaderboard
+#[derive(Debug, Clone, PartialEq)]
+pub struct Standing {
+    pub player: String,
+    pub points: usize,
+    /// Boards the player sent a valid answer for
+    pub boards_solved: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Leaderboard {
+    /// Every entry, by board, best first; invalid ones last
+    pub entries: Vec<ScoredEntry>,
+    /// Most points first, then most boards solved, then by name
+    pub standings: Vec<Standing>,
+}
+
+impl Tournament {
+    /// Read boards and entries in the format described above. Blank lines and lines starting with # are skipped.
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut boards = Vec::new();
+        let mut entries: Vec<Entry> = Vec::new();
+        for (line_num, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |message: String| LetterBouncedError::Tournament(format!("Line {}: {}", line_num + 1, message));
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            match fields.as_slice() {
+                ["board", spec] => boards.push(Board::from_spec(spec).map_err(|e| error(e.to_string()))?),
+                [player, number, answer] => {
+                    let board = match number.parse::<usize>() {
+                        Ok(n) if (1..=boards.len()).contains(&n) => n - 1,
+                        _ => return Err(error(format!("No board {} has been given", number))),
+                    };
+                    let words = answer.split(['-', ',']).filter(|w| !w.is_empty()).map(str::to_lowercase).collect();
+                    let player = player.to_string();
+                    entries.retain(|e| e.player != player || e.board != board);
+                    entries.push(Entry { player, board, words });
+                }
+                _ => {
+                    return Err(error(format!(
+                        "Expected 'board <sides>' or '<player> <board number> <words>', not '{}'",
+                        line
+                    )))
+                }
+            }
+        }
+        Ok(Tournament { boards, entries })
+    }
+
+    /**
+     * Check every entry and award points. On each board, the best valid answer scores one point for every valid
+     * answer to that board, the next one fewer, and so on; answers which rank the same share the higher score.
+     * Invalid answers score nothing.
+     */
+    pub fn score(&self, dictionary: &Dictionary, ruleset: Ruleset) -> Leaderboard {
+        let mut scored = Vec::new();
+        for (index, board) in self.boards.iter().enumerate() {
+            let solver = Solver::new(board.clone(), dictionary, 1);
+            let mut board_entries: Vec<ScoredEntry> = self
+                .entries
+                .iter()
+                .filter(|e| e.board == index)
+                .map(|entry| {
+                    let words: Vec<&str> = entry.words.iter().map(String::as_str).collect();
+                    ScoredEntry {
+                        entry: entry.clone(),
+                        solution: solver.validate_solution(&words).map_err(|e| e.to_string()),
+                        points: 0,
+                    }
+                })
+                .collect();
+
+            let key = |scored: &ScoredEntry| scored.solution.as_ref().ok().map(|s| ruleset.rank_key(s));
+            let valid = board_entries.iter().filter(|e| e.solution.is_ok()).count();
+            let keys: Vec<_> = board_entries.iter().filter_map(key).collect();
+            for scored in board_entries.iter_mut() {
+                if let Some(own) = key(scored) {
+                    let better = keys.iter().filter(|k| **k < own).count();
+                    scored.points = valid - better;
+                }
+            }
+            // Stable, so tied entries stay in the order they were sent
+            board_entries.sort_by_key(|e| std::cmp::Reverse(e.points));
+            scored.extend(board_entries);
+        }
+
+        let mut standings: HashMap<&str, Standing> = HashMap::new();
+        for scored in &scored {
+            let player = scored.entry.player.as_str();
+            let standing = standings.entry(player).or_insert_with(|| Standing {
+                player: player.to_string(),
+                points: 0,
+                boards_solved: 0,
+            });
+            standing.points += scored.points;
+            standing.boards_solved += usize::from(scored.solution.is_ok());
+        }
+        let mut standings: Vec<Standing> = standings.into_values().collect();
+        standings.sort_by(|a, b| {
+            b.points.cmp(&a.points).then(b.boards_solved.cmp(&a.boards_solved)).then(a.player.cmp(&b.player))
+        });
+
+        Leaderboard { entries: scored, standings }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENTRIES: &str = "\
+# Week 1
+board yfa,otk,lgw,rni
+board vyq,fig,ote,xlu
+
+alice 1 forklift-twangy
+bob 1 now-wakf-fragility
+carol 1 forklift-twangy
+dave 1 fork-twangy
+bob 2 foxglove-eye-equity
+alice 2 foxglove-equity
+bob 2 foxglove-equity
+";
+
+    fn dictionary() -> Dictionary {
+        Dictionary::from_text(
+            "forklift 20\ntwangy 16\nnow 30\nwakf 5\nfragility 18\nfoxglove 16\nequity 20\neye 25\n",
+        )
+    }
+
+    #[test]
+    fn test_parse() {
+        let tournament = Tournament::parse(ENTRIES).unwrap();
+        assert_eq!(tournament.boards.len(), 2);
+        // Bob's second answer to board 2 replaced his first
+        assert_eq!(tournament.entries.len(), 6);
+        let bob = tournament.entries.iter().find(|e| e.player == "bob" && e.board == 1).unwrap();
+        assert_eq!(bob.words, vec!["foxglove", "equity"]);
+
+        assert!(Tournament::parse("alice 1 forklift-twangy\n").is_err());
+        assert!(Tournament::parse("board yfa,otk,lgw,rni\nalice forklift-twangy\n").is_err());
+        assert!(Tournament::parse("board yfa,otk\n").is_err());
+    }
+
+    #[test]
+    fn test_score() {
+        let tournament = Tournament::parse(ENTRIES).unwrap();
+        let leaderboard = tournament.score(&dictionary(), Ruleset::FewestWords);
+
+        let points: Vec<(&str, usize, usize)> =
+            leaderboard.entries.iter().map(|e| (e.entry.player.as_str(), e.entry.board, e.points)).collect();
+        // Three valid answers to board 1: alice and carol tie with two words, ahead of bob's three
+        assert_eq!(
+            points,
+            vec![("alice", 0, 3), ("carol", 0, 3), ("bob", 0, 1), ("dave", 0, 0), ("alice", 1, 2), ("bob", 1, 2)]
+        );
+        assert!(leaderboard.entries[3].solution.is_err());
+
+        let standings: Vec<(&str, usize, usize)> =
+            leaderboard.standings.iter().map(|s| (s.player.as_str(), s.points, s.boards_solved)).collect();
+        assert_eq!(standings, vec![("alice", 5, 2), ("bob", 3, 2), ("carol", 3, 1), ("dave", 0, 0)]);
+
+        // Bob's WAKF is the rarest word anyone played
+        let leaderboard = tournament.score(&dictionary(), Ruleset::RarestWords);
+        assert_eq!(leaderboard.entries[0].entry.player, "bob");
+        assert_eq!(leaderboard.entries[0].points, 3);
+    }
+}
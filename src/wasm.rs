@@ -1,14 +1,20 @@
 use wasm_bindgen::prelude::*;
 use wasm_bindgen_futures::future_to_promise;
 use crate::board::Board;
-use crate::dictionary::Dictionary;
-use crate::solver::Solver;
-use std::sync::{OnceLock, Mutex};
+use crate::cache::{cache_key, SolveCache};
+use crate::dawg::Dawg;
+use crate::dictionary::{Dictionary, Word};
+use crate::error::{LetterBouncedError, Result};
+use crate::generator::GeneratorConfig;
+use crate::solver::{Solution, Solver, SolverConfig, SolverPack, FIRST_WORDS_PER_YIELD, NODES_PER_YIELD};
+use std::collections::HashMap;
+use std::sync::{OnceLock, Mutex, RwLock};
 use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use js_sys::Promise;
 
 // Import the `console.log` function from the browser's Web API
+#[cfg(not(feature = "node"))]
 #[wasm_bindgen]
 extern "C" {
     #[wasm_bindgen(js_namespace = console)]
@@ -16,12 +22,41 @@ extern "C" {
 }
 
 // Define a macro to make console logging easier
+#[cfg(not(feature = "node"))]
 macro_rules! console_log {
     ($($t:tt)*) => (log(&format_args!($($t)*).to_string()))
 }
 
-// Global dictionary storage (wrapped in Arc for sharing across tasks)
-static GLOBAL_DICTIONARY: OnceLock<Arc<Dictionary>> = OnceLock::new();
+// Node builds are for backends and serverless functions, whose logs shouldn't fill up with the solver's chatter
+#[cfg(feature = "node")]
+macro_rules! console_log {
+    ($($t:tt)*) => {{
+        let _ = format_args!($($t)*);
+    }};
+}
+
+// Node's synchronous file reading, for loading the dictionary from a path
+#[cfg(feature = "node")]
+#[wasm_bindgen(module = "fs")]
+extern "C" {
+    #[wasm_bindgen(catch, js_name = readFileSync)]
+    fn read_file_sync(path: &str) -> std::result::Result<js_sys::Uint8Array, JsValue>;
+}
+
+// Errors reach JavaScript as their message
+impl From<LetterBouncedError> for JsValue {
+    fn from(error: LetterBouncedError) -> Self {
+        JsValue::from_str(&error.to_string())
+    }
+}
+
+// Global dictionary storage (wrapped in Arc for sharing across tasks). Solves take their own Arc, so swapping
+// dictionaries doesn't pull one out from under a running solve.
+static GLOBAL_DICTIONARY: RwLock<Option<Arc<Dictionary>>> = RwLock::new(None);
+
+fn global_dictionary() -> Option<Arc<Dictionary>> {
+    GLOBAL_DICTIONARY.read().unwrap().clone()
+}
 
 // Current solve task state
 #[derive(Clone, PartialEq)]
@@ -37,23 +72,133 @@ struct SolveTask {
 
 static CURRENT_SOLVE: OnceLock<Mutex<Option<SolveTask>>> = OnceLock::new();
 
+// Independent solves started with `start_solve`, by session id
+#[derive(Default)]
+struct SolveSession {
+    cancel_flag: Arc<AtomicBool>,
+    // "solution:score" strings found since the last poll
+    pending: Vec<String>,
+    done: bool,
+    error: Option<String>,
+}
+
+// How many recent `solve_game` results to keep, so re-solving a board (say with a different max_solutions, and
+// back again) is instant
+const SOLVE_CACHE_CAPACITY: usize = 16;
+
+static SOLVE_CACHE: Mutex<Option<SolveCache>> = Mutex::new(None);
+
+fn with_solve_cache<T>(f: impl FnOnce(&mut SolveCache) -> T) -> T {
+    f(SOLVE_CACHE.lock().unwrap().get_or_insert_with(|| SolveCache::new(SOLVE_CACHE_CAPACITY)))
+}
+
+static SOLVE_SESSIONS: Mutex<Option<HashMap<u32, SolveSession>>> = Mutex::new(None);
+static NEXT_SESSION_ID: AtomicU32 = AtomicU32::new(1);
+
+fn with_session<T>(session_id: u32, f: impl FnOnce(&mut SolveSession) -> T) -> Option<T> {
+    SOLVE_SESSIONS
+        .lock()
+        .unwrap()
+        .get_or_insert_with(HashMap::new)
+        .get_mut(&session_id)
+        .map(f)
+}
+
 #[wasm_bindgen]
-pub fn initialize_dictionary(dictionary_data: Vec<u8>) -> Result<(), String> {
+pub fn initialize_dictionary(dictionary_data: Vec<u8>) -> Result<()> {
     console_log!("Initializing global dictionary from {} bytes", dictionary_data.len());
 
+    let dictionary_data = decompress_dictionary(dictionary_data)?;
     let dictionary = Dictionary::from_bytes(&dictionary_data)?;
-    console_log!("Parsed dictionary with {} words", dictionary.words.len());
+    console_log!("Parsed dictionary with {} words, skipping {} bad lines", dictionary.words().len(), dictionary.warnings.len());
+
+    set_global_dictionary(dictionary)
+}
+
+/// Initialize from a dictionary file, in any format `initialize_dictionary` accepts. Node builds only; a Buffer
+/// already read can be passed straight to `initialize_dictionary`.
+#[cfg(feature = "node")]
+#[wasm_bindgen]
+pub fn initialize_dictionary_from_path(path: &str) -> Result<()> {
+    let dictionary_data = read_file_sync(path).map_err(|e| {
+        let message = js_sys::Error::from(e).message();
+        LetterBouncedError::Io(std::io::Error::other(format!("Can't read {}: {}", path, message)))
+    })?;
+    initialize_dictionary(dictionary_data.to_vec())
+}
+
+/// Initialize from a cached dictionary plus a patch bringing it up to date, so updates don't require
+/// downloading the whole dictionary again.
+#[wasm_bindgen]
+pub fn initialize_patched_dictionary(dictionary_data: Vec<u8>, patch_data: Vec<u8>) -> Result<()> {
+    console_log!("Initializing global dictionary from {} bytes plus {} byte patch", dictionary_data.len(), patch_data.len());
+
+    let dictionary_data = decompress_dictionary(dictionary_data)?;
+    let mut dictionary = Dictionary::from_bytes(&dictionary_data)?;
+    dictionary.apply_patch(&patch_data)?;
+    console_log!("Patched dictionary has {} words", dictionary.words().len());
+
+    set_global_dictionary(dictionary)
+}
+
+/// Swap in a different dictionary (e.g. when the player switches between British and American wordlists),
+/// cancelling any `solve_game` or `solve_game_streaming` solve in progress. Sessions from `start_solve`, and the
+/// worker's solve, finish with the dictionary they started with. Accepts the same formats as
+/// `initialize_dictionary`, and also works as a first initialization.
+#[wasm_bindgen]
+pub fn replace_dictionary(dictionary_data: Vec<u8>) -> Result<()> {
+    console_log!("Replacing global dictionary from {} bytes", dictionary_data.len());
+
+    let dictionary_data = decompress_dictionary(dictionary_data)?;
+    let dictionary = Dictionary::from_bytes(&dictionary_data)?;
+    console_log!("Parsed dictionary with {} words, skipping {} bad lines", dictionary.words().len(), dictionary.warnings.len());
 
-    // Initialize the current solve tracker
     let _ = CURRENT_SOLVE.set(Mutex::new(None));
+    cancel_current_solve();
+    *GLOBAL_DICTIONARY.write().unwrap() = Some(Arc::new(dictionary));
+    Ok(())
+}
 
-    match GLOBAL_DICTIONARY.set(Arc::new(dictionary)) {
-        Ok(()) => {
-            console_log!("Global dictionary initialized successfully");
-            Ok(())
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/**
+ * The dictionary may arrive gzip- or brotli-compressed, so the page can fetch a much smaller file. Gzip and the
+ * binary dictionary format have magic bytes; brotli streams don't, so anything else is tried as brotli, and taken as
+ * it is, a text dictionary, if it doesn't decode.
+ */
+fn decompress_dictionary(data: Vec<u8>) -> Result<Vec<u8>> {
+    use std::io::Read;
+
+    if data.starts_with(&GZIP_MAGIC) {
+        let mut text = Vec::new();
+        flate2::read::GzDecoder::new(data.as_slice()).read_to_end(&mut text)?;
+        console_log!("Decompressed gzip dictionary to {} bytes", text.len());
+        return Ok(text);
+    }
+    if data.starts_with(crate::mapped::MAGIC) {
+        return Ok(data);
+    }
+    let mut text = Vec::new();
+    match brotli_decompressor::Decompressor::new(data.as_slice(), 4096).read_to_end(&mut text) {
+        Ok(_) => {
+            console_log!("Decompressed brotli dictionary to {} bytes", text.len());
+            Ok(text)
         }
-        Err(_) => Err("Dictionary already initialized".to_string())
+        Err(_) => Ok(data),
+    }
+}
+
+fn set_global_dictionary(dictionary: Dictionary) -> Result<()> {
+    // Initialize the current solve tracker
+    let _ = CURRENT_SOLVE.set(Mutex::new(None));
+
+    let mut global = GLOBAL_DICTIONARY.write().unwrap();
+    if global.is_some() {
+        return Err(LetterBouncedError::DictionaryAlreadyInitialized);
     }
+    *global = Some(Arc::new(dictionary));
+    console_log!("Global dictionary initialized successfully");
+    Ok(())
 }
 
 #[wasm_bindgen]
@@ -62,7 +207,7 @@ pub fn solve_game(game_sides: Vec<String>, max_solutions: u16) -> Promise {
 
     future_to_promise(async move {
         // Check if dictionary is initialized
-        let dictionary = match GLOBAL_DICTIONARY.get() {
+        let dictionary = match global_dictionary() {
             Some(dict) => dict,
             None => {
                 console_log!("Error: Dictionary not initialized");
@@ -70,38 +215,10 @@ pub fn solve_game(game_sides: Vec<String>, max_solutions: u16) -> Promise {
             }
         };
 
-        let new_params = SolveParams {
+        let cancel_flag = start_solve_task(SolveParams {
             sides: game_sides.clone(),
             max_solutions,
-        };
-
-        // Check if we need to cancel an existing solve
-        let cancel_flag = if let Some(solve_mutex) = CURRENT_SOLVE.get() {
-            let mut current = solve_mutex.lock().unwrap();
-
-            // If there's a current task with different params, cancel it
-            if let Some(ref task) = *current {
-                if task.params != new_params {
-                    console_log!("Cancelling previous solve with different params");
-                    task.cancel_flag.store(true, Ordering::Relaxed);
-                } else {
-                    console_log!("Solve already in progress with same params, rejecting duplicate");
-                    return Err(JsValue::from_str("Solve already in progress"));
-                }
-            }
-
-            // Create new cancel flag and task
-            let cancel_flag = Arc::new(AtomicBool::new(false));
-            *current = Some(SolveTask {
-                params: new_params.clone(),
-                cancel_flag: cancel_flag.clone(),
-            });
-
-            cancel_flag
-        } else {
-            console_log!("Error: CURRENT_SOLVE not initialized");
-            return Err(JsValue::from_str("Solver not initialized"));
-        };
+        })?;
 
         // Create the board
         let board = match Board::from_sides(game_sides) {
@@ -110,62 +227,194 @@ pub fn solve_game(game_sides: Vec<String>, max_solutions: u16) -> Promise {
                 console_log!("Error creating board: {}", e);
 
                 // Clear current task since we failed
-                if let Some(solve_mutex) = CURRENT_SOLVE.get() {
-                    *solve_mutex.lock().unwrap() = None;
-                }
+                finish_solve_task(&cancel_flag);
 
                 return Err(JsValue::from_str(&e.to_string()));
             }
         };
 
-        // Clone the Arc (cheap) for the async task
-        let dictionary_arc = dictionary.clone();
+        if let Err(e) = board.is_solvable(&dictionary).into_result() {
+            finish_solve_task(&cancel_flag);
+            return Err(e.into());
+        }
 
-        console_log!("Starting solve task");
+        let config = SolverConfig::new().max_solutions(max_solutions);
+        let key = cache_key(&board, &dictionary, &config);
+        let solutions = match with_solve_cache(|cache| cache.get(&key, &dictionary)) {
+            Some(solutions) => {
+                console_log!("Found the solutions in the cache");
+                solutions
+            }
+            None => {
+                console_log!("Starting solve task");
 
-        let solver = Solver::new(board, &dictionary_arc, max_solutions);
-        let solutions = solver.solve_cancellable(Some(cancel_flag.clone()));
+                // Searching a slice at a time lets the worker handle messages, like a cancel, while it solves
+                let solver = Solver::with_config(board, &dictionary, config);
+                let solutions = solver
+                    .solve_async(Some(cancel_flag.clone()), yield_to_event_loop, |_, _| {})
+                    .await
+                    .solutions;
 
-        // Check if we were cancelled
-        if cancel_flag.load(Ordering::Relaxed) {
-            console_log!("Solve was cancelled");
+                // Check if we were cancelled
+                if cancel_flag.load(Ordering::Relaxed) {
+                    console_log!("Solve was cancelled");
 
-            // Clear current task
-            if let Some(solve_mutex) = CURRENT_SOLVE.get() {
-                let mut current = solve_mutex.lock().unwrap();
-                if let Some(ref task) = *current {
-                    if Arc::ptr_eq(&task.cancel_flag, &cancel_flag) {
-                        *current = None;
-                    }
+                    finish_solve_task(&cancel_flag);
+                    return Err(JsValue::from_str("Cancelled"));
                 }
+                with_solve_cache(|cache| cache.insert(key, &solutions));
+                solutions
             }
-
-            return Err(JsValue::from_str("Cancelled"));
-        }
+        };
 
         console_log!("Found {} solutions", solutions.len());
 
         // Convert solutions to JS array
         let js_array = js_sys::Array::new();
         for solution in &solutions {
-            let solution_str = format!("{}:{}", solution.to_string(), solution.score);
+            let solution_str = format!("{}:{}", solution, solution.score);
             js_array.push(&JsValue::from_str(&solution_str));
         }
 
-        // Clear current task
-        if let Some(solve_mutex) = CURRENT_SOLVE.get() {
-            let mut current = solve_mutex.lock().unwrap();
-            if let Some(ref task) = *current {
-                if Arc::ptr_eq(&task.cancel_flag, &cancel_flag) {
-                    *current = None;
+        finish_solve_task(&cancel_flag);
+        Ok(js_array.into())
+    })
+}
+
+/// Solve a board from a solver pack, the JSON written by `solve --save-pack`, without needing a dictionary. Returns
+/// "solution:score" strings, best first.
+#[wasm_bindgen]
+pub fn solve_pack(pack_json: &str, max_solutions: u16) -> Result<Vec<String>> {
+    let pack: SolverPack =
+        serde_json::from_str(pack_json).map_err(|e| LetterBouncedError::InvalidPack(e.to_string()))?;
+    let solver = Solver::from_pack(&pack, SolverConfig::new().max_solutions(max_solutions))?;
+    let solutions = solver.solve();
+    console_log!("Found {} solutions from a pack of {} words", solutions.len(), pack.words.len());
+    Ok(solutions.iter().map(|solution| format!("{}:{}", solution, solution.score)).collect())
+}
+
+/// Forget the solves `solve_game` has cached, e.g. to free memory. Swapping dictionaries doesn't need this, since
+/// a solve with a different dictionary never matches an old one.
+#[wasm_bindgen]
+pub fn clear_solve_cache() {
+    *SOLVE_CACHE.lock().unwrap() = None;
+}
+
+/// Like `solve_game`, but calls `on_batch` with an array of "solution:score" strings whenever a slice of the
+/// search finds some, giving the event loop a turn between slices. Solutions arrive in search order, not
+/// sorted. The promise resolves once the search is over.
+#[wasm_bindgen]
+pub fn solve_game_streaming(game_sides: Vec<String>, max_solutions: u16, on_batch: js_sys::Function) -> Promise {
+    console_log!("Streaming solve requested with {} sides", game_sides.len());
+
+    future_to_promise(async move {
+        let dictionary = match global_dictionary() {
+            Some(dict) => dict,
+            None => return Err(JsValue::from_str("Dictionary not initialized")),
+        };
+        let cancel_flag = start_solve_task(SolveParams {
+            sides: game_sides.clone(),
+            max_solutions,
+        })?;
+
+        let board = match Board::from_sides(game_sides) {
+            Ok(board) => board,
+            Err(e) => {
+                finish_solve_task(&cancel_flag);
+                return Err(e.into());
+            }
+        };
+        if let Err(e) = board.is_solvable(&dictionary).into_result() {
+            finish_solve_task(&cancel_flag);
+            return Err(e.into());
+        }
+        let solver = Solver::new(board, &dictionary, max_solutions);
+        let mut solve = solver.solve_incrementally();
+        let mut found = 0;
+
+        while let Some(batch) = solve.next_batch(FIRST_WORDS_PER_YIELD, NODES_PER_YIELD) {
+            if !batch.is_empty() {
+                found += batch.len();
+                let js_array = js_sys::Array::new();
+                for solution in &batch {
+                    js_array.push(&JsValue::from_str(&format!("{}:{}", solution, solution.score)));
+                }
+                if let Err(e) = on_batch.call1(&JsValue::NULL, &js_array) {
+                    finish_solve_task(&cancel_flag);
+                    return Err(e);
                 }
             }
+
+            yield_to_event_loop().await;
+            if cancel_flag.load(Ordering::Relaxed) {
+                console_log!("Streaming solve was cancelled");
+                finish_solve_task(&cancel_flag);
+                return Err(JsValue::from_str("Cancelled"));
+            }
         }
 
-        Ok(js_array.into())
+        console_log!("Streamed {} solutions", found);
+        finish_solve_task(&cancel_flag);
+        Ok(JsValue::from(found as u32))
     })
 }
 
+// Register a new solve, cancelling any running one with different parameters. Fails if the same solve is
+// already running.
+fn start_solve_task(params: SolveParams) -> std::result::Result<Arc<AtomicBool>, JsValue> {
+    let Some(solve_mutex) = CURRENT_SOLVE.get() else {
+        console_log!("Error: CURRENT_SOLVE not initialized");
+        return Err(JsValue::from_str("Solver not initialized"));
+    };
+    let mut current = solve_mutex.lock().unwrap();
+
+    // If there's a current task with different params, cancel it
+    if let Some(ref task) = *current {
+        if task.params != params {
+            console_log!("Cancelling previous solve with different params");
+            task.cancel_flag.store(true, Ordering::Relaxed);
+        } else {
+            console_log!("Solve already in progress with same params, rejecting duplicate");
+            return Err(JsValue::from_str("Solve already in progress"));
+        }
+    }
+
+    let cancel_flag = Arc::new(AtomicBool::new(false));
+    *current = Some(SolveTask {
+        params,
+        cancel_flag: cancel_flag.clone(),
+    });
+    Ok(cancel_flag)
+}
+
+// Forget the current task, if it's still this one
+fn finish_solve_task(cancel_flag: &Arc<AtomicBool>) {
+    if let Some(solve_mutex) = CURRENT_SOLVE.get() {
+        let mut current = solve_mutex.lock().unwrap();
+        if let Some(ref task) = *current {
+            if Arc::ptr_eq(&task.cancel_flag, cancel_flag) {
+                *current = None;
+            }
+        }
+    }
+}
+
+// Resolve on a fresh macrotask (setTimeout 0), so pending messages and rendering get a turn
+async fn yield_to_event_loop() {
+    let promise = Promise::new(&mut |resolve, _reject| {
+        let set_timeout = js_sys::Reflect::get(&js_sys::global(), &JsValue::from_str("setTimeout"))
+            .ok()
+            .and_then(|f| f.dyn_into::<js_sys::Function>().ok());
+        let scheduled = set_timeout.is_some_and(|set_timeout| {
+            set_timeout.call2(&JsValue::NULL, &resolve, &JsValue::from(0)).is_ok()
+        });
+        if !scheduled {
+            let _ = resolve.call0(&JsValue::NULL);
+        }
+    });
+    let _ = wasm_bindgen_futures::JsFuture::from(promise).await;
+}
+
 #[wasm_bindgen]
 pub fn cancel_current_solve() {
     if let Some(solve_mutex) = CURRENT_SOLVE.get() {
@@ -180,4 +429,391 @@ pub fn cancel_current_solve() {
     } else {
         console_log!("Warning: Solver not initialized");
     }
-}
\ No newline at end of file
+}
+
+/// Start a solve which runs alongside any others, a slice at a time between turns of the event loop, and
+/// return its session id. Collect its solutions with `poll_solve`; stop it with `cancel_solve`.
+#[wasm_bindgen]
+pub fn start_solve(game_sides: Vec<String>, max_solutions: u16) -> Result<u32> {
+    let dictionary = global_dictionary().ok_or(LetterBouncedError::DictionaryNotInitialized)?;
+    let board = Board::from_sides(game_sides)?;
+    board.is_solvable(&dictionary).into_result()?;
+
+    let session_id = NEXT_SESSION_ID.fetch_add(1, Ordering::Relaxed);
+    let cancel_flag = Arc::new(AtomicBool::new(false));
+    SOLVE_SESSIONS.lock().unwrap().get_or_insert_with(HashMap::new).insert(
+        session_id,
+        SolveSession {
+            cancel_flag: cancel_flag.clone(),
+            ..Default::default()
+        },
+    );
+    console_log!("Starting solve session {}", session_id);
+
+    wasm_bindgen_futures::spawn_local(async move {
+        let solver = Solver::new(board, &dictionary, max_solutions);
+        let mut solve = solver.solve_incrementally();
+
+        while let Some(batch) = solve.next_batch(FIRST_WORDS_PER_YIELD, NODES_PER_YIELD) {
+            let found = batch.iter().map(|solution| format!("{}:{}", solution, solution.score));
+            if with_session(session_id, |session| session.pending.extend(found)).is_none() {
+                return;
+            }
+            yield_to_event_loop().await;
+            if cancel_flag.load(Ordering::Relaxed) {
+                return;
+            }
+        }
+
+        with_session(session_id, |session| {
+            session.done = true;
+            if solve.timed_out() {
+                session.error = Some("Timed out".to_string());
+            }
+        });
+    });
+
+    Ok(session_id)
+}
+
+/// The solutions a session has found since the last poll, as `{solutions: ["solution:score", ...], done}`.
+/// Once a finished session has been polled, its id is forgotten.
+#[wasm_bindgen]
+pub fn poll_solve(session_id: u32) -> std::result::Result<JsValue, JsValue> {
+    let mut sessions = SOLVE_SESSIONS.lock().unwrap();
+    let sessions = sessions.get_or_insert_with(HashMap::new);
+    let Some(session) = sessions.get_mut(&session_id) else {
+        return Err(JsValue::from_str(&format!("No solve session {}", session_id)));
+    };
+
+    let solutions = js_sys::Array::new();
+    for solution in session.pending.drain(..) {
+        solutions.push(&JsValue::from_str(&solution));
+    }
+    let result = js_sys::Object::new();
+    js_sys::Reflect::set(&result, &JsValue::from_str("solutions"), &solutions)?;
+    js_sys::Reflect::set(&result, &JsValue::from_str("done"), &JsValue::from_bool(session.done))?;
+    if let Some(error) = &session.error {
+        js_sys::Reflect::set(&result, &JsValue::from_str("error"), &JsValue::from_str(error))?;
+    }
+
+    if session.done {
+        sessions.remove(&session_id);
+    }
+    Ok(result.into())
+}
+
+/// Stop a session started with `start_solve` and forget it. Unknown ids are ignored.
+#[wasm_bindgen]
+pub fn cancel_solve(session_id: u32) {
+    if let Some(session) = SOLVE_SESSIONS.lock().unwrap().get_or_insert_with(HashMap::new).remove(&session_id) {
+        console_log!("Cancelling solve session {}", session_id);
+        session.cancel_flag.store(true, Ordering::Relaxed);
+    }
+}
+
+/*
+ * Worker mode. `run_worker` takes over a dedicated Web Worker's message loop, so the page only has to post messages
+ * and never blocks on a solve. build-web.sh writes the few lines of JavaScript which start it (solver-worker.js).
+ *
+ * Messages in, each `{type, solveId?, payload?}`:
+ *   INIT    payload.dictionaryData: dictionary bytes in any format initialize_dictionary accepts
+ *   SOLVE   payload.sides, payload.maxSolutions (default 10000). Starting a solve cancels the one running.
+ *   CANCEL  stop the running solve
+ * Messages out:
+ *   READY      the dictionary is loaded
+ *   PROGRESS   solveId, solutions found by the latest slice of the search (unsorted), totalCount so far
+ *   COMPLETE   solveId, every solution, best first, totalCount, and duration in milliseconds
+ *   CANCELLED  solveId
+ *   ERROR      error, and the solveId if a solve failed
+ * Solutions are "solution:score" strings, as from solve_game.
+ */
+
+const WORKER_DEFAULT_MAX_SOLUTIONS: u16 = 10000;
+
+// The worker's running solve, if any
+static WORKER_SOLVE: Mutex<Option<Arc<AtomicBool>>> = Mutex::new(None);
+
+/// Handle a dedicated Web Worker's messages from now on. `queued` holds any message events which arrived while the
+/// wasm was loading; they're handled first, in order.
+#[wasm_bindgen]
+pub fn run_worker(queued: js_sys::Array) -> std::result::Result<(), JsValue> {
+    let handler = Closure::<dyn FnMut(JsValue)>::new(|event: JsValue| handle_worker_event(&event));
+    js_sys::Reflect::set(&js_sys::global(), &JsValue::from_str("onmessage"), handler.as_ref())?;
+    // The handler lives as long as the worker
+    handler.forget();
+
+    for event in queued.iter() {
+        handle_worker_event(&event);
+    }
+    Ok(())
+}
+
+fn handle_worker_event(event: &JsValue) {
+    let get = |target: &JsValue, key: &str| js_sys::Reflect::get(target, &JsValue::from_str(key)).unwrap_or_default();
+    let data = get(event, "data");
+    let solve_id = get(&data, "solveId");
+    let payload = get(&data, "payload");
+
+    match get(&data, "type").as_string().as_deref() {
+        Some("INIT") => {
+            let dictionary_data = get(&payload, "dictionaryData");
+            let result = if dictionary_data.is_undefined() {
+                Ok(())
+            } else {
+                replace_dictionary(js_sys::Uint8Array::new(&dictionary_data).to_vec())
+            };
+            match result {
+                Ok(()) => post_worker_message("READY", &JsValue::UNDEFINED, &[]),
+                Err(e) => post_worker_message("ERROR", &JsValue::UNDEFINED, &[("error", e.into())]),
+            }
+        }
+        Some("SOLVE") => {
+            let sides = js_sys::Array::from(&get(&payload, "sides")).iter().filter_map(|side| side.as_string()).collect();
+            let max_solutions = get(&payload, "maxSolutions")
+                .as_f64()
+                .map_or(WORKER_DEFAULT_MAX_SOLUTIONS, |max| max.clamp(1.0, u16::MAX as f64) as u16);
+            let cancel_flag = Arc::new(AtomicBool::new(false));
+            if let Some(running) = WORKER_SOLVE.lock().unwrap().replace(cancel_flag.clone()) {
+                running.store(true, Ordering::Relaxed);
+            }
+            wasm_bindgen_futures::spawn_local(worker_solve(solve_id, sides, max_solutions, cancel_flag));
+        }
+        Some("CANCEL") => {
+            if let Some(running) = WORKER_SOLVE.lock().unwrap().take() {
+                running.store(true, Ordering::Relaxed);
+            }
+        }
+        other => {
+            let error = format!("Unknown message type {:?}", other.unwrap_or("(none)"));
+            post_worker_message("ERROR", &solve_id, &[("error", JsValue::from_str(&error))]);
+        }
+    }
+}
+
+async fn worker_solve(solve_id: JsValue, sides: Vec<String>, max_solutions: u16, cancel_flag: Arc<AtomicBool>) {
+    let started = js_sys::Date::now();
+    let prepared = global_dictionary()
+        .ok_or(LetterBouncedError::DictionaryNotInitialized)
+        .and_then(|dictionary| {
+            let board = Board::from_sides(sides)?;
+            board.is_solvable(&dictionary).into_result()?;
+            Ok((dictionary, board))
+        });
+    let (dictionary, board) = match prepared {
+        Ok(prepared) => prepared,
+        Err(e) => {
+            finish_worker_solve(&cancel_flag);
+            post_worker_message("ERROR", &solve_id, &[("error", e.into())]);
+            return;
+        }
+    };
+
+    let solver = Solver::new(board, &dictionary, max_solutions);
+    let outcome = solver
+        .solve_async(Some(cancel_flag.clone()), yield_to_event_loop, |batch, found| {
+            post_worker_message(
+                "PROGRESS",
+                &solve_id,
+                &[("solutions", solution_strings(batch).into()), ("totalCount", JsValue::from(found as u32))],
+            );
+        })
+        .await;
+    finish_worker_solve(&cancel_flag);
+    if cancel_flag.load(Ordering::Relaxed) {
+        post_worker_message("CANCELLED", &solve_id, &[]);
+        return;
+    }
+
+    post_worker_message(
+        "COMPLETE",
+        &solve_id,
+        &[
+            ("solutions", solution_strings(&outcome.solutions).into()),
+            ("totalCount", JsValue::from(outcome.solutions.len() as u32)),
+            ("duration", JsValue::from((js_sys::Date::now() - started).round())),
+        ],
+    );
+}
+
+// Forget the worker's solve, unless a newer one has already replaced it
+fn finish_worker_solve(cancel_flag: &Arc<AtomicBool>) {
+    let mut running = WORKER_SOLVE.lock().unwrap();
+    if running.as_ref().is_some_and(|flag| Arc::ptr_eq(flag, cancel_flag)) {
+        *running = None;
+    }
+}
+
+fn solution_strings(solutions: &[Solution]) -> js_sys::Array {
+    solutions
+        .iter()
+        .map(|solution| JsValue::from_str(&format!("{}:{}", solution, solution.score)))
+        .collect()
+}
+
+// Post `{type, solveId, ...fields}` to the page which owns this worker
+fn post_worker_message(kind: &str, solve_id: &JsValue, fields: &[(&str, JsValue)]) {
+    let message = js_sys::Object::new();
+    let set = |key: &str, value: &JsValue| {
+        let _ = js_sys::Reflect::set(&message, &JsValue::from_str(key), value);
+    };
+    set("type", &JsValue::from_str(kind));
+    if !solve_id.is_undefined() {
+        set("solveId", solve_id);
+    }
+    for (key, value) in fields {
+        set(key, value);
+    }
+
+    let global = js_sys::global();
+    let post_message = js_sys::Reflect::get(&global, &JsValue::from_str("postMessage"))
+        .ok()
+        .and_then(|f| f.dyn_into::<js_sys::Function>().ok());
+    match post_message {
+        Some(post_message) => {
+            let _ = post_message.call1(&global, &message);
+        }
+        None => console_log!("Can't post {} message: not running in a worker", kind),
+    }
+}
+
+/// A new practice board, as its four sides, made from `seed` with the loaded dictionary. The same seed always
+/// gives the same board.
+#[wasm_bindgen]
+pub fn generate_board(seed: u32, side_length: usize, min_two_word_solutions: u16) -> Result<Vec<String>> {
+    let dictionary = global_dictionary().ok_or(LetterBouncedError::DictionaryNotInitialized)?;
+    let config = GeneratorConfig::new()
+        .side_length(side_length)
+        .min_two_word_solutions(min_two_word_solutions);
+    let board = crate::generator::generate_board(&dictionary, seed as u64, &config)?;
+    console_log!("Generated board {}", board.sides.join(","));
+    Ok(board.sides)
+}
+
+/// For colour-coding a board: one `{letter, words, hard}` object per board letter, in board order, giving how
+/// many playable words contain the letter and in how many solutions only the rarest word covers it.
+#[wasm_bindgen]
+pub fn letter_coverage(game_sides: Vec<String>) -> std::result::Result<JsValue, JsValue> {
+    let dictionary = global_dictionary().ok_or(LetterBouncedError::DictionaryNotInitialized)?;
+    let board = Board::from_sides(game_sides)?;
+
+    let js_array = js_sys::Array::new();
+    for coverage in crate::analysis::letter_coverage(&board, &dictionary) {
+        let entry = js_sys::Object::new();
+        js_sys::Reflect::set(&entry, &JsValue::from_str("letter"), &JsValue::from_str(&coverage.letter.to_string()))?;
+        js_sys::Reflect::set(&entry, &JsValue::from_str("words"), &JsValue::from(coverage.words_containing as u32))?;
+        js_sys::Reflect::set(&entry, &JsValue::from_str("hard"), &JsValue::from(coverage.hard_in_solutions as u32))?;
+        js_array.push(&entry);
+    }
+    Ok(js_array.into())
+}
+
+/// The board's par, as `{words, solutions, sampled}`: the fewest words it can be solved in, and how many solutions
+/// of that length there are (at least that many if `sampled`). Null if it can't be solved in a sensible number.
+#[wasm_bindgen]
+pub fn par(game_sides: Vec<String>) -> std::result::Result<JsValue, JsValue> {
+    let dictionary = global_dictionary().ok_or(LetterBouncedError::DictionaryNotInitialized)?;
+    let board = Board::from_sides(game_sides)?;
+
+    let Some(par) = crate::analysis::par(&board, &dictionary) else {
+        return Ok(JsValue::NULL);
+    };
+    let entry = js_sys::Object::new();
+    js_sys::Reflect::set(&entry, &JsValue::from_str("words"), &JsValue::from(par.words as u32))?;
+    js_sys::Reflect::set(&entry, &JsValue::from_str("solutions"), &JsValue::from(par.solutions as u32))?;
+    js_sys::Reflect::set(&entry, &JsValue::from_str("sampled"), &JsValue::from(par.is_sampled()))?;
+    Ok(entry.into())
+}
+
+/// Check a word as the player types it: null if it can be played on the board (whether or not it's in the
+/// dictionary), or else an object with the `message` to show and the `start` and `end` (exclusive) of the letters
+/// to highlight.
+#[wasm_bindgen]
+pub fn check_word(game_sides: Vec<String>, word: &str) -> std::result::Result<JsValue, JsValue> {
+    let board = Board::from_sides(game_sides)?;
+    let Err(error) = board.is_word_playable(word) else {
+        return Ok(JsValue::NULL);
+    };
+    let letters = error.letters();
+    let entry = js_sys::Object::new();
+    js_sys::Reflect::set(&entry, &JsValue::from_str("message"), &JsValue::from_str(&error.to_string()))?;
+    js_sys::Reflect::set(&entry, &JsValue::from_str("start"), &JsValue::from(letters.start as u32))?;
+    js_sys::Reflect::set(&entry, &JsValue::from_str("end"), &JsValue::from(letters.end as u32))?;
+    Ok(entry.into())
+}
+
+// The prefix index `next_letters` uses, built from the playable words of the board it was last asked about
+struct TypingIndex {
+    sides: Vec<String>,
+    dictionary: Arc<Dictionary>,
+    words: Dawg,
+}
+
+static TYPING_INDEX: Mutex<Option<TypingIndex>> = Mutex::new(None);
+
+/// The letters which can follow `prefix` so that it can still become a playable dictionary word, as a string like
+/// "aeo", for a keyboard to enable or a typing assistant to suggest. Empty if `prefix` itself can't be played.
+/// The first call for a board indexes its playable words; later ones reuse the index.
+#[wasm_bindgen]
+pub fn next_letters(game_sides: Vec<String>, prefix: &str) -> Result<String> {
+    let dictionary = global_dictionary().ok_or(LetterBouncedError::DictionaryNotInitialized)?;
+    let board = Board::from_sides(game_sides)?;
+
+    let mut index = TYPING_INDEX.lock().unwrap();
+    let current = index
+        .as_ref()
+        .is_some_and(|index| index.sides == board.sides && Arc::ptr_eq(&index.dictionary, &dictionary));
+    if !current {
+        let words = board.playable_dictionary(&dictionary).to_dawg();
+        *index = Some(TypingIndex { sides: board.sides.clone(), dictionary, words });
+    }
+    let words = &index.as_ref().expect("the index was just built").words;
+    Ok(board.playable_continuations(prefix, words).into_iter().collect())
+}
+
+/// Split a board typed or linked as text, like "yfa,otk,lgw,rni" or "yfa-otk-lgw-rni", into its sides, checking
+/// it's a playable board.
+#[wasm_bindgen]
+pub fn parse_board_spec(spec: &str) -> Result<Vec<String>> {
+    Ok(Board::from_spec(spec)?.sides)
+}
+
+/// Board coordinates of each letter in a solution like "forklift-twangy", flattened as
+/// [side, position, side, position, ...], for drawing the path between letters.
+#[wasm_bindgen]
+pub fn solution_letter_path(game_sides: Vec<String>, solution: &str) -> Result<Vec<u32>> {
+    let words = solution.split('-').map(String::from).collect();
+    let (_, path) = letter_path(game_sides, words)?;
+    Ok(path
+        .into_iter()
+        .flat_map(|(side, position)| [side as u32, position as u32])
+        .collect())
+}
+
+/// Points on the standard square layout (corners at (0, 0) and (1, 1)) for each letter the words pass through,
+/// flattened as [x, y, x, y, ...], so the UI can animate the lines without knowing the board geometry.
+#[wasm_bindgen]
+pub fn solution_path(game_sides: Vec<String>, words: Vec<String>) -> Result<Vec<f64>> {
+    let (board, path) = letter_path(game_sides, words)?;
+    Ok(path
+        .into_iter()
+        .flat_map(|(side, position)| {
+            let (x, y) = board.letter_point(side, position);
+            [x, y]
+        })
+        .collect())
+}
+
+fn letter_path(game_sides: Vec<String>, words: Vec<String>) -> Result<(Board, Vec<(usize, usize)>)> {
+    let board = Board::from_sides(game_sides)?;
+    if words.is_empty() {
+        return Err(LetterBouncedError::InvalidPlay("An answer needs at least one word".to_string()));
+    }
+    // "forklift--twangy" splits into an empty word, which isn't one
+    let words = words.iter().map(|word| Word::try_new(word.to_lowercase(), 0)).collect::<Result<Vec<_>>>()?;
+    let solution = Solution::new(words);
+    let path = solution
+        .letter_path(&board)
+        .ok_or_else(|| LetterBouncedError::InvalidPlay(format!("'{}' can't be played on this board", solution)))?;
+    Ok((board, path))
+}
@@ -0,0 +1,124 @@
+use letter_bounced::analysis::{analyze, letter_coverage, par, solution_graph, BoardAnalysis, GraphOptions, Par};
+use letter_bounced::board::Board;
+use letter_bounced::dictionary::Dictionary;
+
+mod common;
+use common::sides_from_strs;
+
+#[test]
+fn test_analyze() {
+    let sides = sides_from_strs(&["yfa", "otk", "lgw", "rni"]);
+    let board = Board::from_sides(sides).unwrap();
+    let dictionary = Dictionary::from_text("forklift 14\ntwangy 12\nfiltration 15\nnag 20\ngawkily 10\nabode 20\n");
+
+    let analysis = analyze(&board, &dictionary);
+
+    assert_eq!(analysis.playable_words, 5);
+    assert_eq!(analysis.digraphs_total, 108);
+    let f = analysis.letters.iter().find(|l| l.letter == 'f').unwrap();
+    assert_eq!(f.words_starting, 2);
+    assert_eq!(f.words_containing, 2);
+    assert_eq!(analysis.solution_counts, vec![(1, 0), (2, 1), (3, 1)]);
+    assert_eq!(analysis.min_cover_words, Some(2));
+    assert!(!analysis.is_chaining_hard());
+    // Worked out from the solves analyze has already done
+    assert_eq!(analysis.letter_coverage, letter_coverage(&board, &dictionary));
+    assert_eq!(analysis.par(&board, &dictionary), Some(Par { words: 2, solutions: 1 }));
+}
+
+#[test]
+fn test_chaining_hard() {
+    let sides = sides_from_strs(&["yfa", "otk", "lgw", "rni"]);
+    let board = Board::from_sides(sides).unwrap();
+    // Two words cover every letter, but neither can follow the other
+    let dictionary = Dictionary::from_text("forklift 14\nwangy 12\n");
+
+    let analysis = analyze(&board, &dictionary);
+
+    assert_eq!(analysis.min_cover_words, Some(2));
+    assert_eq!(analysis.solution_counts, vec![(1, 0), (2, 0), (3, 0)]);
+    assert!(analysis.is_chaining_hard());
+    assert_eq!(analysis.par(&board, &dictionary), None);
+    // Nothing's known about four-word solutions, so a board which needs four words to cover isn't chaining-hard
+    let uncounted = BoardAnalysis { min_cover_words: Some(4), ..analysis };
+    assert!(!uncounted.is_chaining_hard());
+
+    // With a four-word chain, par has to search past the counts
+    let dictionary = Dictionary::from_text("fork 18\nkit 18\ntang 15\ngawkily 10\n");
+    let analysis = analyze(&board, &dictionary);
+    assert_eq!(analysis.min_cover_words, Some(3));
+    assert_eq!(analysis.par(&board, &dictionary), Some(Par { words: 4, solutions: 1 }));
+    assert_eq!(analysis.par(&board, &dictionary), par(&board, &dictionary));
+}
+
+#[test]
+fn test_par() {
+    let sides = sides_from_strs(&["yfa", "otk", "lgw", "rni"]);
+    let board = Board::from_sides(sides).unwrap();
+
+    let dictionary = Dictionary::from_text("forklift 14\ntwangy 12\nfiltration 15\nnag 20\ngawkily 10\n");
+    assert_eq!(par(&board, &dictionary), Some(Par { words: 2, solutions: 1 }));
+    // Without TWANGY, the three-word solution is the shortest
+    let dictionary = Dictionary::from_text("forklift 14\nfiltration 15\nnag 20\ngawkily 10\n");
+    assert_eq!(par(&board, &dictionary), Some(Par { words: 3, solutions: 1 }));
+    assert!(!par(&board, &dictionary).unwrap().is_sampled());
+    // Nothing covers Y
+    assert_eq!(par(&board, &Dictionary::from_text("forklift 14\n")), None);
+}
+
+#[test]
+fn test_bottleneck_letters() {
+    let sides = sides_from_strs(&["yfa", "otk", "lgw", "rni"]);
+    let board = Board::from_sides(sides).unwrap();
+    let dictionary = Dictionary::from_text("forklift 14\ntwangy 12\n");
+
+    let analysis = analyze(&board, &dictionary);
+
+    // Only T is shared between the two words
+    let bottlenecks = analysis.bottleneck_letters();
+    assert_eq!(bottlenecks.len(), 11);
+    assert!(!bottlenecks.contains(&'t'));
+}
+
+#[test]
+fn test_letter_coverage() {
+    let sides = sides_from_strs(&["yfa", "otk", "lgw", "rni"]);
+    let board = Board::from_sides(sides).unwrap();
+    let dictionary = Dictionary::from_text("forklift 14\ntwangy 12\nfiltration 15\nnag 20\ngawkily 10\nabode 20\n");
+
+    let coverage = letter_coverage(&board, &dictionary);
+
+    assert_eq!(coverage.len(), 12);
+    // The solutions are forklift-twangy, where rare twangy alone covers w, a, n, g and y, and
+    // filtration-nag-gawkily, where rarer gawkily alone covers w, k and y
+    let hard = |c| coverage.iter().find(|l| l.letter == c).unwrap().hard_in_solutions;
+    assert_eq!(coverage.iter().find(|l| l.letter == 'f').unwrap().words_containing, 2);
+    assert_eq!((hard('w'), hard('y'), hard('a'), hard('k')), (2, 2, 1, 1));
+    assert_eq!((hard('f'), hard('t'), hard('i')), (0, 0, 0));
+}
+
+#[test]
+fn test_solution_graph() {
+    let sides = sides_from_strs(&["yfa", "otk", "lgw", "rni"]);
+    let board = Board::from_sides(sides).unwrap();
+    // fly is playable, but nothing can come before or after it
+    let dictionary =
+        Dictionary::from_text("forklift 14\ntwangy 12\nfiltration 15\nnag 20\ngawkily 10\nabode 20\nfly 18\n");
+
+    let dot = solution_graph(&board, &dictionary, &GraphOptions::default());
+
+    assert!(dot.starts_with("digraph \"yfa,otk,lgw,rni\" {"));
+    assert!(dot.contains("\"forklift\" [tooltip=\"frequency 14\", style=bold];"));
+    assert!(dot.contains("\"fly\" [tooltip=\"frequency 18\"];"));
+    assert!(!dot.contains("abode"));
+    assert!(dot.contains("\"forklift\" -> \"twangy\";"));
+    assert!(dot.contains("\"filtration\" -> \"nag\";"));
+    assert!(dot.contains("\"nag\" -> \"gawkily\";"));
+    assert_eq!(dot.matches("->").count(), 3);
+
+    let options = GraphOptions { solution_words_only: true };
+    let dot = solution_graph(&board, &dictionary, &options);
+    assert!(!dot.contains("fly"));
+    assert!(dot.contains("\"forklift\" [tooltip=\"frequency 14\"];"));
+    assert_eq!(dot.matches("->").count(), 3);
+}
@@ -1,4 +1,6 @@
-use letter_bounced::board::Board;
+use letter_bounced::board::{Board, BoardOptions, GameRules, PlayabilityError};
+use letter_bounced::dictionary::Dictionary;
+use letter_bounced::error::LetterBouncedError;
 
 #[test]
 fn test_from_path() {
@@ -6,4 +8,179 @@ fn test_from_path() {
     
     assert_eq!(board.sides.len(), 4);
     assert!(!board.digraphs.is_empty());
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_spec_string_round_trip() {
+    let board = Board::from_spec_string("OTX-gmi-fle-aun").unwrap();
+    assert_eq!(board.sides, vec!["otx", "gmi", "fle", "aun"]);
+    assert_eq!(board.to_spec_string(), "otx-gmi-fle-aun");
+
+    assert!(Board::from_spec_string("otx-gmi-fle").is_err());
+}
+
+#[test]
+fn test_json_round_trip() {
+    let board = Board::from_spec_string("otx-gmi-fle-aun").unwrap();
+    let json = board.to_json();
+    assert_eq!(json, r#"{"sides":["otx","gmi","fle","aun"]}"#);
+    assert_eq!(Board::from_json(&json).unwrap().sides, board.sides);
+
+    match Board::from_json("[1, 2]") {
+        Err(LetterBouncedError::Board(message)) => assert!(message.starts_with("Invalid board JSON: "), "{}", message),
+        other => panic!("expected a board error, got {:?}", other),
+    }
+    assert!(matches!(
+        Board::from_json(r#"{"sides":["otx","gmi","fle","aut"]}"#),
+        Err(LetterBouncedError::Board(_))
+    ));
+}
+
+#[test]
+fn test_from_spec_separators() {
+    for spec in ["yfa,otk,lgw,rni", "YFA; OTK; LGW; RNI", " yfa otk\tlgw  rni ", "yfa-otk,lgw rni", "YFAOTKLGWRNI"] {
+        let board = Board::from_spec(spec).unwrap();
+        assert_eq!(board.sides, vec!["yfa", "otk", "lgw", "rni"], "parsing {:?}", spec);
+    }
+}
+
+#[test]
+fn test_from_spec_errors() {
+    let invalid_character = Board::from_spec("yfa,otk,lgw,rn1").unwrap_err().to_string();
+    assert!(invalid_character.contains("Invalid character '1'"));
+
+    assert!(matches!(Board::from_spec(" , "), Err(LetterBouncedError::Board(m)) if m.contains("empty")));
+    assert!(matches!(Board::from_spec("yfa,otk,lgw"), Err(LetterBouncedError::Board(m)) if m.contains("4 sides")));
+    assert!(matches!(Board::from_spec("yfaotklgwrn"), Err(LetterBouncedError::Board(m)) if m.contains("11 letters")));
+
+    // Without separators, a board of any side length splits evenly
+    assert_eq!(Board::parse_spec("abcdefgh").unwrap(), vec!["ab", "cd", "ef", "gh"]);
+}
+
+#[test]
+fn test_check_partial_sides() {
+    let options = BoardOptions::default();
+    let sides = |sides: &[&str]| sides.iter().map(|side| side.to_string()).collect::<Vec<_>>();
+    assert!(Board::check_partial_sides(&[], &options).is_ok());
+    assert!(Board::check_partial_sides(&sides(&["yfa", "otk"]), &options).is_ok());
+
+    let error = |partial: &[&str]| Board::check_partial_sides(&sides(partial), &options).unwrap_err().to_string();
+    assert!(error(&["yfa", "ot"]).contains("the right side has length 2"));
+    assert!(error(&["yfa", "otk", "lgy"]).contains("'y' found on the top side and the left side"));
+    assert!(error(&["yfa", "o1k"]).contains("Invalid character '1'"));
+    assert!(error(&["yfa", "otk", "lgw", "rni", "bcd"]).contains("exactly 4 sides"));
+
+    let duplicates = BoardOptions::new(GameRules::DuplicateLetters);
+    assert!(Board::check_partial_sides(&sides(&["yfa", "otk", "lgy"]), &duplicates).is_ok());
+}
+
+#[test]
+fn test_normalize_spec() {
+    let tidy = Board::normalize_spec("abc, def, ghi, jkl").unwrap();
+    assert_eq!(tidy.sides, vec!["abc", "def", "ghi", "jkl"]);
+    assert!(tidy.changes.is_empty());
+
+    let messy = Board::normalize_spec("\"YFA / otk | LgW\\rni.\"").unwrap();
+    assert_eq!(messy.sides, vec!["yfa", "otk", "lgw", "rni"]);
+    assert_eq!(messy.changes, vec!["Ignored '\"', '.'"]);
+
+    let pasted = Board::normalize_spec("y.f.a.o.t.k.l.g.w.r.n.i").unwrap();
+    assert_eq!(pasted.sides, vec!["yfa", "otk", "lgw", "rni"]);
+    assert_eq!(pasted.changes, vec!["Ignored '.'", "Split the 12 letters into 4 sides of 3"]);
+
+    // Digits are probably mistyped letters, so they're still errors
+    assert!(Board::normalize_spec("yfa,otk,lgw,rn1").is_err());
+}
+
+#[test]
+fn test_is_solvable() {
+    let board = Board::from_spec("yfa,otk,lgw,rni").unwrap();
+
+    let report = board.is_solvable(&Dictionary::from_text("forklift 20\ntwangy 20\n"));
+    assert!(!report.is_dead());
+    assert_eq!(report.reason(), None);
+    assert!(report.into_result().is_ok());
+
+    // "yfa" has Y, but all its letters share a side
+    let report = board.is_solvable(&Dictionary::from_text("forklift 20\ntwang 20\nyfa 20\n"));
+    assert_eq!(report.unplayable_letters, vec!['y']);
+    assert_eq!(
+        report.reason().unwrap(),
+        "This board has no solutions because Y only appears in unplayable words"
+    );
+
+    // Nothing follows "twang" or "tangy", and only one of them can end the answer
+    let report = board.is_solvable(&Dictionary::from_text("forklift 20\ntwang 20\ntangy 20\n"));
+    assert!(report.unplayable_letters.is_empty());
+    assert_eq!(report.stranded_letters, vec!['a', 'g', 'n', 'w', 'y']);
+    assert_eq!(
+        report.reason().unwrap(),
+        "This board has no solutions because A, G, N, W and Y only appear in words which nothing can follow, and no \
+         one word has them all"
+    );
+    assert!(matches!(report.into_result(), Err(LetterBouncedError::UnsolvableBoard(_))));
+}
+
+#[test]
+fn test_is_word_playable() {
+    let board = Board::from_spec("yfa,otk,lgw,rni").unwrap();
+    assert_eq!(board.is_word_playable("Forklift"), Ok(()));
+
+    let error = board.is_word_playable("fork-lift").unwrap_err();
+    assert_eq!(error, PlayabilityError::LetterNotOnBoard { letter: '-', position: 4 });
+    // Y and A share the top side, though the Q after them isn't on the board at all
+    let error = board.is_word_playable("yaq").unwrap_err();
+    assert_eq!(error, PlayabilityError::SameSide { first: 'y', second: 'a', position: 0 });
+    assert_eq!(error.to_string(), "YA are on the same side");
+    assert_eq!(error.letters(), 0..2);
+    let error = board.is_word_playable("wool").unwrap_err();
+    assert_eq!(error, PlayabilityError::DoubledLetter { letter: 'o', position: 1 });
+    assert_eq!(error.letters(), 1..3);
+    assert_eq!(board.is_word_playable(""), Err(PlayabilityError::Empty));
+
+    let options = BoardOptions::new(GameRules::DoubledLetters);
+    let board = Board::from_spec_with_options("yfa,otk,lgw,rni", options).unwrap();
+    assert_eq!(board.is_word_playable("wool"), Ok(()));
+}
+
+#[test]
+fn test_is_word_playable_with_repeated_letters() {
+    // A is on the top and right sides. XA needs the top one and AY the right one, so XAY can't be played, though
+    // each of its digraphs can.
+    let options = BoardOptions::new(GameRules::DuplicateLetters);
+    let board = Board::from_spec_with_options("ayb,axc,def,ghi", options).unwrap();
+    let error = board.is_word_playable("xay").unwrap_err();
+    assert_eq!(error, PlayabilityError::SameSide { first: 'a', second: 'y', position: 1 });
+    assert_eq!(board.is_word_playable("bay"), Ok(()));
+    assert_eq!(board.letter_path("day"), Some(vec![(2, 0), (1, 0), (0, 1)]));
+    assert_eq!(board.letter_path("xay"), None);
+
+    let dictionary = Dictionary::from_text("xay 20
+bay 20
+day 20
+");
+    let playable = board.playable_dictionary(&dictionary);
+    let words: Vec<&str> = playable.words().iter().map(|w| w.word.as_str()).collect();
+    assert_eq!(words, vec!["bay", "day"]);
+}
+
+#[test]
+fn test_playable_continuations() {
+    let board = Board::from_spec("yfa,otk,lgw,rni").unwrap();
+    let dictionary = Dictionary::from_text("forklift 20\nfork 20\nfort 20\nfoal 20\ntwangy 20\ntyre 20\n");
+    let words = dictionary.to_dawg();
+    let letters = |prefix: &str| board.playable_continuations(prefix, &words).into_iter().collect::<String>();
+
+    // TYRE has an E, which isn't on the board, so it doesn't make T an option; TWANGY does
+    assert_eq!(letters(""), "ft");
+    assert_eq!(letters("FO"), "ar");
+    assert_eq!(letters("for"), "kt");
+    assert_eq!(letters("fork"), "l");
+    // Y and A are on the same side, so nothing follows
+    assert_eq!(letters("ya"), "");
+    assert_eq!(letters("forklift"), "");
+
+    // A playable-only index gives the same answers
+    let playable = board.playable_dictionary(&dictionary).to_dawg();
+    assert_eq!(board.playable_continuations("fo", &playable), board.playable_continuations("fo", &words));
+}
@@ -1,4 +1,6 @@
-use letter_bounced::board::Board;
+use letter_bounced::board::{render_svg, Board, BoardOptions, GameRules, SvgOptions};
+use letter_bounced::dictionary::Digraph;
+use letter_bounced::error::LetterBouncedError;
 
 mod common;
 use common::sides_from_strs;
@@ -13,13 +15,27 @@ fn test_valid_game() {
     assert_eq!(game.digraphs.len(), 12 * 9); // 12 letters × 9 possible connections each
 }
 
+#[test]
+fn test_from_reader() {
+    let game = Board::from_reader(&b"YFA\notk\nlgw\nrni\n"[..], BoardOptions::default()).unwrap();
+    assert_eq!(game.sides, vec!["yfa", "otk", "lgw", "rni"]);
+
+    // Bad bytes are an error rather than a board cut short at them
+    let result = Board::from_reader(&b"yfa\not\xffk\nlgw\nrni\n"[..], BoardOptions::default());
+    assert!(matches!(result, Err(LetterBouncedError::Io(_))));
+    assert!(Board::from_reader(&b"\xc3\n\n\n\n"[..], BoardOptions::default()).is_err());
+    assert!(Board::from_reader(&b"\xc3\xa9\nb\nc\nd\n"[..], BoardOptions::default()).is_err());
+}
+
 #[test]
 fn test_invalid_number_of_sides() {
     let sides = sides_from_strs(&["abc", "def", "ghi"]); // Only 3 sides
     let result = Board::from_sides(sides);
 
     assert!(result.is_err());
-    assert!(result.unwrap_err().to_string().contains("exactly 4 sides"));
+    let error = result.unwrap_err();
+    assert!(matches!(error, LetterBouncedError::Board(_)));
+    assert!(error.to_string().contains("exactly 4 sides"));
 }
 
 #[test]
@@ -64,11 +80,11 @@ fn test_valid_digraphs_generation() {
     let game = Board::from_sides(sides).unwrap();
 
     // Test specific digraphs that should exist
-    assert!(game.digraphs.contains("ac"));
-    assert!(game.digraphs.contains("bd"));
+    assert!(game.digraphs.contains(&Digraph::from("ac")));
+    assert!(game.digraphs.contains(&Digraph::from("bd")));
     // Test digraphs that shouldn't exist (same side)
-    assert!(!game.digraphs.contains("ab"));
-    assert!(!game.digraphs.contains("ba"));
+    assert!(!game.digraphs.contains(&Digraph::from("ab")));
+    assert!(!game.digraphs.contains(&Digraph::from("ba")));
 }
 
 #[test]
@@ -94,3 +110,95 @@ fn test_from_sides_invalid_duplicate_letters() {
     assert!(result.is_err());
     assert!(result.unwrap_err().to_string().contains("Duplicate letter"));
 }
+
+#[test]
+fn test_duplicate_letters_allowed_with_options() {
+    let sides = sides_from_strs(&["abc", "def", "gha", "jkl"]);
+    let options = BoardOptions::new(GameRules::DuplicateLetters);
+
+    let game = Board::from_sides_with_options(sides, options).unwrap();
+
+    assert_eq!(game.sides[2], "gha");
+    // 'a' is on two different sides, so it can bounce to itself
+    assert!(game.digraphs.contains(&Digraph::from("aa")));
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_allow_duplicate_letters_still_works() {
+    let sides = sides_from_strs(&["abc", "def", "gha", "jkl"]);
+    let options = BoardOptions { allow_duplicate_letters: true, ..Default::default() };
+
+    assert_eq!(options.game_rules(), GameRules::DuplicateLetters);
+    let game = Board::from_sides_with_options(sides, options).unwrap();
+    assert_eq!(game.rules, GameRules::DuplicateLetters);
+}
+
+#[test]
+fn test_doubled_letters_rules() {
+    let sides = sides_from_strs(&["abc", "def", "gha", "jkl"]);
+    let options = BoardOptions::new(GameRules::DoubledLetters);
+
+    let game = Board::from_sides_with_options(sides, options).unwrap();
+
+    // Any letter can follow itself, not just the ones on two sides
+    assert!(game.digraphs.contains(&Digraph::from("bb")));
+    assert!(!game.digraphs.contains(&Digraph::from("bc")));
+    assert!(GameRules::DoubledLetters.allows_word("butt"));
+    assert!(!GameRules::Standard.allows_word("butt"));
+    assert_eq!("duplicate-letters".parse::<GameRules>().unwrap(), GameRules::DuplicateLetters);
+    assert!("anything-goes".parse::<GameRules>().is_err());
+}
+
+#[test]
+fn test_duplicate_letters_options_still_validate_structure() {
+    let sides = sides_from_strs(&["abc", "def", "gha"]);
+    let options = BoardOptions::new(GameRules::DuplicateLetters);
+
+    let result = Board::from_sides_with_options(sides, options);
+
+    assert!(result.is_err());
+    assert!(result.unwrap_err().to_string().contains("exactly 4 sides"));
+}
+
+#[test]
+fn test_render() {
+    let sides = sides_from_strs(&["abc", "def", "ghi", "jkl"]);
+    let game = Board::from_sides(sides).unwrap();
+    let expected = [
+        "            top",
+        "         A   B   C",
+        "       +-----------+",
+        "     G |           | D",
+        "left H |           | E right",
+        "     I |           | F",
+        "       +-----------+",
+        "         J   K   L",
+        "          bottom",
+    ];
+    assert_eq!(game.render(), expected.join("\n"));
+}
+
+#[test]
+fn test_render_svg() {
+    let sides = sides_from_strs(&["abc", "def", "ghi", "jkl"]);
+    let game = Board::from_sides(sides).unwrap();
+    let svg = render_svg(&game, &SvgOptions::default());
+
+    assert!(svg.starts_with("<svg"));
+    assert!(svg.trim_end().ends_with("</svg>"));
+    assert_eq!(svg.matches("<circle").count(), 12);
+    assert!(svg.contains(">A</text>"));
+    assert!(svg.contains(">L</text>"));
+}
+
+#[test]
+fn test_letter_point() {
+    let sides = sides_from_strs(&["abc", "def", "ghi", "jkl"]);
+    let game = Board::from_sides(sides).unwrap();
+
+    assert_eq!(game.letter_point(0, 0), (0.25, 0.0));
+    assert_eq!(game.letter_point(1, 1), (1.0, 0.5));
+    assert_eq!(game.letter_point(2, 2), (0.0, 0.75));
+    assert_eq!(game.letter_point(3, 0), (0.25, 1.0));
+}
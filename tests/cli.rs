@@ -0,0 +1,218 @@
+use std::path::Path;
+use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// Tests run in parallel, so each run gets its own dictionary file
+static RUNS: AtomicUsize = AtomicUsize::new(0);
+
+// The CLI, reading the config file from this directory instead of the user's
+fn command(config_home: &Path) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_letter-bounced"));
+    command.env("XDG_CONFIG_HOME", config_home);
+    command
+}
+
+// Run the CLI with a small dictionary, returning its exit code and stdout
+fn run(dictionary: &str, args: &[&str]) -> (i32, String) {
+    let run = RUNS.fetch_add(1, Ordering::Relaxed);
+    let path = std::env::temp_dir().join(format!("letter_bounced_cli_{}_{}.txt", std::process::id(), run));
+    std::fs::write(&path, dictionary).unwrap();
+    let output = command(Path::new("/no/such/config"))
+        .args(args)
+        .arg("--dictionary")
+        .arg(&path)
+        .output()
+        .unwrap();
+    std::fs::remove_file(&path).unwrap();
+    (output.status.code().unwrap(), String::from_utf8(output.stdout).unwrap())
+}
+
+#[test]
+fn test_exit_codes() {
+    let dictionary = "forklift 20\ntwangy 20\n";
+
+    assert_eq!(run(dictionary, &["yfa,otk,lgw,rni", "--quiet"]), (0, "forklift-twangy\n".to_string()));
+    assert_eq!(run(dictionary, &["solve", "yfa,otk,lgw,rni", "-q", "--max-words", "1"]), (2, String::new()));
+    assert_eq!(run("forklift 20\n", &["yfa,otk,lgw,rni", "-q"]).0, 2);
+    assert_eq!(run(dictionary, &["yfa,otk,lgw", "-q"]).0, 3);
+    assert_eq!(run(dictionary, &["generate", "--improve", "yfa,otk,lgw", "--target", "3"]).0, 3);
+    assert_eq!(run(dictionary, &["yfa,otk,lgw,rni", "--no-such-flag"]).0, 1);
+    assert_eq!(run(dictionary, &["yfa,otk,lgw,rni", "--timeout", "-1"]).0, 1);
+    assert_eq!(run(dictionary, &["yfa,otk,lgw,rni", "--timeout", "NaN"]).0, 1);
+    assert_eq!(run(dictionary, &["yfa,otk,lgw,rni", "--timeout", "1e300"]).0, 1);
+    assert_eq!(run(dictionary, &["yfa,otk,lgw,rni", "-q", "--timeout", "30"]).0, 0);
+
+    let output = command(Path::new("/no/such/config"))
+        .args(["yfa,otk,lgw,rni", "--dictionary", "/no/such/dictionary.txt"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(4));
+}
+
+#[test]
+fn test_quiet_spec_notes() {
+    let stderr = |args: &[&str]| {
+        let output = command(Path::new("/no/such/config"))
+            .args(["yfa otk lgw rni!", "--dictionary", "/no/such/dictionary.txt"])
+            .args(args)
+            .output()
+            .unwrap();
+        String::from_utf8(output.stderr).unwrap()
+    };
+    assert!(stderr(&[]).contains("Note: Ignored '!'"));
+    assert!(!stderr(&["-q"]).contains("Note:"));
+}
+
+#[test]
+fn test_nyt_wordlist() {
+    let args = ["yfa,otk,lgw,rni", "-q", "--wordlist", "nyt", "--nyt-words", "/no/such/list.txt"];
+
+    // Tags in the dictionary stand in for the missing list
+    assert_eq!(run("forklift 20 nyt\ntwangy 20 nyt\n", &args), (0, "forklift-twangy\n".to_string()));
+    assert_eq!(run("forklift 20 nyt\ntwangy 20\n", &args).0, 2);
+    assert_eq!(run("forklift 20\ntwangy 20\n", &args).0, 4);
+}
+
+#[test]
+fn test_default_nyt_words() {
+    let dir = std::env::temp_dir().join(format!("letter_bounced_nyt_{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("data")).unwrap();
+    std::fs::write(dir.join("words.txt"), "forklift 20\ntwangy 20\nfork 20\nkilt 20\nwangy 20\n").unwrap();
+    let page = r#"window.gameData = {"sides":["YFA","OTK","LGW","RNI"],"dictionary":["FORKLIFT","TWANGY"]};"#;
+    std::fs::write(dir.join("page.html"), page).unwrap();
+
+    // Gather the list where --wordlist nyt looks for it by default
+    let output = command(&dir).current_dir(&dir).args(["build-dict", "--nyt-words", "page.html"]).output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout.clone()).unwrap(), "forklift\ntwangy\n");
+    std::fs::write(dir.join("data/nyt-accepted.txt"), &output.stdout).unwrap();
+
+    let solve = |args: &[&str]| {
+        let output = command(&dir)
+            .current_dir(&dir)
+            .args(["yfa,otk,lgw,rni", "-q", "--dictionary", "words.txt"])
+            .args(args)
+            .output()
+            .unwrap();
+        String::from_utf8(output.stdout).unwrap()
+    };
+    assert_eq!(solve(&["--wordlist", "nyt"]), "forklift-twangy\n");
+    assert!(solve(&[]).lines().count() > 1);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_spelling() {
+    let dictionary = "forklift 20 british\ntwangy 20\n";
+    let args = |spelling| ["yfa,otk,lgw,rni", "-q", "--spelling", spelling, "--spelling-variants", "/no/such/list.txt"];
+
+    assert_eq!(run(dictionary, &args("both")), (0, "forklift-twangy\n".to_string()));
+    assert_eq!(run(dictionary, &args("uk")), (0, "forklift-twangy\n".to_string()));
+    assert_eq!(run(dictionary, &args("us")).0, 2);
+    // Without tags or a variants file, there's no telling which words to drop
+    assert_eq!(run("forklift 20\ntwangy 20\n", &args("us")).0, 4);
+}
+
+#[test]
+fn test_csv() {
+    let dictionary = "forklift 20\ntwangy 16\nnow 30\nwakf 5\nfragility 18\n";
+    let (code, output) = run(dictionary, &["yfa,otk,lgw,rni", "-q", "--format", "csv"]);
+    assert_eq!(code, 0);
+    assert_eq!(
+        output,
+        "rank,words,word_count,letters,min_frequency,score\n1,forklift-twangy,2,14,16,58\n2,now-wakf-fragility,3,16,5,24\n"
+    );
+    assert_eq!(run(dictionary, &["yfa,otk,lgw,rni", "--format", "csv", "--plain"]).0, 1);
+    assert_eq!(run(dictionary, &["yfa,otk,lgw,rni", "--format", "csv", "--explain-scores"]).0, 1);
+    assert_eq!(run(dictionary, &["yfa,otk,lgw,rni", "--format", "csv", "--show-letters"]).0, 1);
+
+    // Without -q there's still no board drawing in the way of the header
+    let (_, output) = run(dictionary, &["yfa,otk,lgw,rni", "--format", "csv"]);
+    assert!(output.starts_with("rank,words,"));
+}
+
+#[test]
+fn test_output_file() {
+    let dictionary = "forklift 20\ntwangy 16\nnow 30\nwakf 5\nfragility 18\nfoxglove 16\nequity 20\n";
+    let path = std::env::temp_dir().join(format!("letter_bounced_output_{}.txt", std::process::id()));
+    let output = path.to_str().unwrap();
+    std::fs::write(&path, "Week 12\n").unwrap();
+
+    // Nothing goes to stdout, and each board keeps one section however often it's solved
+    assert_eq!(run(dictionary, &["yfa,otk,lgw,rni", "-q", "-o", output, "--append"]), (0, String::new()));
+    assert_eq!(run(dictionary, &["vyq,fig,ote,xlu", "-q", "-o", output, "--append"]).0, 0);
+    assert_eq!(run(dictionary, &["yfa,otk,lgw,rni", "-q", "-o", output, "--append", "--max-words", "2"]).0, 0);
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "Week 12\n# board yfa-otk-lgw-rni\nforklift-twangy\n\n# board vyq-fig-ote-xlu\nfoxglove-equity\n"
+    );
+
+    // Without --append the file is replaced
+    assert_eq!(run(dictionary, &["yfa,otk,lgw,rni", "-q", "--format", "csv", "-o", output]).0, 0);
+    assert!(std::fs::read_to_string(&path).unwrap().starts_with("rank,words,"));
+    assert_eq!(run(dictionary, &["yfa,otk,lgw,rni", "--format", "csv", "-o", output, "--append"]).0, 1);
+
+    // Set covers go to the file too
+    assert_eq!(run(dictionary, &["yfa,otk,lgw,rni", "-q", "--set-cover", "-o", output]), (0, String::new()));
+    assert!(std::fs::read_to_string(&path).unwrap().contains("forklift twangy\n"));
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(run(dictionary, &["yfa,otk,lgw,rni", "--append"]).0, 1);
+}
+
+#[test]
+fn test_config_file() {
+    let dir = std::env::temp_dir().join(format!("letter_bounced_config_{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("letterbounced")).unwrap();
+    let dictionary = dir.join("words.txt");
+    std::fs::write(&dictionary, "forklift 20\ntwangy 20 nyt\nwork 20\nkif 20\nflagrantly 20\n").unwrap();
+    let config = format!("dictionary = {:?}\nmax-solutions = 1\n", dictionary.to_str().unwrap());
+    std::fs::write(dir.join("letterbounced/config.toml"), config).unwrap();
+
+    let solve = |args: &[&str]| {
+        let output = command(&dir).arg("yfa,otk,lgw,rni").arg("-q").args(args).output().unwrap();
+        (output.status.code().unwrap(), String::from_utf8(output.stdout).unwrap())
+    };
+    assert_eq!(solve(&[]), (0, "forklift-twangy\n".to_string()));
+    // The command line wins
+    assert_eq!(solve(&["--max-solutions", "5"]).1.lines().count(), 2);
+    assert_eq!(solve(&["--tag", "nyt"]).0, 2);
+
+    std::fs::write(dir.join("letterbounced/config.toml"), "max_solutions = 1\n").unwrap();
+    assert_eq!(solve(&[]).0, 1);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_completions() {
+    let output = command(Path::new("/no/such/config")).args(["completions", "bash"]).output().unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout).unwrap().contains("--max-solutions"));
+}
+
+#[test]
+fn test_dict_diff() {
+    let dir = std::env::temp_dir().join(format!("letter_bounced_diff_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("old.txt"), "and 31\ndojo 20\nfoxglove 16\nzax 4\n").unwrap();
+    std::fs::write(dir.join("new.txt"), "and 31\ndojo 21\nequity 18\nfoxglove 12\n").unwrap();
+
+    let diff = |args: &[&str]| {
+        let output = command(Path::new("/no/such/config"))
+            .arg("dict-diff")
+            .args([dir.join("old.txt"), dir.join("new.txt")])
+            .args(args)
+            .output()
+            .unwrap();
+        String::from_utf8(output.stdout).unwrap()
+    };
+    assert_eq!(
+        diff(&[]),
+        "4 words, now 4: 1 added, 1 removed, 2 changed frequency\n\
+         \nAdded:\n  + equity 18 (common)\n\
+         \nRemoved:\n  - zax 4 (obscure)\n\
+         \nChanged frequency:\n  ~ foxglove 16 -> 12 (-4, uncommon -> rare)\n  ~ dojo 20 -> 21 (+1)\n"
+    );
+    assert_eq!(diff(&["--min-shift", "2", "--summary"]), "4 words, now 4: 1 added, 1 removed, 1 changed frequency\n");
+    std::fs::remove_dir_all(&dir).unwrap();
+}
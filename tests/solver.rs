@@ -1,6 +1,10 @@
-use letter_bounced::board::Board;
-use letter_bounced::solver::Solver;
+use letter_bounced::board::{Board, BoardOptions, GameRules};
+use letter_bounced::solver::{NoSolutionReason, ScoringStrategy, Solver, SolverConfig};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::Duration;
 use letter_bounced::dictionary::Dictionary;
+use letter_bounced::error::LetterBouncedError;
 
 mod common;
 use common::sides_from_strs;
@@ -29,3 +33,514 @@ fn test_solver_basic() {
         .iter()
         .any(|s| s.to_string() == "filtration-nag-gawkily"));
 }
+
+#[test]
+fn test_solver_duplicate_letters() {
+    // 'a' appears on two sides; visiting either copy covers it. B shares a side with the top one, so BAD plays
+    // the right one.
+    let sides = sides_from_strs(&["ab", "ca", "de", "fg"]);
+    let options = BoardOptions::new(GameRules::DuplicateLetters);
+    let game = Board::from_sides_with_options(sides, options).unwrap();
+
+    let words = vec!["bad".to_string(), "dfcge".to_string()];
+    let wordlist = Dictionary::from_strings(words);
+    let solver = Solver::new(game, &wordlist, 10);
+    let solutions = solver.solve();
+
+    assert!(solutions.iter().any(|s| s.to_string() == "bad-dfcge"));
+}
+
+#[test]
+fn test_solver_conflicting_copies() {
+    // A is on the top and right sides. XAY needs the top copy after X and the right one before Y, and XA ends on
+    // the top copy, which B can't follow, so neither is played however playable each digraph is.
+    let sides = sides_from_strs(&["ayb", "axc", "def", "ghi"]);
+    let options = BoardOptions::new(GameRules::DuplicateLetters);
+    let game = Board::from_sides_with_options(sides, options).unwrap();
+
+    let words = ["xay", "ydbgecfhdi", "xa", "abdgecfhdiy", "ydx"].map(String::from).to_vec();
+    let wordlist = Dictionary::from_strings(words);
+    let solutions = Solver::new(game.clone(), &wordlist, 10).solve();
+
+    let found: Vec<String> = solutions.iter().map(|s| s.to_string()).collect();
+    assert_eq!(found, vec!["abdgecfhdiy-ydx"]);
+    assert!(solutions.iter().all(|s| s.letter_path(&game).is_some()));
+}
+
+#[test]
+fn test_solver_doubled_letters() {
+    // C is only on one side, so ACCEG needs a variant which lets a letter follow itself
+    let sides = sides_from_strs(&["ab", "cd", "ef", "gh"]);
+    let wordlist = Dictionary::from_strings(vec!["acceg".to_string(), "gbdfh".to_string()]);
+
+    let standard = Board::from_sides(sides.clone()).unwrap();
+    assert!(Solver::new(standard, &wordlist, 10).solve().is_empty());
+
+    let options = BoardOptions::new(GameRules::DoubledLetters);
+    let game = Board::from_sides_with_options(sides, options).unwrap();
+    let solutions = Solver::new(game.clone(), &wordlist, 10).solve();
+    assert_eq!(solutions.len(), 1);
+    assert_eq!(solutions[0].to_string(), "acceg-gbdfh");
+    let path = solutions[0].letter_path(&game).unwrap();
+    assert_eq!(&path[..3], &[(0, 0), (1, 0), (1, 0)]);
+}
+
+#[test]
+fn test_solver_timeout() {
+    let sides = sides_from_strs(&["yfa", "otk", "lgw", "rni"]);
+    let words = vec!["forklift".to_string(), "twangy".to_string()];
+    let wordlist = Dictionary::from_strings(words);
+
+    let config = SolverConfig::new().max_solutions(10).timeout(Duration::ZERO);
+    let solver = Solver::with_config(Board::from_sides(sides.clone()).unwrap(), &wordlist, config);
+    let outcome = solver.solve_with_outcome(None);
+    assert!(outcome.timed_out);
+    assert!(outcome.solutions.is_empty());
+
+    let config = SolverConfig::new().max_solutions(10).timeout(Duration::from_secs(60));
+    let solver = Solver::with_config(Board::from_sides(sides).unwrap(), &wordlist, config);
+    let outcome = solver.solve_with_outcome(None);
+    assert!(!outcome.timed_out);
+    assert_eq!(outcome.solutions.len(), 1);
+}
+
+#[test]
+fn test_solver_excluded_words() {
+    let sides = sides_from_strs(&["yfa", "otk", "lgw", "rni"]);
+    let game = Board::from_sides(sides).unwrap();
+    let words = vec![
+        "forklift".to_string(),
+        "twangy".to_string(),
+        "filtration".to_string(),
+        "nag".to_string(),
+        "gawkily".to_string(),
+    ];
+    let wordlist = Dictionary::from_strings(words);
+
+    let config = SolverConfig::new().max_solutions(10).excluded_words(["TWANGY"]);
+    let solver = Solver::with_config(game, &wordlist, config);
+    let solutions: Vec<String> = solver.solve().iter().map(|s| s.to_string()).collect();
+
+    assert_eq!(solutions, vec!["filtration-nag-gawkily"]);
+}
+
+#[test]
+fn test_solver_required_words() {
+    let sides = sides_from_strs(&["yfa", "otk", "lgw", "rni"]);
+    let game = Board::from_sides(sides).unwrap();
+    let words = vec![
+        "forklift".to_string(),
+        "twangy".to_string(),
+        "filtration".to_string(),
+        "nag".to_string(),
+        "gawkily".to_string(),
+    ];
+    let wordlist = Dictionary::from_strings(words);
+
+    let config = SolverConfig::new().max_solutions(10).required_words(["nag"]);
+    let solver = Solver::with_config(game, &wordlist, config);
+    let solutions: Vec<String> = solver.solve().iter().map(|s| s.to_string()).collect();
+
+    assert_eq!(solutions, vec!["filtration-nag-gawkily"]);
+}
+
+#[test]
+fn test_solve_from_prefix() {
+    let sides = sides_from_strs(&["yfa", "otk", "lgw", "rni"]);
+    let game = Board::from_sides(sides).unwrap();
+    let words = vec![
+        "forklift".to_string(),
+        "twangy".to_string(),
+        "filtration".to_string(),
+        "nag".to_string(),
+        "gawkily".to_string(),
+    ];
+    let wordlist = Dictionary::from_strings(words);
+    let solver = Solver::new(game, &wordlist, 10);
+
+    let completions = solver.solve_from_prefix(&["filtration"]).unwrap();
+    let completions: Vec<String> = completions.iter().map(|s| s.to_string()).collect();
+    assert_eq!(completions, vec!["nag-gawkily"]);
+
+    // Played words don't have to be in the dictionary
+    let completions = solver.solve_from_prefix(&["fit"]).unwrap();
+    assert!(completions.is_empty());
+
+    assert!(solver.solve_from_prefix(&["forklift", "nag"]).is_err());
+    assert!(solver.solve_from_prefix(&["zebra"]).is_err());
+}
+
+#[test]
+fn test_solve_by_word_count() {
+    let sides = sides_from_strs(&["yfa", "otk", "lgw", "rni"]);
+    let game = Board::from_sides(sides).unwrap();
+    let words = vec![
+        "forklift".to_string(),
+        "twangy".to_string(),
+        "filtration".to_string(),
+        "nag".to_string(),
+        "gawkily".to_string(),
+    ];
+    let wordlist = Dictionary::from_strings(words);
+
+    // With one solution allowed, the flat list stops at the two-word solution
+    let solver = Solver::new(game.clone(), &wordlist, 1);
+    assert_eq!(solver.solve().len(), 1);
+
+    let set = solver.solve_by_word_count(None);
+    assert!(set.with_word_count(1).is_empty());
+    assert_eq!(set.with_word_count(2)[0].to_string(), "forklift-twangy");
+    assert_eq!(set.with_word_count(3)[0].to_string(), "filtration-nag-gawkily");
+    assert!(set.with_word_count(0).is_empty());
+    assert_eq!(set.len(), 2);
+    assert!(!set.timed_out());
+}
+
+#[test]
+fn test_config_validation() {
+    assert!(SolverConfig::new().start_letter('g').validate().is_ok());
+
+    let conflicting = SolverConfig::new().required_words(["nag"]).excluded_words(["nag"]);
+    assert!(matches!(conflicting.validate(), Err(LetterBouncedError::SolverConfig(_))));
+
+    let too_many = SolverConfig::new().required_words(["a", "b", "c", "d", "e"]);
+    assert!(matches!(too_many.validate(), Err(LetterBouncedError::SolverConfig(_))));
+
+    let not_a_letter = SolverConfig::new().start_letter('7');
+    assert!(matches!(not_a_letter.validate(), Err(LetterBouncedError::SolverConfig(_))));
+
+    let no_openings = SolverConfig::new().max_per_first_word(0);
+    assert!(matches!(no_openings.validate(), Err(LetterBouncedError::SolverConfig(_))));
+}
+
+#[test]
+fn test_config_shapes_results() {
+    let sides = sides_from_strs(&["vyq", "fig", "ote", "xlu"]);
+    let game = Board::from_sides(sides).unwrap();
+    let wordlist = Dictionary::from_text("eye 30\nfugue 25\nequivoque 25\nexility 25\nfoxglove 10\nequity 10\n");
+    let solve = |config: SolverConfig| -> Vec<String> {
+        let solver = Solver::with_config(game.clone(), &wordlist, config);
+        solver.solve().iter().map(|s| s.to_string()).collect()
+    };
+
+    // The three-word solution uses more common words, so it scores higher
+    assert_eq!(
+        solve(SolverConfig::new()),
+        vec!["fugue-equivoque-exility", "foxglove-equity", "foxglove-equivoque-exility"]
+    );
+    assert_eq!(
+        solve(SolverConfig::new().scoring(ScoringStrategy::FewestWords)),
+        vec!["foxglove-equity", "fugue-equivoque-exility", "foxglove-equivoque-exility"]
+    );
+    assert_eq!(solve(SolverConfig::new().max_words(2)), vec!["foxglove-equity"]);
+    // foxglove (10) is 15 points rarer than equivoque and exility (25)
+    assert_eq!(
+        solve(SolverConfig::new().max_rarity_spread(10)),
+        vec!["fugue-equivoque-exility", "foxglove-equity"]
+    );
+    // Both FOXGLOVE solutions share an opening; the shorter one is found first
+    assert_eq!(
+        solve(SolverConfig::new().max_per_first_word(1)),
+        vec!["fugue-equivoque-exility", "foxglove-equity"]
+    );
+
+    let with_redundant = solve(SolverConfig::new().filter_redundant(false));
+    assert!(with_redundant.contains(&"foxglove-eye-equity".to_string()));
+}
+
+#[test]
+fn test_fewest_letters_scoring() {
+    let sides = sides_from_strs(&["vyq", "fig", "ote", "xlu"]);
+    let game = Board::from_sides(sides).unwrap();
+    let wordlist = Dictionary::from_text("fugue 25\nequivoque 25\nexility 25\nfoxglove 10\nequity 10\n");
+    let config = SolverConfig::new().scoring(ScoringStrategy::FewestLetters);
+    let solutions = Solver::with_config(game, &wordlist, config).solve();
+
+    let letters: Vec<usize> = solutions.iter().map(|s| s.letter_count()).collect();
+    assert_eq!(letters, vec![14, 21, 24]);
+}
+
+#[test]
+fn test_elegance_scoring() {
+    let sides = sides_from_strs(&["yfa", "otk", "lgw", "rni"]);
+    let game = Board::from_sides(sides).unwrap();
+    // WORK-KIF-FLAGRANTLY scores higher, but FORKLIFT-TWANGY only revisits F
+    let wordlist = Dictionary::from_text("work 20\nkif 20\nflagrantly 20\nforklift 10\ntwangy 10\n");
+    let config = SolverConfig::new().scoring(ScoringStrategy::Elegance);
+    let solutions = Solver::with_config(game, &wordlist, config).solve();
+
+    let revisits: Vec<(String, usize)> = solutions.iter().map(|s| (s.to_string(), s.revisited_letters())).collect();
+    assert_eq!(
+        revisits,
+        vec![("forklift-twangy".to_string(), 1), ("work-kif-flagrantly".to_string(), 3)]
+    );
+}
+
+#[test]
+fn test_incremental_solve_matches_solve() {
+    let sides = sides_from_strs(&["yfa", "otk", "lgw", "rni"]);
+    let game = Board::from_sides(sides).unwrap();
+    let wordlist = Dictionary::from_text(
+        "work 20\nkif 20\nflagrantly 20\nforklift 10\ntwangy 10\nfrontal 12\nnag 12\ngawkily 8\n",
+    );
+    let solver = Solver::new(game, &wordlist, 100);
+
+    let mut incremental = solver.solve_incrementally();
+    let mut batches = 0;
+    let mut streamed = Vec::new();
+    while let Some(batch) = incremental.next_batch(2, usize::MAX) {
+        batches += 1;
+        streamed.extend(batch.iter().map(|s| s.to_string()));
+    }
+    assert!(batches > 1);
+    assert!(!incremental.timed_out());
+
+    let mut solved: Vec<String> = solver.solve().iter().map(|s| s.to_string()).collect();
+    solved.sort();
+    streamed.sort();
+    assert_eq!(streamed, solved);
+
+    // A tiny node budget ends every batch after a single first word, and still finds the same solutions
+    let mut incremental = solver.solve_incrementally();
+    let mut budgeted_batches = 0;
+    let mut streamed = Vec::new();
+    while let Some(batch) = incremental.next_batch(usize::MAX, 1) {
+        budgeted_batches += 1;
+        streamed.extend(batch.iter().map(|s| s.to_string()));
+    }
+    assert!(budgeted_batches > batches);
+    streamed.sort();
+    assert_eq!(streamed, solved);
+}
+
+// Drive a future whose awaits never really wait, without needing a runtime
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    let mut future = std::pin::pin!(future);
+    let mut context = std::task::Context::from_waker(std::task::Waker::noop());
+    loop {
+        if let std::task::Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+    }
+}
+
+#[test]
+fn test_solve_async() {
+    let sides = sides_from_strs(&["yfa", "otk", "lgw", "rni"]);
+    let game = Board::from_sides(sides).unwrap();
+    let wordlist = Dictionary::from_text("work 20\nkif 20\nflagrantly 20\nforklift 10\ntwangy 10\n");
+    let solver = Solver::new(game, &wordlist, 100);
+
+    let mut yields = 0;
+    let mut progress = Vec::new();
+    let outcome = block_on(solver.solve_async(
+        None,
+        || {
+            yields += 1;
+            std::future::ready(())
+        },
+        |batch, found| progress.push((batch.len(), found)),
+    ));
+    assert!(yields > 1);
+    assert!(!outcome.timed_out);
+    assert_eq!(outcome.solutions, solver.solve());
+    // Only slices which found something are reported, with a running total
+    assert!(progress.iter().all(|&(new, _)| new > 0));
+    assert_eq!(progress.last().map(|&(_, found)| found), Some(outcome.solutions.len()));
+    assert_eq!(progress.iter().map(|&(new, _)| new).sum::<usize>(), outcome.solutions.len());
+
+    // Cancelled after the first slice, which only looks for one-word solutions
+    let cancel_flag = Arc::new(AtomicBool::new(true));
+    let outcome = block_on(solver.solve_async(Some(cancel_flag), || std::future::ready(()), |_, _| {}));
+    assert!(outcome.solutions.is_empty());
+}
+
+#[test]
+fn test_validate_solution() {
+    let sides = sides_from_strs(&["yfa", "otk", "lgw", "rni"]);
+    let game = Board::from_sides(sides).unwrap();
+    let wordlist = Dictionary::from_text("forklift 14\ntwangy 12\ntwang 20\nfork 20\nkilt 15\n");
+    let solver = Solver::new(game, &wordlist, 10);
+
+    let solution = solver.validate_solution(&["FORKLIFT", "twangy"]).unwrap();
+    assert_eq!(solution.to_string(), "forklift-twangy");
+
+    match solver.validate_solution(&["forklift", "twangi"]) {
+        Err(LetterBouncedError::UnknownWord { word, suggestions }) => {
+            assert_eq!(word, "twangi");
+            assert_eq!(suggestions, vec!["twang", "twangy"]);
+        }
+        other => panic!("expected an unknown word, got {:?}", other),
+    }
+    let message = solver.validate_solution(&["forklift", "twangi"]).unwrap_err().to_string();
+    assert!(message.ends_with("Did you mean twang, twangy?"), "{}", message);
+
+    // A letter which isn't on the board still gets suggestions
+    match solver.validate_solution(&["forklift", "twangs"]) {
+        Err(LetterBouncedError::UnknownWord { suggestions, .. }) => assert_eq!(suggestions, vec!["twang", "twangy"]),
+        other => panic!("expected an unknown word, got {:?}", other),
+    }
+    // but with nothing close, the letter is what's reported
+    assert!(matches!(
+        solver.validate_solution(&["forklift", "tzzzzz"]),
+        Err(LetterBouncedError::InvalidPlay(m)) if m.contains("not on the board")
+    ));
+
+    assert!(matches!(
+        solver.validate_solution(&["forklift", "twang"]),
+        Err(LetterBouncedError::InvalidPlay(m)) if m == "The answer doesn't use y"
+    ));
+    assert!(matches!(solver.validate_solution(&["fork", "twangy"]), Err(LetterBouncedError::InvalidPlay(_))));
+    assert!(matches!(solver.validate_solution(&[]), Err(LetterBouncedError::InvalidPlay(_))));
+}
+
+#[test]
+fn test_explain_no_solutions() {
+    let board = Board::from_spec("yfa,otk,lgw,rni").unwrap();
+    let dictionary = Dictionary::from_text("forklift 30\ntwangy 5\n");
+    let explain = |dictionary: &Dictionary, config: SolverConfig| {
+        let solver = Solver::with_config(board.clone(), dictionary, config);
+        assert!(solver.solve().is_empty());
+        solver.explain_no_solutions()
+    };
+
+    assert_eq!(
+        explain(&dictionary, SolverConfig::new().max_words(1)),
+        NoSolutionReason::MaxWordsTooLow { max_words: 1, needed: 2 }
+    );
+    assert_eq!(
+        explain(&dictionary, SolverConfig::new().max_solutions(0)),
+        NoSolutionReason::NoSolutionsRequested
+    );
+    assert_eq!(
+        explain(&dictionary, SolverConfig::new().required_words(["zax"])),
+        NoSolutionReason::RequiredWordUnplayable("zax".to_string())
+    );
+    assert_eq!(
+        explain(&dictionary, SolverConfig::new().start_letter('y')),
+        NoSolutionReason::NoWordStartsWith('y')
+    );
+    assert_eq!(
+        explain(&dictionary, SolverConfig::new().max_rarity_spread(3)),
+        NoSolutionReason::FilteredOut
+    );
+
+    let reason = explain(&Dictionary::from_text("forklift 20\n"), SolverConfig::new());
+    assert_eq!(reason, NoSolutionReason::UncoverableLetters(vec!['a', 'g', 'n', 'w', 'y']));
+    assert_eq!(reason.to_string(), "No playable word uses AGNWY");
+
+    // Only one of "twang" and "tangy" can end the chain
+    let dead = Dictionary::from_text("forklift 20\ntwang 20\ntangy 20\n");
+    assert_eq!(
+        explain(&dead, SolverConfig::new()),
+        NoSolutionReason::NoCoveringChain { max_words: 6 }
+    );
+}
+
+#[test]
+fn test_tag_filters() {
+    let sides = sides_from_strs(&["yfa", "otk", "lgw", "rni"]);
+    let game = Board::from_sides(sides).unwrap();
+    let wordlist = Dictionary::from_text("work 20 common\nkif 20 scrabble-only\nflagrantly 20 common\nforklift 10 common\ntwangy 10\n");
+    let solve = |config: SolverConfig| -> Vec<String> {
+        Solver::with_config(game.clone(), &wordlist, config).solve().iter().map(|s| s.to_string()).collect()
+    };
+
+    assert_eq!(solve(SolverConfig::new()), vec!["work-kif-flagrantly", "forklift-twangy"]);
+    assert_eq!(solve(SolverConfig::new().excluded_tags(["scrabble-only"])), vec!["forklift-twangy"]);
+    assert!(solve(SolverConfig::new().required_tags(["common"])).is_empty());
+
+    let conflicting = SolverConfig::new().required_tags(["common"]).excluded_tags(["Common"]);
+    assert!(matches!(conflicting.validate(), Err(LetterBouncedError::SolverConfig(_))));
+}
+
+#[test]
+fn test_layered_dictionary_solutions() {
+    let sides = sides_from_strs(&["yfa", "otk", "lgw", "rni"]);
+    let game = Board::from_sides(sides).unwrap();
+    let personal = Dictionary::from_text("twangy 25\n");
+    let base = Dictionary::from_text("forklift 20\ntwangy 10\n");
+    let solutions = Solver::new(game, &Dictionary::layered(vec![personal, base]), 10).solve();
+
+    let layers: Vec<(&str, i8, u8)> = solutions[0].words.iter().map(|w| (w.word.as_str(), w.frequency, w.layer)).collect();
+    assert_eq!(layers, vec![("forklift", 20, 1), ("twangy", 25, 0)]);
+}
+
+#[test]
+fn test_minimal_covers() {
+    let sides = sides_from_strs(&["yfa", "otk", "lgw", "rni"]);
+    let board = Board::from_sides(sides).unwrap();
+    // forklift and wangy cover the board between them, but don't chain
+    let dictionary = Dictionary::from_text("fly 18\nforklift 14\nwangy 12\ntwangy 10\n");
+
+    let covers = Solver::new(board.clone(), &dictionary, 10).minimal_covers();
+    let covers: Vec<Vec<&str>> = covers.iter().map(|cover| cover.iter().map(|w| w.word.as_str()).collect()).collect();
+    assert_eq!(covers, vec![vec!["forklift", "wangy"], vec!["forklift", "twangy"]]);
+
+    assert_eq!(Solver::new(board.clone(), &dictionary, 1).minimal_covers().len(), 1);
+
+    // Nothing covers T without forklift
+    let dictionary = Dictionary::from_text("fly 18\nwangy 12\n");
+    assert!(Solver::new(board, &dictionary, 10).minimal_covers().is_empty());
+}
+
+#[test]
+fn test_collapse_plurals() {
+    let sides = sides_from_strs(&["sat", "rlo", "nip", "egh"]);
+    let board = Board::from_sides(sides).unwrap();
+    let dictionary = Dictionary::from_text("triangles 20\nshop 18\nshops 10\n");
+
+    assert_eq!(Solver::new(board.clone(), &dictionary, 10).solve().len(), 2);
+
+    let config = SolverConfig::new().max_solutions(10).collapse_plurals(true);
+    let solutions = Solver::with_config(board.clone(), &dictionary, config).solve();
+    let solutions: Vec<String> = solutions.iter().map(|s| s.to_string()).collect();
+    assert_eq!(solutions, vec!["triangles-shop"]);
+
+    // The variant kept is the one the scoring strategy prefers, not the one found first
+    let dictionary = Dictionary::from_text("triangles 20\nshops 18\nshop 10\n");
+    let collapsed = |scoring: ScoringStrategy| {
+        let config = SolverConfig::new().max_solutions(10).collapse_plurals(true).scoring(scoring);
+        let solver = Solver::with_config(board.clone(), &dictionary, config);
+        let async_outcome = block_on(solver.solve_async(None, || std::future::ready(()), |_, _| {}));
+        assert_eq!(async_outcome.solutions, solver.solve());
+        solver.solve().iter().map(|s| s.to_string()).collect::<Vec<_>>()
+    };
+    assert_eq!(collapsed(ScoringStrategy::Score), vec!["triangles-shops"]);
+    assert_eq!(collapsed(ScoringStrategy::FewestLetters), vec!["triangles-shop"]);
+}
+
+#[test]
+fn test_max_per_length() {
+    let sides = sides_from_strs(&["sat", "rlo", "nip", "egh"]);
+    let board = Board::from_sides(sides).unwrap();
+    let dictionary = Dictionary::from_text("people 20\nethnographies 15\nelse 20\nare 20\nelephant 18\ntigons 10\n");
+    let words = |config: SolverConfig| -> Vec<String> {
+        let solutions = Solver::with_config(board.clone(), &dictionary, config).solve();
+        solutions.iter().map(|s| s.to_string()).collect()
+    };
+
+    // The two-word solutions fill max_solutions, so three words are never tried
+    let config = SolverConfig::new().max_solutions(2).max_words(3);
+    assert_eq!(words(config), vec!["people-ethnographies", "else-ethnographies"]);
+
+    // With a quota for each word count, every count gets a look in
+    let config = SolverConfig::new().max_solutions(2).max_words(3).max_per_length(1);
+    assert_eq!(words(config), vec!["people-ethnographies", "are-elephant-tigons"]);
+
+    // but the ranked list is still cut to max_solutions
+    let config = SolverConfig::new().max_solutions(1).max_words(3).max_per_length(1);
+    assert_eq!(words(config), vec!["people-ethnographies"]);
+
+    // The quota holds for the incremental and per word count solves too
+    let config = SolverConfig::new().max_solutions(2).max_words(3).max_per_length(1);
+    let solver = Solver::with_config(board.clone(), &dictionary, config);
+    let outcome = block_on(solver.solve_async(None, || std::future::ready(()), |_, _| {}));
+    let solutions: Vec<String> = outcome.solutions.iter().map(|s| s.to_string()).collect();
+    assert_eq!(solutions, vec!["people-ethnographies", "are-elephant-tigons"]);
+    let set = solver.solve_by_word_count(None);
+    assert_eq!(set.with_word_count(2).len(), 1);
+    assert_eq!(set.with_word_count(3).len(), 1);
+
+    assert!(SolverConfig::new().max_per_length(0).validate().is_err());
+}
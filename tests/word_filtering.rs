@@ -18,7 +18,7 @@ fn test_playable_words() {
     let dictionary = Dictionary::from_strings(word_strings);
     let playable_dictionary = board.playable_dictionary(&dictionary);
 
-    let playable_words: Vec<String> = playable_dictionary.words.iter().map(|w| w.word.clone()).collect();
+    let playable_words: Vec<String> = playable_dictionary.words().iter().map(|w| w.word.clone()).collect();
     assert!(playable_words.contains(&"dojo".to_string()));
     assert!(!playable_words.contains(&"abode".to_string()));
     assert!(playable_words.contains(&"joke".to_string()));
@@ -25,7 +25,8 @@ let worker: Worker | null = null;
 
 export function initializeSolverWorker(dictionaryData: Uint8Array): void {
   worker = new Worker(
-    new URL('../workers/solver-worker.ts', import.meta.url),
+    // Written by build-web.sh alongside the wasm package
+    new URL('../pkg/solver-worker.js', import.meta.url),
     { type: 'module' }
   );
 
@@ -38,6 +39,13 @@ export function initializeSolverWorker(dictionaryData: Uint8Array): void {
       solverReady.set(true);
     }
 
+    if (type === 'PROGRESS') {
+      // Partial results arrive unsorted, so only the count is shown until the solve completes
+      if (solveId === currentSolveId && totalCount !== undefined) {
+        solveStats.set({ totalCount, duration: null });
+      }
+    }
+
     if (type === 'COMPLETE') {
       console.log(`[Store] COMPLETE check: solveId=${solveId}, currentSolveId=${currentSolveId}, match=${solveId === currentSolveId}, solutions count=${receivedSolutions?.length}`);
       if (solveId === currentSolveId && receivedSolutions) {